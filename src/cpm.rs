@@ -0,0 +1,129 @@
+use std::io::Write;
+
+use crate::cpu::bus::Bus;
+use crate::cpu::cpu::CPU;
+use crate::cpu::error::ExecutionError;
+use crate::cpu::memory::Memory;
+use crate::cpu::Z80::Z80;
+
+// Just enough of CP/M to run .COM programs that only talk to the console, such
+// as the ZEXDOC and ZEXALL instruction exercisers. The program is loaded at the
+// start of the TPA with a return address of $0000 on the stack, a jump to
+// $0000 (warm boot) ends the run
+const TPA: u16 = 0x0100;
+// CALL 5 jumps to the BDOS entry point, whose address at $0006 also tells
+// programs where the TPA ends. The entry point is a RET, the calls are
+// serviced from here when PC reaches it
+const BDOS_CALL: u16 = 0x0005;
+const BDOS_ENTRY: u16 = 0xFE00;
+
+const CONSOLE_OUTPUT: u8 = 2;
+const PRINT_STRING: u8 = 9;
+
+// How a program run ended, with the T-states it took
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    // Jump to $0000, the normal way back to CP/M
+    WarmBoot { cycles: u64 },
+    // HALT at pc. Nothing here raises interrupts, so the program can never go on
+    Halted { pc: u16, cycles: u64 },
+}
+
+// Runs the program to its end
+pub fn run(program: &[u8], console: &mut impl Write) -> Result<Exit, ExecutionError> {
+    let mut memory: Memory = Memory::new();
+    for (offset, byte) in program.iter().enumerate() {
+        memory.write(TPA as u32 + offset as u32, *byte);
+    }
+    memory.write(BDOS_CALL as u32, 0xC3);
+    memory.write(BDOS_CALL as u32 + 1, BDOS_ENTRY as u8);
+    memory.write(BDOS_CALL as u32 + 2, (BDOS_ENTRY >> 8) as u8);
+    memory.write(BDOS_ENTRY as u32, 0xC9);
+
+    let mut cpu: Z80 = Z80::new();
+    cpu.reset(&mut memory);
+    cpu.set_pc(TPA);
+    cpu.set_sp(BDOS_ENTRY - 2);
+    memory.write(BDOS_ENTRY as u32 - 2, 0x00);
+    memory.write(BDOS_ENTRY as u32 - 1, 0x00);
+
+    loop {
+        match cpu.pc() {
+            0x0000 => return Ok(Exit::WarmBoot { cycles : cpu.total_cycles() }),
+            BDOS_ENTRY => bdos(&cpu, &memory, console),
+            _ => {}
+        }
+        // PC has already moved past the HALT
+        if cpu.waiting() {
+            return Ok(Exit::Halted { pc : cpu.pc().wrapping_sub(1), cycles : cpu.total_cycles() });
+        }
+        cpu.step(&mut memory)?;
+    }
+}
+
+// Function number in C, parameter in E or DE
+fn bdos(cpu: &Z80, memory: &Memory, console: &mut impl Write) {
+    let function: u8 = cpu.bc() as u8;
+    let text: Vec<u8> = match function {
+        CONSOLE_OUTPUT => vec![cpu.de() as u8],
+        PRINT_STRING => (0..=u16::MAX)
+            .map(|offset| memory.peek(cpu.de().wrapping_add(offset) as u32))
+            .take_while(|character| *character != b'$')
+            .collect(),
+        _ => return,
+    };
+    // Console output is best effort, the program has no way to see a failure
+    let _ = console.write_all(&text);
+    let _ = console.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, Exit};
+
+    #[test]
+    fn prints_and_returns_to_cp_m() {
+        // LD C,9 ; LD DE,text ; CALL 5 ; LD C,2 ; LD E,'!' ; CALL 5 ; RET
+        let program: [u8; 21] = [
+            0x0E, 0x09, 0x11, 0x10, 0x01, 0xCD, 0x05, 0x00, 0x0E, 0x02, 0x1E, b'!', 0xCD, 0x05, 0x00, 0xC9,
+            b'H', b'i', b'$', 0x00, 0x00,
+        ];
+        let mut console: Vec<u8> = Vec::new();
+        let exit: Exit = run(&program, &mut console).unwrap();
+        assert!(matches!(exit, Exit::WarmBoot { .. }));
+        assert_eq!(console, b"Hi!");
+    }
+
+    #[test]
+    fn halt_ends_the_run() {
+        // NOP ; HALT
+        let mut console: Vec<u8> = Vec::new();
+        let exit: Exit = run(&[0x00, 0x76], &mut console).unwrap();
+        assert!(matches!(exit, Exit::Halted { pc : 0x0101, .. }), "{:?}", exit);
+    }
+
+    // The instruction exercisers are not redistributed here. Put zexdoc.com and zexall.com
+    // in roms/ and run these with cargo test --release -- --ignored, they take a few minutes
+    fn exercise(name: &str) {
+        let path: String = format!("{}/roms/{}", env!("CARGO_MANIFEST_DIR"), name);
+        let program: Vec<u8> = std::fs::read(&path).unwrap_or_else(|error| panic!("{}: {}", path, error));
+        let mut console: Vec<u8> = Vec::new();
+        let exit: Exit = run(&program, &mut console).unwrap();
+        let output: String = String::from_utf8_lossy(&console).into_owned();
+        assert!(matches!(exit, Exit::WarmBoot { .. }), "{:?}\n{}", exit, output);
+        assert!(!output.lines().any(|line| line.contains("ERROR")), "{}", output);
+        assert!(output.contains("Tests complete"), "{}", output);
+    }
+
+    #[test]
+    #[ignore]
+    fn zexdoc() {
+        exercise("zexdoc.com");
+    }
+
+    #[test]
+    #[ignore]
+    fn zexall() {
+        exercise("zexall.com");
+    }
+}
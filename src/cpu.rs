@@ -1,3 +1,7 @@
 pub mod cpu;
 pub mod MOS6502;
-pub mod memory;
\ No newline at end of file
+pub mod WDC65C816;
+pub mod Z80;
+pub mod memory;
+pub mod bus;
+pub mod error;
\ No newline at end of file
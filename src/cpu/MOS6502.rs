@@ -1,4 +1,7 @@
 mod processor_status;
+pub mod addressing;
+pub mod opcodes;
+pub mod quirks;
 
 mod load_MOS6502;
 mod store_MOS6502;
@@ -6,16 +9,84 @@ mod transfer_MOS6502;
 mod stack_MOS6502;
 mod logical_MOS6502;
 mod arithmetic_MOS6502;
+mod branch_MOS6502;
+mod jump_MOS6502;
+mod interrupt_MOS6502;
+mod shift_MOS6502;
+mod incdec_MOS6502;
+mod flag_MOS6502;
+mod illegal_MOS6502;
+mod cycle_MOS6502;
+mod cmos_MOS6502;
+mod io_port_MOS6502;
 
 use processor_status::ProcessorStatus;
+use cycle_MOS6502::CycleState;
+use io_port_MOS6502::{IoPort, PORT_PINS};
+use addressing::AddressingMode;
+use opcodes::{opcode, Opcode, OPCODES, OPCODES_65C02};
+use quirks::Quirks;
 
-use super::memory::Memory; 
-use super::cpu::CPU;
+use super::bus::Bus; 
+#[cfg(test)]
+use super::bus::RecordingBus;
+#[cfg(test)]
+use super::memory::Memory;
+use super::cpu::{Register, StepInfo, CPU};
+use super::error::ExecutionError;
 
 use num_enum::TryFromPrimitive;
 use num_enum::IntoPrimitive;
 use std::convert::TryFrom;
 
+// What to do with opcodes that are not part of the documented instruction set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownOpcodePolicy {
+    // Stop and report ExecutionError::UnknownOpcode, PC is left on the opcode
+    Error,
+    // Skip as a NOP, taking the length and base cycles from the opcode table
+    Nop,
+    // Execute the undocumented NMOS behaviour, including JAM
+    Undocumented,
+}
+
+// Members of the 6502 family emulated by this core
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    // Original NMOS 6502
+    NMOS6502,
+    // WDC 65C02, with the Rockwell bit instructions
+    CMOS65C02,
+    // Ricoh 2A03, the NTSC NES CPU: an NMOS 6502 with the decimal adder disconnected
+    RP2A03,
+    // Ricoh 2A07, the PAL version of the 2A03
+    RP2A07,
+    // MOS 6510, the C64 CPU: an NMOS 6502 with an I/O port at $0000/$0001
+    MOS6510,
+    // MOS 6507, the Atari 2600 CPU: 13 address lines and no IRQ or NMI pins
+    MOS6507,
+}
+
+impl Variant {
+    // CPU clock in Hz for chips tied to a video standard, the others run at whatever the board gives them
+    pub fn clock_rate(&self) -> Option<u32> {
+        match self {
+            Variant::RP2A03 => Some(1_789_773),
+            Variant::RP2A07 => Some(1_662_607),
+            Variant::NMOS6502 | Variant::CMOS65C02 | Variant::MOS6510 | Variant::MOS6507 => None,
+        }
+    }
+}
+
+// How step() and execute() advance the processor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoreMode {
+    // Each instruction runs at once and reports its cycle count
+    Instruction,
+    // One bus access per cycle through tick(), including dummy reads and writes
+    Cycle,
+}
+
 #[derive(Debug, TryFromPrimitive, IntoPrimitive)]
 #[repr(u8)] // Ensure the enum is represented as a `u8`
 enum Instr {
@@ -116,6 +187,60 @@ enum Instr {
     CPY_IM = 0xC0,
     CPY_ZP = 0xC4,
     CPY_ABS = 0xCC,
+    BCC = 0x90,
+    BCS = 0xB0,
+    BNE = 0xD0,
+    BEQ = 0xF0,
+    BPL = 0x10,
+    BMI = 0x30,
+    BVC = 0x50,
+    BVS = 0x70,
+    JMP_ABS = 0x4C,
+    JMP_IND = 0x6C,
+    JSR = 0x20,
+    RTS = 0x60,
+    BRK = 0x00,
+    RTI = 0x40,
+    ASL_ACC = 0x0A,
+    ASL_ZP = 0x06,
+    ASL_ZPX = 0x16,
+    ASL_ABS = 0x0E,
+    ASL_ABSX = 0x1E,
+    LSR_ACC = 0x4A,
+    LSR_ZP = 0x46,
+    LSR_ZPX = 0x56,
+    LSR_ABS = 0x4E,
+    LSR_ABSX = 0x5E,
+    ROL_ACC = 0x2A,
+    ROL_ZP = 0x26,
+    ROL_ZPX = 0x36,
+    ROL_ABS = 0x2E,
+    ROL_ABSX = 0x3E,
+    ROR_ACC = 0x6A,
+    ROR_ZP = 0x66,
+    ROR_ZPX = 0x76,
+    ROR_ABS = 0x6E,
+    ROR_ABSX = 0x7E,
+    INC_ZP = 0xE6,
+    INC_ZPX = 0xF6,
+    INC_ABS = 0xEE,
+    INC_ABSX = 0xFE,
+    INX = 0xE8,
+    INY = 0xC8,
+    DEC_ZP = 0xC6,
+    DEC_ZPX = 0xD6,
+    DEC_ABS = 0xCE,
+    DEC_ABSX = 0xDE,
+    DEX = 0xCA,
+    DEY = 0x88,
+    CLC = 0x18,
+    SEC = 0x38,
+    CLI = 0x58,
+    SEI = 0x78,
+    CLD = 0xD8,
+    SED = 0xF8,
+    CLV = 0xB8,
+    NOP = 0xEA,
 }
 
 pub struct MOS6502 {
@@ -127,332 +252,788 @@ pub struct MOS6502 {
     regY : u8,
 
     proc_status: ProcessorStatus,
+
+    irq_line: bool,
+    nmi_line: bool,
+    nmi_pending: bool,
+    jammed: bool,
+    // 65C02 STP and WAI
+    stopped: bool,
+    waiting: bool,
+
+    variant: Variant,
+    quirks: Quirks,
+    // Only mapped in on the 6510
+    io_port: IoPort,
+
+    total_cycles: u64,
+    cycle_debt: u32,
+    // Bus accesses made so far by the instruction or cycle in progress, total_cycles
+    // only catches up once it completes
+    step_accesses: u32,
+
+    core_mode: CoreMode,
+    cycle_state: Option<CycleState>,
+    // Cycle mode samples the interrupt lines on every cycle but the last of an
+    // instruction, the sample taken on the second-to-last one decides what comes next
+    interrupt_poll: bool,
+
+    // Start of the instruction or interrupt step() is reporting on
+    step_pc: u16,
+    step_opcode: Option<u8>,
+}
+
+impl Default for MOS6502 {
+    fn default() -> Self {
+        MOS6502::new()
+    }
 }
 
 impl MOS6502 {
+    // Power-on state: registers cleared and interrupts disabled. PC is not
+    // meaningful until reset() loads it from the reset vector, which also
+    // leaves SP at $FD
     pub fn new() -> Self {
+        let mut proc_status = ProcessorStatus::new();
+        proc_status.set_interrupt_disable();
         MOS6502 {
-            regPC : 0xFFFC,
-            regSP : 0xFF,
+            regPC : 0x0000,
+            regSP : 0x00,
             regA : 0,
             regX : 0,
             regY : 0,
-            proc_status : ProcessorStatus::new(),
+            proc_status,
+            irq_line : false,
+            nmi_line : false,
+            nmi_pending : false,
+            jammed : false,
+            stopped : false,
+            waiting : false,
+            variant : Variant::NMOS6502,
+            quirks : Variant::NMOS6502.quirks(),
+            io_port : IoPort::new(),
+            total_cycles : 0,
+            cycle_debt : 0,
+            step_accesses : 0,
+            core_mode : CoreMode::Instruction,
+            cycle_state : None,
+            interrupt_poll : false,
+            step_pc : 0x0000,
+            step_opcode : None,
+        }
+    }
+
+    // Same power-on state as new(), for another member of the family
+    pub fn with_variant(variant: Variant) -> Self {
+        let mut cpu = MOS6502::new();
+        cpu.variant = variant;
+        cpu.quirks = variant.quirks();
+        cpu
+    }
+
+    // The chip this instance started from, set_quirks() may have changed its behaviour since
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn quirks(&self) -> Quirks {
+        self.quirks
+    }
+
+    // Replaces the behaviour taken from the variant, to describe a clone chip
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+    }
+
+    // Opcode metadata for the instruction set in use
+    pub fn opcode_table(&self) -> &'static [Opcode; 256] {
+        if self.quirks.cmos_instructions {
+            &OPCODES_65C02
+        } else {
+            &OPCODES
+        }
+    }
+
+    // Register access, PC is on the CPU trait
+    pub fn sp(&self) -> u8 {
+        self.regSP
+    }
+
+    pub fn a(&self) -> u8 {
+        self.regA
+    }
+
+    pub fn x(&self) -> u8 {
+        self.regX
+    }
+
+    pub fn y(&self) -> u8 {
+        self.regY
+    }
+
+    // Bit 5 has no latch and always reads back as set, as it does when P is pushed
+    pub fn status(&self) -> u8 {
+        u8::from(self.proc_status) | 0x20
+    }
+
+    // 6510 port pins P0-P5 as seen from outside: outputs driven by the CPU,
+    // inputs from set_port_input() or, when nothing drives them, fading charge
+    pub fn port_pins(&self) -> u8 {
+        self.io_port.levels(self.total_cycles) & PORT_PINS
+    }
+
+    // 6510 data direction register, 1 = output
+    pub fn port_direction(&self) -> u8 {
+        self.io_port.direction()
+    }
+
+    // Levels the host drives on the 6510 port pins selected by driven, such as
+    // pull-ups or the datasette sense line. Pins configured as outputs ignore them
+    pub fn set_port_input(&mut self, levels: u8, driven: u8) {
+        self.io_port.set_external(levels, driven);
+    }
+
+    // Undocumented NMOS opcodes are executed by default
+    pub fn set_unknown_opcode_policy(&mut self, policy: UnknownOpcodePolicy) {
+        self.quirks.illegal_opcodes = policy;
+    }
+
+    // Magic constant ORed into A by the unstable XAA and LAX #imm, it varies between chips and temperature
+    pub fn set_unstable_magic(&mut self, magic: u8) {
+        self.quirks.unstable_magic = magic;
+    }
+
+    // SHA, SHX, SHY and TAS AND the stored value with the base high byte + 1.
+    // On some chips, or when RDY stalls the write cycle, that term drops out
+    pub fn set_store_high_and(&mut self, enabled: bool) {
+        self.quirks.store_high_and = enabled;
+    }
+
+    // Instruction-stepped by default. An instruction already in flight is finished by the next step()
+    pub fn set_core_mode(&mut self, mode: CoreMode) {
+        self.core_mode = mode;
+    }
+
+    // Steps over an opcode as a NOP of its table length, without penalty cycles
+    // whatever the addressing mode
+    fn skip(&mut self, opcode: &Opcode) -> u32 {
+        self.regPC = self.regPC.wrapping_add(opcode.bytes as u16 - 1);
+        0
+    }
+
+    fn execute_unknown<B: Bus>(&mut self, instruction: u8, memory: &mut B) -> Result<u32, ExecutionError> {
+        match self.quirks.illegal_opcodes {
+            UnknownOpcodePolicy::Undocumented => {
+                if let Some(penalty) = self.execute_undocumented(instruction, memory) {
+                    return Ok(penalty);
+                }
+            }
+            UnknownOpcodePolicy::Nop => {
+                return Ok(self.skip(opcode(instruction)));
+            }
+            UnknownOpcodePolicy::Error => {}
+        }
+
+        let pc: u16 = self.regPC.wrapping_sub(1);
+        self.regPC = pc;
+        Err(ExecutionError::UnknownOpcode { pc: pc as u32, opcode: instruction })
+    }
+
+    // Fetches and executes one opcode, returns its cycles including the opcode fetch.
+    // Handlers only report the penalty cycles they ran into, a page crossed by indexing
+    // or a taken branch, the base count and whether the penalty applies come from the opcode table
+    fn dispatch<B: Bus>(&mut self, memory: &mut B) -> Result<u32, ExecutionError> {
+        let instruction : u8 = self.fetch(memory);
+        self.step_opcode = Some(instruction);
+        if self.quirks.cmos_instructions
+            && let Some(penalty) = self.execute_cmos(instruction, memory)
+        {
+            return Ok(self.instruction_cycles(instruction, penalty));
+        }
+        let penalty : u32 = match Instr::try_from(instruction) {
+            Ok(Instr::LDA_IM) => self.lda_im(memory),
+            Ok(Instr::LDA_ZP) => self.lda_zp(memory),
+            Ok(Instr::LDA_ZPX) => self.lda_zpx(memory),
+            Ok(Instr::LDA_ABS) => self.lda_abs(memory),
+            Ok(Instr::LDA_ABSX) => self.lda_absx(memory),
+            Ok(Instr::LDA_ABSY) => self.lda_absy(memory),
+            Ok(Instr::LDA_INDX) => self.lda_indx(memory),
+            Ok(Instr::LDA_INDY) => self.lda_indy(memory),
+            Ok(Instr::LDX_IM) => self.ldx_im(memory),
+            Ok(Instr::LDX_ZP) => self.ldx_zp(memory),
+            Ok(Instr::LDX_ZPY) => self.ldx_zpy(memory),
+            Ok(Instr::LDX_ABS) => self.ldx_abs(memory),
+            Ok(Instr::LDX_ABSY) => self.ldx_absy(memory),
+            Ok(Instr::LDY_IM) => self.ldy_im(memory),
+            Ok(Instr::LDY_ZP) => self.ldy_zp(memory),
+            Ok(Instr::LDY_ZPX) => self.ldy_zpx(memory),
+            Ok(Instr::LDY_ABS) => self.ldy_abs(memory),
+            Ok(Instr::LDY_ABSX) => self.ldy_absx(memory),
+            Ok(Instr::STA_ZP) => self.sta_zp(memory),
+            Ok(Instr::STA_ZPX) => self.sta_zpx(memory),
+            Ok(Instr::STA_ABS) => self.sta_abs(memory),
+            Ok(Instr::STA_ABSX) => self.sta_absx(memory),
+            Ok(Instr::STA_ABSY) => self.sta_absy(memory),
+            Ok(Instr::STA_INDX) => self.sta_indx(memory),
+            Ok(Instr::STA_INDY) => self.sta_indy(memory),
+            Ok(Instr::STX_ZP) => self.stx_zp(memory),
+            Ok(Instr::STX_ZPY) => self.stx_zpy(memory),
+            Ok(Instr::STX_ABS) => self.stx_abs(memory),
+            Ok(Instr::STY_ZP) => self.sty_zp(memory),
+            Ok(Instr::STY_ZPX) => self.sty_zpx(memory),
+            Ok(Instr::STY_ABS) => self.sty_abs(memory),
+            Ok(Instr::TAX) => self.tax(),
+            Ok(Instr::TAY) => self.tay(),
+            Ok(Instr::TSX) => self.tsx(),
+            Ok(Instr::TXA) => self.txa(),
+            Ok(Instr::TXS) => self.txs(),
+            Ok(Instr::TYA) => self.tya(),
+            Ok(Instr::PHA) => self.pha(memory),
+            Ok(Instr::PHP) => self.php(memory),
+            Ok(Instr::PLA) => self.pla(memory),
+            Ok(Instr::PLP) => self.plp(memory),
+            Ok(Instr::AND_IM) => self.and_im(memory),
+            Ok(Instr::AND_ZP) => self.and_zp(memory),
+            Ok(Instr::AND_ZPX) => self.and_zpx(memory),
+            Ok(Instr::AND_ABS) => self.and_abs(memory),
+            Ok(Instr::AND_ABSX) => self.and_absx(memory),
+            Ok(Instr::AND_ABSY) => self.and_absy(memory),
+            Ok(Instr::AND_INDX) => self.and_indx(memory),
+            Ok(Instr::AND_INDY) => self.and_indy(memory),
+            Ok(Instr::EOR_IM) => self.eor_im(memory),
+            Ok(Instr::EOR_ZP) => self.eor_zp(memory),
+            Ok(Instr::EOR_ZPX) => self.eor_zpx(memory),
+            Ok(Instr::EOR_ABS) => self.eor_abs(memory),
+            Ok(Instr::EOR_ABSX) => self.eor_absx(memory),
+            Ok(Instr::EOR_ABSY) => self.eor_absy(memory),
+            Ok(Instr::EOR_INDX) => self.eor_indx(memory),
+            Ok(Instr::EOR_INDY) => self.eor_indy(memory),
+            Ok(Instr::ORA_IM) => self.ora_im(memory),
+            Ok(Instr::ORA_ZP) => self.ora_zp(memory),
+            Ok(Instr::ORA_ZPX) => self.ora_zpx(memory),
+            Ok(Instr::ORA_ABS) => self.ora_abs(memory),
+            Ok(Instr::ORA_ABSX) => self.ora_absx(memory),
+            Ok(Instr::ORA_ABSY) => self.ora_absy(memory),
+            Ok(Instr::ORA_INDX) => self.ora_indx(memory),
+            Ok(Instr::ORA_INDY) => self.ora_indy(memory),
+            Ok(Instr::BIT_ZP) => self.bit_zp(memory),
+            Ok(Instr::BIT_ABS) => self.bit_abs(memory),
+            Ok(Instr::ADC_IM) => self.adc_im(memory),
+            Ok(Instr::ADC_ZP) => self.adc_zp(memory),
+            Ok(Instr::ADC_ZPX) => self.adc_zpx(memory),
+            Ok(Instr::ADC_ABS) => self.adc_abs(memory),
+            Ok(Instr::ADC_ABSX) => self.adc_absx(memory),
+            Ok(Instr::ADC_ABSY) => self.adc_absy(memory),
+            Ok(Instr::ADC_INDX) => self.adc_indx(memory),
+            Ok(Instr::ADC_INDY) => self.adc_indy(memory),
+            Ok(Instr::SBC_IM) => self.sbc_im(memory),
+            Ok(Instr::SBC_ZP) => self.sbc_zp(memory),
+            Ok(Instr::SBC_ZPX) => self.sbc_zpx(memory),
+            Ok(Instr::SBC_ABS) => self.sbc_abs(memory),
+            Ok(Instr::SBC_ABSX) => self.sbc_absx(memory),
+            Ok(Instr::SBC_ABSY) => self.sbc_absy(memory),
+            Ok(Instr::SBC_INDX) => self.sbc_indx(memory),
+            Ok(Instr::SBC_INDY) => self.sbc_indy(memory),
+            Ok(Instr::CMP_IM) => self.cmp_im(memory),
+            Ok(Instr::CMP_ZP) => self.cmp_zp(memory),
+            Ok(Instr::CMP_ZPX) => self.cmp_zpx(memory),
+            Ok(Instr::CMP_ABS) => self.cmp_abs(memory),
+            Ok(Instr::CMP_ABSX) => self.cmp_absx(memory),
+            Ok(Instr::CMP_ABSY) => self.cmp_absy(memory),
+            Ok(Instr::CMP_INDX) => self.cmp_indx(memory),
+            Ok(Instr::CMP_INDY) => self.cmp_indy(memory),
+            Ok(Instr::CPX_IM) => self.cpx_im(memory),
+            Ok(Instr::CPX_ZP) => self.cpx_zp(memory),
+            Ok(Instr::CPX_ABS) => self.cpx_abs(memory),
+            Ok(Instr::CPY_IM) => self.cpy_im(memory),
+            Ok(Instr::CPY_ZP) => self.cpy_zp(memory),
+            Ok(Instr::CPY_ABS) => self.cpy_abs(memory),
+            Ok(Instr::BCC) => self.bcc(memory),
+            Ok(Instr::BCS) => self.bcs(memory),
+            Ok(Instr::BNE) => self.bne(memory),
+            Ok(Instr::BEQ) => self.beq(memory),
+            Ok(Instr::BPL) => self.bpl(memory),
+            Ok(Instr::BMI) => self.bmi(memory),
+            Ok(Instr::BVC) => self.bvc(memory),
+            Ok(Instr::BVS) => self.bvs(memory),
+            Ok(Instr::JMP_ABS) => self.jmp_abs(memory),
+            Ok(Instr::JMP_IND) => self.jmp_ind(memory),
+            Ok(Instr::JSR) => self.jsr(memory),
+            Ok(Instr::RTS) => self.rts(memory),
+            Ok(Instr::BRK) => self.brk(memory),
+            Ok(Instr::RTI) => self.rti(memory),
+            Ok(Instr::ASL_ACC) => self.asl_acc(),
+            Ok(Instr::ASL_ZP) => self.asl_zp(memory),
+            Ok(Instr::ASL_ZPX) => self.asl_zpx(memory),
+            Ok(Instr::ASL_ABS) => self.asl_abs(memory),
+            Ok(Instr::ASL_ABSX) => self.asl_absx(memory),
+            Ok(Instr::LSR_ACC) => self.lsr_acc(),
+            Ok(Instr::LSR_ZP) => self.lsr_zp(memory),
+            Ok(Instr::LSR_ZPX) => self.lsr_zpx(memory),
+            Ok(Instr::LSR_ABS) => self.lsr_abs(memory),
+            Ok(Instr::LSR_ABSX) => self.lsr_absx(memory),
+            Ok(Instr::ROL_ACC) => self.rol_acc(),
+            Ok(Instr::ROL_ZP) => self.rol_zp(memory),
+            Ok(Instr::ROL_ZPX) => self.rol_zpx(memory),
+            Ok(Instr::ROL_ABS) => self.rol_abs(memory),
+            Ok(Instr::ROL_ABSX) => self.rol_absx(memory),
+            Ok(Instr::ROR_ACC) => self.ror_acc(),
+            Ok(Instr::ROR_ZP) => self.ror_zp(memory),
+            Ok(Instr::ROR_ZPX) => self.ror_zpx(memory),
+            Ok(Instr::ROR_ABS) => self.ror_abs(memory),
+            Ok(Instr::ROR_ABSX) => self.ror_absx(memory),
+            Ok(Instr::INC_ZP) => self.inc_zp(memory),
+            Ok(Instr::INC_ZPX) => self.inc_zpx(memory),
+            Ok(Instr::INC_ABS) => self.inc_abs(memory),
+            Ok(Instr::INC_ABSX) => self.inc_absx(memory),
+            Ok(Instr::INX) => self.inx(),
+            Ok(Instr::INY) => self.iny(),
+            Ok(Instr::DEC_ZP) => self.dec_zp(memory),
+            Ok(Instr::DEC_ZPX) => self.dec_zpx(memory),
+            Ok(Instr::DEC_ABS) => self.dec_abs(memory),
+            Ok(Instr::DEC_ABSX) => self.dec_absx(memory),
+            Ok(Instr::DEX) => self.dex(),
+            Ok(Instr::DEY) => self.dey(),
+            Ok(Instr::CLC) => self.clc(),
+            Ok(Instr::SEC) => self.sec(),
+            Ok(Instr::CLI) => self.cli(),
+            Ok(Instr::SEI) => self.sei(),
+            Ok(Instr::CLD) => self.cld(),
+            Ok(Instr::SED) => self.sed(),
+            Ok(Instr::CLV) => self.clv(),
+            Ok(Instr::NOP) => self.nop(),
+            Err(_) => self.execute_unknown(instruction, memory)?,
+        };
+        Ok(self.instruction_cycles(instruction, penalty))
+    }
+
+    // Base cycles from this variant's opcode table, plus the penalty cycles the handler
+    // ran into when the table says the opcode pays for them
+    fn instruction_cycles(&self, instruction: u8, penalty: u32) -> u32 {
+        let opcode : &Opcode = &self.opcode_table()[instruction as usize];
+        let mut cycles : u32 = opcode.cycles as u32;
+        if opcode.page_penalty {
+            cycles += penalty;
+        }
+        if opcode.decimal_penalty && self.decimal_arithmetic() {
+            cycles += 1;
+        }
+        // Each table times JMP (abs) for its own chip. Fixing the NMOS page bug
+        // costs one more cycle, keeping it on a 65C02 saves one
+        if opcode.mode == AddressingMode::Indirect && self.quirks.jmp_indirect_bug == self.quirks.cmos_instructions {
+            if self.quirks.jmp_indirect_bug {
+                cycles -= 1;
+            } else {
+                cycles += 1;
+            }
         }
+        cycles
+    }
+
+    // ADC and SBC work in BCD when D is set, except on chips such as the
+    // Ricoh 2A03 where D is stored and pushed but has no effect
+    fn decimal_arithmetic(&self) -> bool {
+        self.proc_status.decimal_mode() && self.quirks.decimal_mode
+    }
+
+    // Cycle on which the current bus access happens, each access takes one
+    fn bus_cycle(&mut self) -> u64 {
+        let accesses : u32 = self.step_accesses;
+        self.step_accesses += 1;
+        self.total_cycles + accesses as u64
+    }
+
+    // Address as it appears on the pins. The 6507 only has A0-A12, so the
+    // upper bits are dropped and every 8 KiB of memory is a mirror
+    fn bus_address(&self, address: u16) -> u16 {
+        address & self.quirks.address_mask
+    }
+
+    fn step_info(&self, cycles: u32) -> StepInfo {
+        StepInfo { address : self.step_pc as u32, opcode : self.step_opcode, cycles }
+    }
+
+    // Error reported while the processor is locked up by JAM or STP
+    fn halt_error(&self) -> Option<ExecutionError> {
+        if self.jammed {
+            Some(ExecutionError::Jammed { pc: self.regPC as u32 })
+        } else if self.stopped {
+            Some(ExecutionError::Stopped { pc: self.regPC as u32 })
+        } else {
+            None
+        }
+    }
+
+    // Read-modify-write: the NMOS 6502 writes the unmodified value back
+    // during the modify cycle, then writes the result. The 65C02 reads it again instead.
+    fn read_modify_write<B: Bus>(&mut self, address: u16, memory: &mut B, operation: fn(&mut MOS6502, u8) -> u8) {
+        let value: u8 = self.read(address, memory);
+        if self.quirks.rmw_double_write {
+            self.write(address, value, memory);
+        } else {
+            self.read(address, memory);
+        }
+        let result: u8 = operation(self, value);
+        self.write(address, result, memory);
     }
 }
 
 impl CPU for MOS6502 {
-    fn fetch(&mut self, memory : &Memory) -> u8 {
+    fn fetch<B: Bus>(&mut self, memory : &mut B) -> u8 {
         let res = self.read(self.regPC, memory);
-        self.regPC += 1;
+        self.regPC = self.regPC.wrapping_add(1);
         res
     }
 
-    fn read(&self, address: u16, memory : &Memory) -> u8 {
-       memory[address as usize]
+    fn read<B: Bus>(&mut self, address: u16, memory : &mut B) -> u8 {
+       let cycle : u64 = self.bus_cycle();
+       if self.quirks.io_port && address <= 0x0001 {
+           return self.io_port.read(address, cycle);
+       }
+       memory.read(self.bus_address(address) as u32)
     }
 
-    fn execute(&mut self, mut cycles : u32, memory : &mut Memory) {
-        while cycles > 0 {
-            let instruction : u8 = self.fetch(memory);
-            cycles -= 1;
-            match Instr::try_from(instruction) {
-                Ok(Instr::LDA_IM) => {
-                    cycles -= self.lda_im(memory);
-                }
-                Ok(Instr::LDA_ZP) => {
-                    cycles -= self.lda_zp(memory);
-                }
-                Ok(Instr::LDA_ZPX) => {
-                    cycles -= self.lda_zpx(memory);
-                }
-                Ok(Instr::LDA_ABS) => {
-                    cycles -= self.lda_abs(memory);
-                }
-                Ok(Instr::LDA_ABSX) => {
-                    cycles -= self.lda_absx(memory);
-                }
-                Ok(Instr::LDA_ABSY) => {
-                    cycles -= self.lda_absy(memory);
-                }
-                Ok(Instr::LDA_INDX) => {
-                    cycles -= self.lda_indx(memory);
-                }
-                Ok(Instr::LDA_INDY) => {
-                    cycles -= self.lda_indy(memory);
-                }
-                Ok(Instr::LDX_IM) => {
-                    cycles -= self.ldx_im(memory);
-                }
-                Ok(Instr::LDX_ZP) => {
-                    cycles -= self.ldx_zp(memory);
-                }
-                Ok(Instr::LDX_ZPY) => {
-                    cycles -= self.ldx_zpy(memory);
-                }
-                Ok(Instr::LDX_ABS) => {
-                    cycles -= self.ldx_abs(memory);
-                }
-                Ok(Instr::LDX_ABSY) => {
-                    cycles -= self.ldx_absy(memory);
-                }
-                Ok(Instr::LDY_IM) => {
-                    cycles -= self.ldy_im(memory);
-                }
-                Ok(Instr::LDY_ZP) => {
-                    cycles -= self.ldy_zp(memory);
-                }
-                Ok(Instr::LDY_ZPX) => {
-                    cycles -= self.ldy_zpx(memory);
-                }
-                Ok(Instr::LDY_ABS) => {
-                    cycles -= self.ldy_abs(memory);
-                }
-                Ok(Instr::LDY_ABSX) => {
-                    cycles -= self.ldy_absx(memory);
-                }
-                Ok(Instr::STA_ZP) => {
-                    cycles -= self.sta_zp(memory);
-                }
-                Ok(Instr::STA_ZPX) => {
-                    cycles -= self.sta_zpx(memory);
-                }
-                Ok(Instr::STA_ABS) => {
-                    cycles -= self.sta_abs(memory);
-                }
-                Ok(Instr::STA_ABSX) => {
-                    cycles -= self.sta_absx(memory);
-                }
-                Ok(Instr::STA_ABSY) => {
-                    cycles -= self.sta_absy(memory);
-                }
-                Ok(Instr::STA_INDX) => {
-                    cycles -= self.sta_indx(memory);
-                }
-                Ok(Instr::STA_INDY) => {
-                    cycles -= self.sta_indy(memory);
-                }
-                Ok(Instr::STX_ZP) => {
-                    cycles -= self.stx_zp(memory);
-                }
-                Ok(Instr::STX_ZPY) => {
-                    cycles -= self.stx_zpy(memory);
-                }
-                Ok(Instr::STX_ABS) => {
-                    cycles -= self.stx_abs(memory);
-                }
-                Ok(Instr::STY_ZP) => {
-                    cycles -= self.sty_zp(memory);
-                }
-                Ok(Instr::STY_ZPX) => {
-                    cycles -= self.sty_zpx(memory);
-                }
-                Ok(Instr::STY_ABS) => {
-                    cycles -= self.sty_abs(memory);
-                }
-                Ok(Instr::TAX) => {
-                    cycles -= self.tax();
-                }
-                Ok(Instr::TAY) => {
-                    cycles -= self.tay();
-                }
-                Ok(Instr::TSX) => {
-                    cycles -= self.tsx();
-                }
-                Ok(Instr::TXA) => {
-                    cycles -= self.txa();
-                }
-                Ok(Instr::TXS) => {
-                    cycles -= self.txs();
-                }
-                Ok(Instr::TYA) => {
-                    cycles -= self.tya();
-                }
-                Ok(Instr::PHA) => {
-                    cycles -= self.pha(memory);
-                }
-                Ok(Instr::PHP) => {
-                    cycles -= self.php(memory);
-                }
-                Ok(Instr::PLA) => {
-                    cycles -= self.pla(memory);
-                }
-                Ok(Instr::PLP) => {
-                    cycles -= self.plp(memory);
-                }
-                Ok(Instr::AND_IM) => {
-                    cycles -= self.and_im(memory);
-                }
-                Ok(Instr::AND_ZP) => {
-                    cycles -= self.and_zp(memory);
-                }
-                Ok(Instr::AND_ZPX) => {
-                    cycles -= self.and_zpx(memory);
-                }
-                Ok(Instr::AND_ABS) => {
-                    cycles -= self.and_abs(memory);
-                }
-                Ok(Instr::AND_ABSX) => {
-                    cycles -= self.and_absx(memory);
-                }
-                Ok(Instr::AND_ABSY) => {
-                    cycles -= self.and_absy(memory);
-                }
-                Ok(Instr::AND_INDX) => {
-                    cycles -= self.and_indx(memory);
-                }
-                Ok(Instr::AND_INDY) => {
-                    cycles -= self.and_indy(memory);
-                }
-                Ok(Instr::EOR_IM) => {
-                    cycles -= self.eor_im(memory);
-                }
-                Ok(Instr::EOR_ZP) => {
-                    cycles -= self.eor_zp(memory);
-                }
-                Ok(Instr::EOR_ZPX) => {
-                    cycles -= self.eor_zpx(memory);
-                }
-                Ok(Instr::EOR_ABS) => {
-                    cycles -= self.eor_abs(memory);
-                }
-                Ok(Instr::EOR_ABSX) => {
-                    cycles -= self.eor_absx(memory);
-                }
-                Ok(Instr::EOR_ABSY) => {
-                    cycles -= self.eor_absy(memory);
-                }
-                Ok(Instr::EOR_INDX) => {
-                    cycles -= self.eor_indx(memory);
-                }
-                Ok(Instr::EOR_INDY) => {
-                    cycles -= self.eor_indy(memory);
-                }
-                Ok(Instr::ORA_IM) => {
-                    cycles -= self.ora_im(memory);
-                }
-                Ok(Instr::ORA_ZP) => {
-                    cycles -= self.ora_zp(memory);
-                }
-                Ok(Instr::ORA_ZPX) => {
-                    cycles -= self.ora_zpx(memory);
-                }
-                Ok(Instr::ORA_ABS) => {
-                    cycles -= self.ora_abs(memory);
-                }
-                Ok(Instr::ORA_ABSX) => {
-                    cycles -= self.ora_absx(memory);
-                }
-                Ok(Instr::ORA_ABSY) => {
-                    cycles -= self.ora_absy(memory);
-                }
-                Ok(Instr::ORA_INDX) => {
-                    cycles -= self.ora_indx(memory);
-                }
-                Ok(Instr::ORA_INDY) => {
-                    cycles -= self.ora_indy(memory);
-                }
-                Ok(Instr::BIT_ZP) => {
-                    cycles -= self.bit_zp(memory);
-                }
-                Ok(Instr::BIT_ABS) => {
-                    cycles -= self.bit_abs(memory);
-                }
-                Ok(Instr::ADC_IM) => {
-                    cycles -= self.adc_im(memory);
-                }
-                Ok(Instr::ADC_ZP) => {
-                    cycles -= self.adc_zp(memory);
-                }
-                Ok(Instr::ADC_ZPX) => {
-                    cycles -= self.adc_zpx(memory);
-                }
-                Ok(Instr::ADC_ABS) => {
-                    cycles -= self.adc_abs(memory);
-                }
-                Ok(Instr::ADC_ABSX) => {
-                    cycles -= self.adc_absx(memory);
-                }
-                Ok(Instr::ADC_ABSY) => {
-                    cycles -= self.adc_absy(memory);
-                }
-                Ok(Instr::ADC_INDX) => {
-                    cycles -= self.adc_indx(memory);
-                }
-                Ok(Instr::ADC_INDY) => {
-                    cycles -= self.adc_indy(memory);
-                }
-                Ok(Instr::SBC_IM) => {
-                    cycles -= self.sbc_im(memory);
-                }
-                Ok(Instr::SBC_ZP) => {
-                    cycles -= self.sbc_zp(memory);
-                }
-                Ok(Instr::SBC_ZPX) => {
-                    cycles -= self.sbc_zpx(memory);
-                }
-                Ok(Instr::SBC_ABS) => {
-                    cycles -= self.sbc_abs(memory);
-                }
-                Ok(Instr::SBC_ABSX) => {
-                    cycles -= self.sbc_absx(memory);
-                }
-                Ok(Instr::SBC_ABSY) => {
-                    cycles -= self.sbc_absy(memory);
-                }
-                Ok(Instr::SBC_INDX) => {
-                    cycles -= self.sbc_indx(memory);
-                }
-                Ok(Instr::SBC_INDY) => {
-                    cycles -= self.sbc_indy(memory);
-                }
-                Ok(Instr::CMP_IM) => {
-                    cycles -= self.cmp_im(memory);
-                }
-                Ok(Instr::CMP_ZP) => {
-                    cycles -= self.cmp_zp(memory);
-                }
-                Ok(Instr::CMP_ZPX) => {
-                    cycles -= self.cmp_zpx(memory);
-                }
-                Ok(Instr::CMP_ABS) => {
-                    cycles -= self.cmp_abs(memory);
-                }
-                Ok(Instr::CMP_ABSX) => {
-                    cycles -= self.cmp_absx(memory);
-                }
-                Ok(Instr::CMP_ABSY) => {
-                    cycles -= self.cmp_absy(memory);
-                }
-                Ok(Instr::CMP_INDX) => {
-                    cycles -= self.cmp_indx(memory);
-                }
-                Ok(Instr::CMP_INDY) => {
-                    cycles -= self.cmp_indy(memory);
-                }
-                Ok(Instr::CPX_IM) => {
-                    cycles -= self.cpx_im(memory);
-                }
-                Ok(Instr::CPX_ZP) => {
-                    cycles -= self.cpx_zp(memory);
-                }
-                Ok(Instr::CPX_ABS) => {
-                    cycles -= self.cpx_abs(memory);
-                }
-                Ok(Instr::CPY_IM) => {
-                    cycles -= self.cpy_im(memory);
-                }
-                Ok(Instr::CPY_ZP) => {
-                    cycles -= self.cpy_zp(memory);
-                }
-                Ok(Instr::CPY_ABS) => {
-                    cycles -= self.cpy_abs(memory);
-                }
-                Err(_) => {
-                    println!("Unknown instruction: {:#X}", instruction);
+    // On the 6510 writes to $0000/$0001 land in the port registers,
+    // the RAM underneath gets the value as well
+    fn write<B: Bus>(&mut self, address: u16, value: u8, memory : &mut B) {
+       let cycle : u64 = self.bus_cycle();
+       if self.quirks.io_port && address <= 0x0001 {
+           self.io_port.write(address, value, cycle);
+       }
+       memory.write(self.bus_address(address) as u32, value);
+    }
+
+    fn step<B: Bus>(&mut self, memory : &mut B) -> Result<StepInfo, ExecutionError> {
+        if self.core_mode == CoreMode::Cycle || self.cycle_state.is_some() {
+            let cycles : u32 = self.tick_instruction(memory)?;
+            return Ok(self.step_info(cycles));
+        }
+
+        if let Some(error) = self.halt_error() {
+            return Err(error);
+        }
+        self.step_pc = self.regPC;
+        self.step_opcode = None;
+        if self.waiting && !self.wake_up() {
+            self.total_cycles += 1;
+            return Ok(self.step_info(1));
+        }
+
+        self.step_accesses = 0;
+        let mut cycles : u32 = self.poll_interrupts(memory);
+        if cycles == 0 {
+            cycles = self.dispatch(memory)?;
+        }
+        self.total_cycles += cycles as u64;
+        if let Some(address) = memory.fault() {
+            return Err(ExecutionError::BusFault { address });
+        }
+
+        match self.halt_error() {
+            Some(error) => Err(error),
+            None => Ok(self.step_info(cycles)),
+        }
+    }
+
+    // Runs for at least the given budget, the cycles spent past it are
+    // carried over and deducted from the next call
+    fn execute<B: Bus>(&mut self, cycles : u32, memory : &mut B) -> Result<(), ExecutionError> {
+        let debt : u32 = self.cycle_debt;
+        let mut remaining : i64 = cycles as i64 - debt as i64;
+        while remaining > 0 {
+            let result : Result<u32, ExecutionError> = if self.core_mode == CoreMode::Cycle {
+                self.tick(memory).map(|_| 1)
+            } else {
+                self.step(memory).map(|info| info.cycles)
+            };
+            match result {
+                Ok(step_cycles) => remaining -= step_cycles as i64,
+                Err(error) => {
+                    // The debt is only paid off by cycles that actually ran,
+                    // what is left of it still applies to the next call
+                    let run : i64 = cycles as i64 - debt as i64 - remaining;
+                    self.cycle_debt = (debt as i64 - run).max(0) as u32;
+                    return Err(error);
                 }
             }
         }
+        self.cycle_debt = (-remaining) as u32;
+        Ok(())
+    }
+
+    // 7-cycle reset sequence, PC is loaded little-endian from $FFFC/$FFFD
+    fn reset<B: Bus>(&mut self, memory : &mut B) {
+        self.reset_sequence(memory);
+        self.total_cycles += 7;
     }
-}
\ No newline at end of file
+
+    fn halted(&self) -> bool {
+        self.jammed || self.stopped
+    }
+
+    fn waiting(&self) -> bool {
+        self.waiting
+    }
+
+    fn total_cycles(&self) -> u64 {
+        self.total_cycles
+    }
+
+    // The 6507 has no interrupt pins, its lines never change
+    fn irq(&mut self, active: bool) {
+        if !self.quirks.interrupt_pins {
+            return;
+        }
+        self.irq_line = active;
+    }
+
+    fn nmi(&mut self, active: bool) {
+        if !self.quirks.interrupt_pins {
+            return;
+        }
+        if active && !self.nmi_line {
+            self.nmi_pending = true;
+        }
+        self.nmi_line = active;
+    }
+
+    fn pc(&self) -> u16 {
+        self.regPC
+    }
+
+    // Takes effect at the next instruction boundary, an instruction in flight in cycle mode is abandoned
+    fn set_pc(&mut self, pc: u16) {
+        self.regPC = pc;
+        self.cycle_state = None;
+    }
+
+    fn registers(&self) -> Vec<Register> {
+        vec![
+            Register { name : "PC", value : self.regPC as u32, bits : 16 },
+            Register { name : "SP", value : self.regSP as u32, bits : 8 },
+            Register { name : "A", value : self.regA as u32, bits : 8 },
+            Register { name : "X", value : self.regX as u32, bits : 8 },
+            Register { name : "Y", value : self.regY as u32, bits : 8 },
+            Register { name : "P", value : self.status() as u32, bits : 8 },
+        ]
+    }
+}
+
+// Loads `program` at `address` and points PC at it, with an empty stack
+// and every flag clear
+#[cfg(test)]
+fn setup(address: u16, program: &[u8]) -> (MOS6502, RecordingBus) {
+    let mut cpu: MOS6502 = MOS6502::new();
+    let mut memory: RecordingBus = RecordingBus::new(Memory::new());
+    for (offset, byte) in program.iter().enumerate() {
+        memory[address as usize + offset] = *byte;
+    }
+    cpu.regPC = address;
+    cpu.regSP = 0xFF;
+    cpu.proc_status = ProcessorStatus::new();
+    (cpu, memory)
+}
+
+// Same as setup(), for another member of the family
+#[cfg(test)]
+fn setup_variant(variant: Variant, address: u16, program: &[u8]) -> (MOS6502, RecordingBus) {
+    let (mut cpu, memory) = setup(address, program);
+    cpu.variant = variant;
+    cpu.quirks = variant.quirks();
+    (cpu, memory)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+    use crate::cpu::error::ExecutionError;
+
+    use super::{setup, setup_variant, UnknownOpcodePolicy, Variant};
+
+    #[test]
+    fn error_policy_reports_the_opcode() {
+        // LAX $10
+        let (mut cpu, mut memory) = setup(0x0200, &[0xA7, 0x10]);
+        cpu.set_unknown_opcode_policy(UnknownOpcodePolicy::Error);
+        memory[0x0010] = 0x42;
+        assert_eq!(cpu.execute(10, &mut memory), Err(ExecutionError::UnknownOpcode { pc: 0x0200, opcode: 0xA7 }));
+        // PC is left on the opcode and nothing was executed
+        assert_eq!(cpu.regPC, 0x0200);
+        assert_eq!(cpu.regA, 0x00);
+    }
+
+    #[test]
+    fn error_policy_also_covers_jam() {
+        let (mut cpu, mut memory) = setup(0x0200, &[0x02]);
+        cpu.set_unknown_opcode_policy(UnknownOpcodePolicy::Error);
+        assert_eq!(cpu.step(&mut memory), Err(ExecutionError::UnknownOpcode { pc: 0x0200, opcode: 0x02 }));
+        assert!(!cpu.halted());
+    }
+
+    #[test]
+    fn nop_policy_skips_the_operand_bytes() {
+        // LAX $10 as a two byte, three cycle NOP; ASL A
+        let (mut cpu, mut memory) = setup(0x0200, &[0xA7, 0x10, 0x0A]);
+        cpu.set_unknown_opcode_policy(UnknownOpcodePolicy::Nop);
+        cpu.regA = 0x21;
+        assert_eq!(cpu.step(&mut memory).map(|info| info.cycles), Ok(3));
+        assert_eq!(cpu.regPC, 0x0202);
+        assert_eq!(cpu.regA, 0x21);
+        assert_eq!(cpu.step(&mut memory).map(|info| info.cycles), Ok(2));
+        assert_eq!(cpu.regA, 0x42);
+    }
+
+    #[test]
+    fn nop_policy_does_not_jam() {
+        let (mut cpu, mut memory) = setup(0x0200, &[0x02, 0x02]);
+        cpu.set_unknown_opcode_policy(UnknownOpcodePolicy::Nop);
+        cpu.execute(4, &mut memory).unwrap();
+        assert!(!cpu.halted());
+        assert_eq!(cpu.regPC, 0x0202);
+    }
+
+    #[test]
+    fn undocumented_policy_is_the_default() {
+        // LAX $10
+        let (mut cpu, mut memory) = setup(0x0200, &[0xA7, 0x10]);
+        memory[0x0010] = 0x42;
+        assert_eq!(cpu.step(&mut memory).map(|info| info.cycles), Ok(3));
+        assert_eq!(cpu.regX, 0x42);
+    }
+
+    #[test]
+    fn execute_does_not_underflow_on_a_short_budget() {
+        // JSR $0300 with a single cycle left
+        let (mut cpu, mut memory) = setup(0x0200, &[0x20, 0x00, 0x03]);
+        cpu.execute(1, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0300);
+    }
+
+    #[test]
+    fn overshoot_is_carried_into_the_next_call() {
+        // JSR $0300; at $0300: NOP
+        let (mut cpu, mut memory) = setup(0x0200, &[0x20, 0x00, 0x03]);
+        memory[0x0300] = 0xEA;
+        cpu.execute(1, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0300);
+        assert_eq!(cpu.total_cycles(), 6);
+
+        // The five cycles owed are paid before anything else runs
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0300);
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0301);
+        assert_eq!(cpu.total_cycles(), 8);
+    }
+
+    #[test]
+    fn debt_survives_an_error() {
+        // LDA $0300; ISC $0300,X
+        let (mut cpu, mut memory) = setup(0x0200, &[0xAD, 0x00, 0x03, 0xFF, 0x00, 0x03]);
+        cpu.set_unknown_opcode_policy(UnknownOpcodePolicy::Error);
+        cpu.execute(1, &mut memory).unwrap();
+        assert!(cpu.execute(10, &mut memory).is_err());
+
+        // Nothing ran, so the three cycles LDA overshot by are still owed
+        cpu.set_unknown_opcode_policy(UnknownOpcodePolicy::Nop);
+        cpu.execute(3, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0203);
+        cpu.execute(7, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0206);
+        assert_eq!(cpu.total_cycles(), 11);
+    }
+
+    #[test]
+    fn total_cycles_include_reset_and_interrupts() {
+        let (mut cpu, mut memory) = setup(0x0200, &[0xEA]);
+        memory[0xFFFC] = 0x00;
+        memory[0xFFFD] = 0x02;
+        memory[0xFFFE] = 0x00;
+        memory[0xFFFF] = 0x03;
+        cpu.reset(&mut memory);
+        assert_eq!(cpu.total_cycles(), 7);
+        cpu.step(&mut memory).unwrap();
+        assert_eq!(cpu.total_cycles(), 9);
+
+        cpu.proc_status.clear_interrupt_disable();
+        cpu.irq(true);
+        assert_eq!(cpu.step(&mut memory).map(|info| info.cycles), Ok(7));
+        assert_eq!(cpu.total_cycles(), 16);
+    }
+
+    // LDX #$03; loop: DEX; BNE loop; NOP
+    const COUNTDOWN: [u8; 6] = [0xA2, 0x03, 0xCA, 0xD0, 0xFD, 0xEA];
+
+    #[test]
+    fn run_until_pc_stops_before_the_target() {
+        let (mut cpu, mut memory) = setup(0x0200, &COUNTDOWN);
+        // LDX, then two taken and one untaken BNE
+        assert_eq!(cpu.run_until_pc(0x0205, &mut memory), Ok(2 + 2 * 5 + 4));
+        assert_eq!(cpu.regX, 0x00);
+        assert_eq!(cpu.regPC, 0x0205);
+    }
+
+    #[test]
+    fn run_until_checks_registers_and_memory() {
+        // loop: INC $10; JMP loop
+        let (mut cpu, mut memory) = setup(0x0200, &[0xE6, 0x10, 0x4C, 0x00, 0x02]);
+        let cycles: u64 = cpu.run_until(|_, memory| memory[0x0010] == 3, &mut memory).unwrap();
+        assert_eq!(cycles, 5 + 3 + 5 + 3 + 5);
+
+        let (mut cpu, mut memory) = setup(0x0200, &COUNTDOWN);
+        cpu.run_until(|cpu, _| cpu.x() == 1, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0203);
+    }
+
+    #[test]
+    fn run_instructions_counts_whole_instructions() {
+        let (mut cpu, mut memory) = setup(0x0200, &COUNTDOWN);
+        assert_eq!(cpu.run_instructions(3, &mut memory), Ok(2 + 2 + 3));
+        assert_eq!(cpu.regPC, 0x0202);
+        assert_eq!(cpu.run_instructions(0, &mut memory), Ok(0));
+    }
+
+    #[test]
+    fn run_until_ends_on_a_halted_cpu() {
+        // NOP; JAM
+        let (mut cpu, mut memory) = setup(0x0200, &[0xEA, 0x02]);
+        assert_eq!(cpu.run_until_pc(0x0300, &mut memory), Err(ExecutionError::Jammed { pc: 0x0201 }));
+        assert!(cpu.halted());
+        assert_eq!(cpu.run_until(|_, _| false, &mut memory), Err(ExecutionError::Jammed { pc: 0x0201 }));
+    }
+
+    #[test]
+    fn ricoh_clock_rates() {
+        assert_eq!(Variant::RP2A03.clock_rate(), Some(1_789_773));
+        assert_eq!(Variant::RP2A07.clock_rate(), Some(1_662_607));
+        assert_eq!(Variant::NMOS6502.clock_rate(), None);
+    }
+
+    #[test]
+    fn mos6507_mirrors_memory_every_8k() {
+        // LDA $E010; STA $2011
+        let (mut cpu, mut memory) = setup_variant(Variant::MOS6507, 0x0200, &[0xAD, 0x10, 0xE0, 0x8D, 0x11, 0x20]);
+        memory[0x0010] = 0x42;
+        memory[0xE010] = 0x99;
+        cpu.run_instructions(2, &mut memory).unwrap();
+        assert_eq!(cpu.regA, 0x42);
+        assert_eq!(memory[0x0011], 0x42);
+        assert_eq!(memory[0x2011], 0x00);
+
+        // The reset vector comes from $1FFC/$1FFD
+        memory[0x1FFC] = 0x00;
+        memory[0x1FFD] = 0xF0;
+        cpu.reset(&mut memory);
+        assert_eq!(cpu.regPC, 0xF000);
+    }
+
+    #[test]
+    fn mos6507_ignores_irq_and_nmi() {
+        let (mut cpu, mut memory) = setup_variant(Variant::MOS6507, 0x0200, &[0xEA, 0xEA]);
+        cpu.proc_status.clear_interrupt_disable();
+        cpu.irq(true);
+        cpu.nmi(true);
+        assert_eq!(cpu.step(&mut memory).map(|info| info.cycles), Ok(2));
+        assert_eq!(cpu.regPC, 0x0201);
+    }
+}
@@ -1,5 +1,333 @@
+use crate::cpu::bus::Bus; 
+
+use crate::cpu::cpu::CPU;
+
+use super::MOS6502;
+
+// Addressing modes of the 6502 family, as listed in the opcode table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressingMode {
+    Implied,
+    Accumulator,
+    Immediate,
+    ZeroPage,
+    ZeroPageX,
+    ZeroPageY,
+    Absolute,
+    AbsoluteX,
+    AbsoluteY,
+    Indirect,
+    IndirectX,
+    IndirectY,
+    Relative,
+    // 65C02 additions: (zp), JMP (abs,X) and the zp,rel operands of BBR/BBS
+    ZeroPageIndirect,
+    AbsoluteIndexedIndirect,
+    ZeroPageRelative,
+}
+
+impl AddressingMode {
+    // Instruction length in bytes, opcode included
+    pub const fn bytes(self) -> u8 {
+        match self {
+            AddressingMode::Implied | AddressingMode::Accumulator => 1,
+            AddressingMode::Immediate
+            | AddressingMode::ZeroPage
+            | AddressingMode::ZeroPageX
+            | AddressingMode::ZeroPageY
+            | AddressingMode::IndirectX
+            | AddressingMode::IndirectY
+            | AddressingMode::Relative
+            | AddressingMode::ZeroPageIndirect => 2,
+            AddressingMode::Absolute
+            | AddressingMode::AbsoluteX
+            | AddressingMode::AbsoluteY
+            | AddressingMode::Indirect
+            | AddressingMode::AbsoluteIndexedIndirect
+            | AddressingMode::ZeroPageRelative => 3,
+        }
+    }
+}
+
+// Effective address of an operand, as computed by resolve()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Operand {
+    pub address: u16,
+    // Indexing carried into the high byte
+    pub page_crossed: bool,
+}
+
 impl MOS6502 {
-    pub fn get_zero_page(&self, memory : &Memory) -> u8 {
-        
-    } 
-}
\ No newline at end of file
+    // Fetches the operand bytes of an addressing mode and computes the effective address,
+    // issuing the dummy reads the NMOS chip does on the way. Relative resolves to the branch target
+    pub(super) fn resolve<B: Bus>(&mut self, mode: AddressingMode, memory: &mut B) -> Operand {
+        match mode {
+            AddressingMode::Immediate => {
+                let address: u16 = self.regPC;
+                self.regPC = self.regPC.wrapping_add(1);
+                Operand { address, page_crossed : false }
+            }
+            AddressingMode::ZeroPage => {
+                let address: u16 = self.fetch(memory) as u16;
+                Operand { address, page_crossed : false }
+            }
+            AddressingMode::ZeroPageX => self.zero_page_indexed(self.regX, memory),
+            AddressingMode::ZeroPageY => self.zero_page_indexed(self.regY, memory),
+            AddressingMode::Absolute => {
+                let address: u16 = self.fetch_address(memory);
+                Operand { address, page_crossed : false }
+            }
+            AddressingMode::AbsoluteX => {
+                let base_address: u16 = self.fetch_address(memory);
+                indexed(base_address, self.regX)
+            }
+            AddressingMode::AbsoluteY => {
+                let base_address: u16 = self.fetch_address(memory);
+                indexed(base_address, self.regY)
+            }
+            AddressingMode::Indirect => {
+                let pointer: u16 = self.fetch_address(memory);
+                if !self.quirks.jmp_indirect_bug {
+                    // Fixed on the 65C02 at the cost of one more cycle
+                    self.read(self.regPC.wrapping_sub(1), memory);
+                    let address: u16 = self.read_pointer(pointer, memory);
+                    return Operand { address, page_crossed : false };
+                }
+                // NMOS bug: the pointer high byte is read without carrying into the page
+                let low_byte: u8 = self.read(pointer, memory);
+                let high_byte: u8 = self.read((pointer & 0xFF00) | (pointer.wrapping_add(1) & 0x00FF), memory);
+                let address: u16 = ((high_byte as u16) << 8) | (low_byte as u16);
+                Operand { address, page_crossed : false }
+            }
+            AddressingMode::AbsoluteIndexedIndirect => {
+                let base_address: u16 = self.fetch_address(memory);
+                self.read(self.regPC.wrapping_sub(1), memory);
+                let address: u16 = self.read_pointer(base_address.wrapping_add(self.regX as u16), memory);
+                Operand { address, page_crossed : false }
+            }
+            AddressingMode::ZeroPageIndirect => {
+                let pointer: u8 = self.fetch(memory);
+                let address: u16 = self.read_zero_page_pointer(pointer, memory);
+                Operand { address, page_crossed : false }
+            }
+            AddressingMode::IndirectX => {
+                let base_address: u8 = self.fetch(memory);
+                self.read(base_address as u16, memory);
+                let address: u16 = self.read_zero_page_pointer(base_address.wrapping_add(self.regX), memory);
+                Operand { address, page_crossed : false }
+            }
+            AddressingMode::IndirectY => {
+                let pointer: u8 = self.fetch(memory);
+                let base_address: u16 = self.read_zero_page_pointer(pointer, memory);
+                indexed(base_address, self.regY)
+            }
+            AddressingMode::Relative => {
+                let offset: u8 = self.fetch(memory);
+                let address: u16 = self.regPC.wrapping_add(offset as i8 as u16);
+                let page_crossed: bool = (address & 0xFF00) != (self.regPC & 0xFF00);
+                Operand { address, page_crossed }
+            }
+            AddressingMode::Implied | AddressingMode::Accumulator | AddressingMode::ZeroPageRelative => {
+                unreachable!("{:?} has no single memory operand", mode)
+            }
+        }
+    }
+
+    // Resolves and reads the operand, then hands it to the operation. Indexed reads only
+    // take the fix-up cycle when a page is crossed, that is the penalty cycle returned
+    pub(super) fn read_operand<B: Bus>(&mut self, mode: AddressingMode, memory: &mut B, operation: fn(&mut MOS6502, u8)) -> u32 {
+        let operand: Operand = self.resolve(mode, memory);
+        if operand.page_crossed {
+            self.read(operand.address.wrapping_sub(0x0100), memory);
+        }
+        let value: u8 = self.read(operand.address, memory);
+        operation(self, value);
+        operand.page_crossed as u32
+    }
+
+    // Resolves the operand and stores the value there. Indexed writes always take
+    // the fix-up cycle, so there is never a penalty to return
+    pub(super) fn write_operand<B: Bus>(&mut self, mode: AddressingMode, value: u8, memory: &mut B) -> u32 {
+        let operand: Operand = self.resolve(mode, memory);
+        self.fix_up(mode, operand, memory);
+        self.write(operand.address, value, memory);
+        0
+    }
+
+    // Resolves the operand and runs a read-modify-write on it, indexed modes
+    // always take the fix-up cycle as writes do
+    pub(super) fn modify_operand<B: Bus>(&mut self, mode: AddressingMode, memory: &mut B, operation: fn(&mut MOS6502, u8) -> u8) -> u32 {
+        let operand: Operand = self.resolve(mode, memory);
+        self.fix_up(mode, operand, memory);
+        self.read_modify_write(operand.address, memory, operation);
+        0
+    }
+
+    // Indexed modes that write read the address before the high byte is fixed up,
+    // whether or not a page was crossed
+    fn fix_up<B: Bus>(&mut self, mode: AddressingMode, operand: Operand, memory: &mut B) {
+        if !matches!(mode, AddressingMode::AbsoluteX | AddressingMode::AbsoluteY | AddressingMode::IndirectY) {
+            return;
+        }
+        let unfixed_address: u16 = if operand.page_crossed {
+            operand.address.wrapping_sub(0x0100)
+        } else {
+            operand.address
+        };
+        self.read(unfixed_address, memory);
+    }
+
+    fn fetch_address<B: Bus>(&mut self, memory: &mut B) -> u16 {
+        let low_byte: u8 = self.fetch(memory);
+        let high_byte: u8 = self.fetch(memory);
+        ((high_byte as u16) << 8) | (low_byte as u16)
+    }
+
+    fn zero_page_indexed<B: Bus>(&mut self, index: u8, memory: &mut B) -> Operand {
+        let base_address: u8 = self.fetch(memory);
+        self.read(base_address as u16, memory);
+        let address: u16 = base_address.wrapping_add(index) as u16;
+        Operand { address, page_crossed : false }
+    }
+
+    fn read_pointer<B: Bus>(&mut self, pointer: u16, memory: &mut B) -> u16 {
+        let low_byte: u8 = self.read(pointer, memory);
+        let high_byte: u8 = self.read(pointer.wrapping_add(1), memory);
+        ((high_byte as u16) << 8) | (low_byte as u16)
+    }
+
+    // Pointers wrap around within the zero page
+    fn read_zero_page_pointer<B: Bus>(&mut self, pointer: u8, memory: &mut B) -> u16 {
+        let low_byte: u8 = self.read(pointer as u16, memory);
+        let high_byte: u8 = self.read(pointer.wrapping_add(1) as u16, memory);
+        ((high_byte as u16) << 8) | (low_byte as u16)
+    }
+}
+
+fn indexed(base_address: u16, index: u8) -> Operand {
+    let address: u16 = base_address.wrapping_add(index as u16);
+    let page_crossed: bool = (base_address & 0xFF00) != (address & 0xFF00);
+    Operand { address, page_crossed }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::bus::{Access, RecordingBus};
+
+    use super::super::{setup, MOS6502};
+    use super::{AddressingMode, Operand};
+
+    // Resolves the operand bytes placed after $0200 and checks how many were consumed
+    fn resolve(operand_bytes: &[u8], mode: AddressingMode, memory_setup: fn(&mut MOS6502, &mut RecordingBus)) -> Operand {
+        let (mut cpu, mut memory) = setup(0x0200, operand_bytes);
+        memory_setup(&mut cpu, &mut memory);
+        let operand: Operand = cpu.resolve(mode, &mut memory);
+        assert_eq!(cpu.regPC, 0x0200 + mode.bytes() as u16 - 1);
+        operand
+    }
+
+    fn nothing(_cpu: &mut MOS6502, _memory: &mut RecordingBus) {}
+
+    #[test]
+    fn immediate_is_the_operand_byte_itself() {
+        let operand: Operand = resolve(&[0x42], AddressingMode::Immediate, nothing);
+        assert_eq!(operand, Operand { address : 0x0200, page_crossed : false });
+    }
+
+    #[test]
+    fn zero_page_and_absolute() {
+        assert_eq!(resolve(&[0x42], AddressingMode::ZeroPage, nothing).address, 0x0042);
+        assert_eq!(resolve(&[0x34, 0x12], AddressingMode::Absolute, nothing).address, 0x1234);
+    }
+
+    #[test]
+    fn zero_page_indexing_wraps_within_the_zero_page() {
+        let operand: Operand = resolve(&[0xF0], AddressingMode::ZeroPageX, |cpu, _| cpu.regX = 0x20);
+        assert_eq!(operand, Operand { address : 0x0010, page_crossed : false });
+        let operand: Operand = resolve(&[0xF0], AddressingMode::ZeroPageY, |cpu, _| cpu.regY = 0x11);
+        assert_eq!(operand, Operand { address : 0x0001, page_crossed : false });
+    }
+
+    #[test]
+    fn absolute_indexing_reports_page_crossing() {
+        let operand: Operand = resolve(&[0xF0, 0x12], AddressingMode::AbsoluteX, |cpu, _| cpu.regX = 0x0F);
+        assert_eq!(operand, Operand { address : 0x12FF, page_crossed : false });
+        let operand: Operand = resolve(&[0xF0, 0x12], AddressingMode::AbsoluteY, |cpu, _| cpu.regY = 0x10);
+        assert_eq!(operand, Operand { address : 0x1300, page_crossed : true });
+    }
+
+    #[test]
+    fn indexed_indirect_pointer_wraps_within_the_zero_page() {
+        let operand: Operand = resolve(&[0xFE], AddressingMode::IndirectX, |cpu, memory| {
+            cpu.regX = 0x01;
+            memory[0x00FF] = 0x34;
+            memory[0x0000] = 0x12;
+        });
+        assert_eq!(operand, Operand { address : 0x1234, page_crossed : false });
+    }
+
+    #[test]
+    fn indirect_indexed_pointer_wraps_and_reports_page_crossing() {
+        let operand: Operand = resolve(&[0xFF], AddressingMode::IndirectY, |cpu, memory| {
+            cpu.regY = 0x10;
+            memory[0x00FF] = 0xF8;
+            memory[0x0000] = 0x12;
+        });
+        assert_eq!(operand, Operand { address : 0x1308, page_crossed : true });
+    }
+
+    #[test]
+    fn indirect_keeps_the_nmos_page_wrap() {
+        let operand: Operand = resolve(&[0xFF, 0x03], AddressingMode::Indirect, |_, memory| {
+            memory[0x03FF] = 0x34;
+            memory[0x0300] = 0x12;
+            memory[0x0400] = 0x56;
+        });
+        assert_eq!(operand.address, 0x1234);
+    }
+
+    #[test]
+    fn relative_resolves_to_the_branch_target() {
+        let operand: Operand = resolve(&[0xFC], AddressingMode::Relative, nothing);
+        assert_eq!(operand, Operand { address : 0x01FD, page_crossed : true });
+        let operand: Operand = resolve(&[0x10], AddressingMode::Relative, nothing);
+        assert_eq!(operand, Operand { address : 0x0211, page_crossed : false });
+    }
+
+    #[test]
+    fn indexed_reads_only_pay_for_a_page_crossing() {
+        let (mut cpu, mut memory) = setup(0x0200, &[0xF0, 0x12, 0xF0, 0x12]);
+        cpu.regX = 0x01;
+        assert_eq!(cpu.read_operand(AddressingMode::AbsoluteX, &mut memory, MOS6502::lda), 0);
+        cpu.regX = 0x10;
+        memory[0x1300] = 0x42;
+        memory.accesses.clear();
+        assert_eq!(cpu.read_operand(AddressingMode::AbsoluteX, &mut memory, MOS6502::lda), 1);
+        assert_eq!(cpu.regA, 0x42);
+        assert_eq!(memory.accesses, vec![
+            Access::Read(0x0202), Access::Read(0x0203), Access::Read(0x1200), Access::Read(0x1300),
+        ]);
+    }
+
+    #[test]
+    fn indexed_writes_always_do_the_fix_up_read() {
+        let (mut cpu, mut memory) = setup(0x0200, &[0x00, 0x12]);
+        cpu.regY = 0x01;
+        assert_eq!(cpu.write_operand(AddressingMode::AbsoluteY, 0x42, &mut memory), 0);
+        assert_eq!(memory.accesses, vec![
+            Access::Read(0x0200), Access::Read(0x0201), Access::Read(0x1201), Access::Write(0x1201, 0x42),
+        ]);
+    }
+
+    #[test]
+    fn modify_does_the_fix_up_read_and_the_double_write() {
+        let (mut cpu, mut memory) = setup(0x0200, &[0xFF, 0x12]);
+        cpu.regX = 0x01;
+        memory[0x1300] = 0x41;
+        assert_eq!(cpu.modify_operand(AddressingMode::AbsoluteX, &mut memory, MOS6502::inc), 0);
+        assert_eq!(memory.accesses, vec![
+            Access::Read(0x0200), Access::Read(0x0201), Access::Read(0x1200),
+            Access::Read(0x1300), Access::Write(0x1300, 0x41), Access::Write(0x1300, 0x42),
+        ]);
+    }
+}
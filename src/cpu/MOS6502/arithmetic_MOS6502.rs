@@ -1,8 +1,7 @@
-use crate::cpu::memory::Memory; 
-
-use crate::cpu::cpu::CPU;
+use crate::cpu::bus::Bus; 
 
 use super::processor_status::ProcessorStatus;
+use super::addressing::AddressingMode;
 use super::MOS6502;
 
 fn on_arit_set_status(proc_status: &mut ProcessorStatus, value: u8) {
@@ -18,114 +17,49 @@ fn on_arit_set_status(proc_status: &mut ProcessorStatus, value: u8) {
     }
 }
 
+fn sbc_decimal(a: u8, value: u8, carry: u8) -> u8 {
+    let a: u16 = a as u16;
+    let value: u16 = value as u16;
+    let borrow: u16 = 1 - carry as u16;
 
-
-macro_rules! arit_im {
-    ($self:ident, $memory:ident, $call:tt) => {{
-        let byte: u8 = $self.fetch($memory);
-        $self.$call(byte);
-        1
-    }};
-}
-
-macro_rules! arit_zp {
-    ($self:ident, $memory:ident, $call:ident) => {{
-        let zero_page_address: u16 = $self.fetch($memory) as u16;
-        let value = $self.read(zero_page_address, $memory);
-        $self.$call(value);
-        2
-    }};
-}
-
-macro_rules! arit_zpx {
-    ($self:ident, $memory:ident, $call:ident) => {{
-        let base_address: u8 = $self.fetch($memory);
-        let effective_address: u8 = base_address.wrapping_add($self.regX);
-        let value: u8 = $memory[effective_address as usize];
-        $self.$call(value);
-        3
-    }};
-}
-
-macro_rules! arit_abs {
-    ($self:ident, $memory:ident, $call:ident) => {{
-        let low_byte: u8 = $self.fetch($memory);
-        let high_byte: u8 = $self.fetch($memory);
-        let address: u16 = ((high_byte as u16) << 8) | (low_byte as u16);
-        let value: u8 = $self.read(address, $memory);
-        $self.$call(value);
-        3
-    }};
-}
-
-macro_rules! arit_absx {
-    ($self:ident, $memory:ident, $call:ident) => {{
-        let low_byte: u8 = $self.fetch($memory);
-        let high_byte: u8 = $self.fetch($memory);
-        let base_address: u16 = ((high_byte as u16) << 8) | (low_byte as u16);
-        let effective_address: u16 = base_address.wrapping_add($self.regX as u16);
-        let value: u8 = $self.read(effective_address, $memory);
-        $self.$call(value);
-
-        if (base_address & 0xFF00) != (effective_address & 0xFF00) {
-            4
-        } else {
-            3
-        }
-    }};
-}
-
-macro_rules! arit_absy {
-    ($self:ident, $memory:ident, $call:ident) => {{
-        let low_byte: u8 = $self.fetch($memory);
-        let high_byte: u8 = $self.fetch($memory);
-        let base_address: u16 = ((high_byte as u16) << 8) | (low_byte as u16);
-        let effective_address: u16 = base_address.wrapping_add($self.regY as u16);
-        let value: u8 = $self.read(effective_address, $memory);
-        $self.$call(value);
-
-        if (base_address & 0xFF00) != (effective_address & 0xFF00) {
-            4
-        } else {
-            3
-        }
-    }};
-}
-
-macro_rules! arit_indx {
-    ($self:ident, $memory:ident, $call:ident) => {{
-        let base_address: u8 = $self.fetch($memory);
-        let indirect_address: u8 = base_address.wrapping_add($self.regX);
-        let low_byte: u8 = $memory[indirect_address as usize];
-        let high_byte: u8 = $memory[indirect_address.wrapping_add(1) as usize];
-        let final_address: u16 = ((high_byte as u16) << 8) | (low_byte as u16);
-        let value: u8 = $self.read(final_address, $memory);
-        $self.$call(value);
-        5
-    }};
+    let mut result: u16 = (a & 0x0F).wrapping_sub(value & 0x0F).wrapping_sub(borrow);
+    result = if result & 0x10 != 0 {
+        (result.wrapping_sub(0x06) & 0x0F) | (a & 0xF0).wrapping_sub(value & 0xF0).wrapping_sub(0x10)
+    } else {
+        (result & 0x0F) | (a & 0xF0).wrapping_sub(value & 0xF0)
+    };
+    if result & 0x100 != 0 {
+        result = result.wrapping_sub(0x60);
+    }
+    result as u8
 }
 
-macro_rules! arit_indy {
-    ($self:ident, $memory:ident, $call:ident) => {{
-        let base_address: u8 = $self.fetch($memory);
-        let low_byte: u8 = $memory[base_address as usize];
-        let high_byte: u8 = $memory[base_address.wrapping_add(1) as usize];
-        let base_address: u16 = ((high_byte as u16) << 8) | (low_byte as u16);
-        let effective_address: u16 = base_address.wrapping_add($self.regY as u16);
-        let value: u8 = $self.read(effective_address, $memory);
-        $self.$call(value);
-
-        if (base_address & 0xFF00) != (effective_address & 0xFF00) {
-            5
-        } else {
-            4
-        }
-    }};
+// 65C02 decimal subtraction, same as NMOS for valid BCD but not for invalid digits
+fn sbc_decimal_cmos(a: u8, value: u8, carry: u8) -> u8 {
+    let borrow: i16 = 1 - carry as i16;
+    let low_nibble: i16 = (a & 0x0F) as i16 - (value & 0x0F) as i16 - borrow;
+    let mut result: i16 = a as i16 - value as i16 - borrow;
+    if result < 0 {
+        result -= 0x60;
+    }
+    if low_nibble < 0 {
+        result -= 0x06;
+    }
+    result as u8
 }
 
 impl MOS6502 {
     // Add with carry
-    fn adc(&mut self, value: u8) {
+    pub(super) fn adc(&mut self, value: u8) {
+        if self.decimal_arithmetic() {
+            self.adc_decimal(value);
+            // The 65C02 sets N and Z from the decimal result
+            if self.quirks.cmos_instructions {
+                on_arit_set_status(&mut self.proc_status, self.regA);
+            }
+            return;
+        }
+
         let carry = if self.proc_status.carry() { 1 } else { 0 };
         let result = self.regA as u16 + value as u16 + carry as u16;
 
@@ -153,41 +87,89 @@ impl MOS6502 {
         on_arit_set_status(&mut self.proc_status, self.regA);
     }
     
-    pub fn adc_im(&mut self, memory : &Memory) -> u32 {
-        arit_im!(self, memory, adc)
+    // NMOS decimal addition: Z comes from the binary sum, N and V from the
+    // intermediate result after the low nibble adjustment, C from the BCD sum
+    fn adc_decimal(&mut self, value: u8) {
+        let carry: u16 = if self.proc_status.carry() { 1 } else { 0 };
+        let a: u16 = self.regA as u16;
+        let value: u16 = value as u16;
+
+        let binary_result: u16 = a + value + carry;
+        if binary_result as u8 == 0 {
+            self.proc_status.set_zero();
+        } else {
+            self.proc_status.clear_zero();
+        }
+
+        let mut result: u16 = (a & 0x0F) + (value & 0x0F) + carry;
+        if result > 0x09 {
+            result += 0x06;
+        }
+        result = if result <= 0x0F {
+            (result & 0x0F) + (a & 0xF0) + (value & 0xF0)
+        } else {
+            (result & 0x0F) + (a & 0xF0) + (value & 0xF0) + 0x10
+        };
+
+        if result & 0x80 != 0 {
+            self.proc_status.set_negative();
+        } else {
+            self.proc_status.clear_negative();
+        }
+        if ((a ^ result) & 0x80) != 0 && ((a ^ value) & 0x80) == 0 {
+            self.proc_status.set_overflow();
+        } else {
+            self.proc_status.clear_overflow();
+        }
+
+        if (result & 0x1F0) > 0x90 {
+            result += 0x60;
+        }
+        if (result & 0xFF0) > 0xF0 {
+            self.proc_status.set_carry();
+        } else {
+            self.proc_status.clear_carry();
+        }
+
+        self.regA = result as u8;
+    }
+
+    pub fn adc_im<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.read_operand(AddressingMode::Immediate, memory, MOS6502::adc)
     }
 
-    pub fn adc_zp(&mut self, memory : &Memory) -> u32 {
-        arit_zp!(self, memory, adc)
+    pub fn adc_zp<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.read_operand(AddressingMode::ZeroPage, memory, MOS6502::adc)
     }
 
-    pub fn adc_zpx(&mut self, memory : &Memory) -> u32 {
-        arit_zpx!(self, memory, adc)
+    pub fn adc_zpx<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.read_operand(AddressingMode::ZeroPageX, memory, MOS6502::adc)
     }
 
-    pub fn adc_abs(&mut self, memory : &Memory) -> u32 {
-        arit_abs!(self, memory, adc)
+    pub fn adc_abs<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.read_operand(AddressingMode::Absolute, memory, MOS6502::adc)
     }
 
-    pub fn adc_absx(&mut self, memory : &Memory) -> u32 {
-        arit_absx!(self, memory, adc)
+    pub fn adc_absx<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.read_operand(AddressingMode::AbsoluteX, memory, MOS6502::adc)
     }
 
-    pub fn adc_absy(&mut self, memory : &Memory) -> u32 {
-        arit_absy!(self, memory, adc)
+    pub fn adc_absy<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.read_operand(AddressingMode::AbsoluteY, memory, MOS6502::adc)
     }
 
-    pub fn adc_indx(&mut self, memory: &Memory) -> u32 {
-        arit_indx!(self, memory, adc)
+    pub fn adc_indx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::IndirectX, memory, MOS6502::adc)
     }
 
-    pub fn adc_indy(&mut self, memory: &Memory) -> u32 {
-        arit_indy!(self, memory, adc)
+    pub fn adc_indy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::IndirectY, memory, MOS6502::adc)
     }
 
     // Subtract with Carry
-    fn sbc(&mut self, value: u8) {
+    pub(super) fn sbc(&mut self, value: u8) {
         let carry = if self.proc_status.carry() { 1 } else { 0 };
+        let a = self.regA;
         let result = self.regA as i16 - value as i16 - (1 - carry as i16);
 
         // Update carry flag
@@ -212,38 +194,49 @@ impl MOS6502 {
 
         // Update zero and negative flags
         on_arit_set_status(&mut self.proc_status, self.regA);
+
+        // NMOS decimal subtraction keeps every flag from the binary result,
+        // only the accumulator is adjusted
+        if self.decimal_arithmetic() {
+            if self.quirks.cmos_instructions {
+                self.regA = sbc_decimal_cmos(a, value, carry as u8);
+                on_arit_set_status(&mut self.proc_status, self.regA);
+            } else {
+                self.regA = sbc_decimal(a, value, carry as u8);
+            }
+        }
     }
 
-    pub fn sbc_im(&mut self, memory: &Memory) -> u32 {
-        arit_im!(self, memory, sbc)
+    pub fn sbc_im<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Immediate, memory, MOS6502::sbc)
     }
 
-    pub fn sbc_zp(&mut self, memory: &Memory) -> u32 {
-        arit_zp!(self, memory, sbc)
+    pub fn sbc_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::ZeroPage, memory, MOS6502::sbc)
     }
 
-    pub fn sbc_zpx(&mut self, memory: &Memory) -> u32 {
-        arit_zpx!(self, memory, sbc)
+    pub fn sbc_zpx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::ZeroPageX, memory, MOS6502::sbc)
     }
 
-    pub fn sbc_abs(&mut self, memory: &Memory) -> u32 {
-        arit_abs!(self, memory, sbc)
+    pub fn sbc_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Absolute, memory, MOS6502::sbc)
     }
 
-    pub fn sbc_absx(&mut self, memory: &Memory) -> u32 {
-        arit_absx!(self, memory, sbc)
+    pub fn sbc_absx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::AbsoluteX, memory, MOS6502::sbc)
     }
 
-    pub fn sbc_absy(&mut self, memory: &Memory) -> u32 {
-        arit_absy!(self, memory, sbc)
+    pub fn sbc_absy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::AbsoluteY, memory, MOS6502::sbc)
     }
 
-    pub fn sbc_indx(&mut self, memory: &Memory) -> u32 {
-        arit_indx!(self, memory, sbc)
+    pub fn sbc_indx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::IndirectX, memory, MOS6502::sbc)
     }
 
-    pub fn sbc_indy(&mut self, memory: &Memory) -> u32 {
-        arit_indy!(self, memory, sbc)
+    pub fn sbc_indy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::IndirectY, memory, MOS6502::sbc)
     }
 
     // Compare
@@ -271,74 +264,202 @@ impl MOS6502 {
         }
     }
 
-    fn cmpa2cmp(&mut self, value: u8) {
+    pub(super) fn cmpa2cmp(&mut self, value: u8) {
         let result = self.regA.wrapping_sub(value);
         self.cmp(self.regA, result, value)
     }
 
-    fn cmpx2cmp(&mut self, value: u8) {
+    pub(super) fn cmpx2cmp(&mut self, value: u8) {
         let result = self.regX.wrapping_sub(value);
         self.cmp(self.regX, result, value)
     }
 
-    fn cmpy2cmp(&mut self, value: u8) {
+    pub(super) fn cmpy2cmp(&mut self, value: u8) {
         let result = self.regY.wrapping_sub(value);
         self.cmp(self.regY, result, value)
     }
 
-    pub fn cmp_im(&mut self, memory: &Memory) -> u32 {
-        arit_im!(self, memory, cmpa2cmp)
+    pub fn cmp_im<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Immediate, memory, MOS6502::cmpa2cmp)
+    }
+
+    pub fn cmp_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::ZeroPage, memory, MOS6502::cmpa2cmp)
+    }
+
+    pub fn cmp_zpx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::ZeroPageX, memory, MOS6502::cmpa2cmp)
+    }
+
+    pub fn cmp_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Absolute, memory, MOS6502::cmpa2cmp)
+    }
+
+    pub fn cmp_absx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::AbsoluteX, memory, MOS6502::cmpa2cmp)
     }
 
-    pub fn cmp_zp(&mut self, memory: &Memory) -> u32 {
-        arit_zp!(self, memory, cmpa2cmp)
+    pub fn cmp_absy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::AbsoluteY, memory, MOS6502::cmpa2cmp)
     }
 
-    pub fn cmp_zpx(&mut self, memory: &Memory) -> u32 {
-        arit_zpx!(self, memory, cmpa2cmp)
+    pub fn cmp_indx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::IndirectX, memory, MOS6502::cmpa2cmp)
     }
 
-    pub fn cmp_abs(&mut self, memory: &Memory) -> u32 {
-        arit_abs!(self, memory, cmpa2cmp)
+    pub fn cmp_indy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::IndirectY, memory, MOS6502::cmpa2cmp)
     }
 
-    pub fn cmp_absx(&mut self, memory: &Memory) -> u32 {
-        arit_absx!(self, memory, cmpa2cmp)
+    pub fn cpx_im<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Immediate, memory, MOS6502::cmpx2cmp)
     }
 
-    pub fn cmp_absy(&mut self, memory: &Memory) -> u32 {
-        arit_absy!(self, memory, cmpa2cmp)
+    pub fn cpx_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::ZeroPage, memory, MOS6502::cmpx2cmp)
     }
 
-    pub fn cmp_indx(&mut self, memory: &Memory) -> u32 {
-        arit_indx!(self, memory, cmpa2cmp)
+    pub fn cpx_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Absolute, memory, MOS6502::cmpx2cmp)
     }
 
-    pub fn cmp_indy(&mut self, memory: &Memory) -> u32 {
-        arit_indy!(self, memory, cmpa2cmp)
+    pub fn cpy_im<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Immediate, memory, MOS6502::cmpy2cmp)
     }
 
-    pub fn cpx_im(&mut self, memory: &Memory) -> u32 {
-        arit_im!(self, memory, cmpx2cmp)
+    pub fn cpy_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::ZeroPage, memory, MOS6502::cmpy2cmp)
     }
 
-    pub fn cpx_zp(&mut self, memory: &Memory) -> u32 {
-        arit_zp!(self, memory, cmpx2cmp)
+    pub fn cpy_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Absolute, memory, MOS6502::cmpy2cmp)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+
+    use super::super::{setup, setup_variant, Variant, MOS6502};
 
-    pub fn cpx_abs(&mut self, memory: &Memory) -> u32 {
-        arit_abs!(self, memory, cmpx2cmp)
+    // Flags and accumulator after a decimal ADC or SBC
+    #[derive(Debug, PartialEq, Eq)]
+    struct Outcome {
+        a: u8,
+        carry: bool,
+        zero: bool,
+        negative: bool,
+        overflow: bool,
     }
 
-    pub fn cpy_im(&mut self, memory: &Memory) -> u32 {
-        arit_im!(self, memory, cmpy2cmp)
+    fn run(subtract: bool, a: u8, value: u8, carry: bool) -> Outcome {
+        let mut cpu: MOS6502 = MOS6502::new();
+        cpu.regA = a;
+        cpu.proc_status.set_decimal_mode();
+        if carry {
+            cpu.proc_status.set_carry();
+        } else {
+            cpu.proc_status.clear_carry();
+        }
+        if subtract {
+            cpu.sbc(value);
+        } else {
+            cpu.adc(value);
+        }
+        Outcome {
+            a : cpu.regA,
+            carry : cpu.proc_status.carry(),
+            zero : cpu.proc_status.zero(),
+            negative : cpu.proc_status.negative(),
+            overflow : cpu.proc_status.overflow(),
+        }
     }
 
-    pub fn cpy_zp(&mut self, memory: &Memory) -> u32 {
-        arit_zp!(self, memory, cmpy2cmp)
+    // Bruce Clark's reference model from "Decimal Mode", appendix B, sequences 1 and 2
+    fn reference_adc(a: u8, value: u8, carry: bool) -> Outcome {
+        let carry: i32 = carry as i32;
+        let mut low: i32 = (a & 0x0F) as i32 + (value & 0x0F) as i32 + carry;
+        if low >= 0x0A {
+            low = ((low + 0x06) & 0x0F) + 0x10;
+        }
+        let mut sum: i32 = (a & 0xF0) as i32 + (value & 0xF0) as i32 + low;
+        let signed: i32 = (a & 0xF0) as i8 as i32 + (value & 0xF0) as i8 as i32 + low;
+        let intermediate: u8 = sum as u8;
+        if sum >= 0xA0 {
+            sum += 0x60;
+        }
+        let binary: u8 = a.wrapping_add(value).wrapping_add(carry as u8);
+        Outcome {
+            a : sum as u8,
+            carry : sum >= 0x100,
+            zero : binary == 0,
+            negative : intermediate & 0x80 != 0,
+            overflow : !(-128..=127).contains(&signed),
+        }
     }
 
-    pub fn cpy_abs(&mut self, memory: &Memory) -> u32 {
-        arit_abs!(self, memory, cmpy2cmp)
+    // Sequence 3: every flag comes from the binary subtraction
+    fn reference_sbc(a: u8, value: u8, carry: bool) -> Outcome {
+        let borrow: i32 = 1 - carry as i32;
+        let binary: i32 = a as i32 - value as i32 - borrow;
+        let mut low: i32 = (a & 0x0F) as i32 - (value & 0x0F) as i32 - borrow;
+        if low < 0 {
+            low = ((low - 0x06) & 0x0F) - 0x10;
+        }
+        let mut difference: i32 = (a & 0xF0) as i32 - (value & 0xF0) as i32 + low;
+        if difference < 0 {
+            difference -= 0x60;
+        }
+        let signed: i32 = a as i8 as i32 - value as i8 as i32 - borrow;
+        Outcome {
+            a : difference as u8,
+            carry : binary >= 0,
+            zero : binary as u8 == 0,
+            negative : binary & 0x80 != 0,
+            overflow : !(-128..=127).contains(&signed),
+        }
+    }
+
+    #[test]
+    fn nmos_decimal_matches_reference() {
+        for a in 0..=0xFF_u8 {
+            for value in 0..=0xFF_u8 {
+                for carry in [false, true] {
+                    assert_eq!(run(false, a, value, carry), reference_adc(a, value, carry),
+                               "ADC A={:02X} operand={:02X} C={}", a, value, carry);
+                    assert_eq!(run(true, a, value, carry), reference_sbc(a, value, carry),
+                               "SBC A={:02X} operand={:02X} C={}", a, value, carry);
+                }
+            }
+        }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn decimal_through_the_opcodes() {
+        // SED; CLC; LDA #$19; ADC #$28; SEC; SBC #$48
+        let (mut cpu, mut memory) = setup(0x0200, &[0xF8, 0x18, 0xA9, 0x19, 0x69, 0x28, 0x38, 0xE9, 0x48]);
+        cpu.execute(8, &mut memory).unwrap();
+        assert_eq!(cpu.regA, 0x47);
+        cpu.execute(4, &mut memory).unwrap();
+        assert_eq!(cpu.regA, 0x99);
+        assert!(!cpu.proc_status.carry());
+    }
+
+    #[test]
+    fn ricoh_ignores_decimal_mode() {
+        for variant in [Variant::RP2A03, Variant::RP2A07] {
+            // SED; CLC; LDA #$19; ADC #$28; SEC; SBC #$0A; PHP
+            let (mut cpu, mut memory) = setup_variant(variant, 0x0200, &[0xF8, 0x18, 0xA9, 0x19, 0x69, 0x28, 0x38, 0xE9, 0x0A, 0x08]);
+            cpu.execute(8, &mut memory).unwrap();
+            assert_eq!(cpu.regA, 0x41);
+            cpu.execute(4, &mut memory).unwrap();
+            assert_eq!(cpu.regA, 0x37);
+            assert!(cpu.proc_status.carry());
+
+            // D is still stored and pushed
+            cpu.execute(3, &mut memory).unwrap();
+            assert!(cpu.proc_status.decimal_mode());
+            assert_eq!(memory[0x01FF] & 0x08, 0x08);
+        }
+    }
+}
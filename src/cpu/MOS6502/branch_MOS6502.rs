@@ -0,0 +1,118 @@
+use crate::cpu::bus::Bus; 
+
+use crate::cpu::cpu::CPU;
+
+use super::MOS6502;
+
+impl MOS6502 {
+    pub(super) fn branch<B: Bus>(&mut self, memory: &mut B, condition: bool) -> u32 {
+        // The offset is a signed byte relative to the instruction that follows the branch
+        let offset: i8 = self.fetch(memory) as i8;
+        if !condition {
+            return 0;
+        }
+        let base_address: u16 = self.regPC;
+        self.regPC = base_address.wrapping_add(offset as u16);
+
+        // A taken branch costs one more cycle, and another one if the target is on a different page
+        if (base_address & 0xFF00) != (self.regPC & 0xFF00) {
+            2
+        } else {
+            1
+        }
+    }
+
+    // Branch on carry
+    pub fn bcc<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.branch(memory, !self.proc_status.carry())
+    }
+
+    pub fn bcs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.branch(memory, self.proc_status.carry())
+    }
+
+    // Branch on zero
+    pub fn bne<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.branch(memory, !self.proc_status.zero())
+    }
+
+    pub fn beq<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.branch(memory, self.proc_status.zero())
+    }
+
+    // Branch on negative
+    pub fn bpl<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.branch(memory, !self.proc_status.negative())
+    }
+
+    pub fn bmi<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.branch(memory, self.proc_status.negative())
+    }
+
+    // Branch on overflow
+    pub fn bvc<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.branch(memory, !self.proc_status.overflow())
+    }
+
+    pub fn bvs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.branch(memory, self.proc_status.overflow())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+    use crate::cpu::memory::Memory;
+
+    use super::super::MOS6502;
+
+    // Places a branch at `address` and runs exactly `cycles` cycles of it
+    fn branch(opcode: u8, offset: u8, address: u16, flags: u8, cycles: u32) -> u16 {
+        let mut cpu: MOS6502 = MOS6502::new();
+        let mut memory: Memory = Memory::new();
+        memory[address as usize] = opcode;
+        memory[address as usize + 1] = offset;
+        cpu.regPC = address;
+        if flags & 0x01 != 0 { cpu.proc_status.set_carry(); }
+        if flags & 0x02 != 0 { cpu.proc_status.set_zero(); }
+        if flags & 0x40 != 0 { cpu.proc_status.set_overflow(); }
+        if flags & 0x80 != 0 { cpu.proc_status.set_negative(); }
+        cpu.execute(cycles, &mut memory).unwrap();
+        cpu.regPC
+    }
+
+    #[test]
+    fn every_branch_tests_its_flag() {
+        // Opcode, flag it reads, and whether it branches when the flag is set
+        let branches: [(u8, u8, bool); 8] = [
+            (0x90, 0x01, false), (0xB0, 0x01, true),
+            (0xD0, 0x02, false), (0xF0, 0x02, true),
+            (0x10, 0x80, false), (0x30, 0x80, true),
+            (0x50, 0x40, false), (0x70, 0x40, true),
+        ];
+        for (opcode, flag, when_set) in branches {
+            let (taken, not_taken): (u8, u8) = if when_set { (flag, 0) } else { (0, flag) };
+            assert_eq!(branch(opcode, 0x10, 0x0200, taken, 3), 0x0212, "{:#04X} taken", opcode);
+            assert_eq!(branch(opcode, 0x10, 0x0200, not_taken, 2), 0x0202, "{:#04X} not taken", opcode);
+        }
+    }
+
+    #[test]
+    fn backward_branch_within_page() {
+        assert_eq!(branch(0xD0, 0xFC, 0x0240, 0, 3), 0x023E);
+    }
+
+    #[test]
+    fn page_crossing_costs_an_extra_cycle() {
+        // Backwards from $0202 into page $01
+        assert_eq!(branch(0xD0, 0xFC, 0x0200, 0, 4), 0x01FE);
+        // Forwards from $02F0 into page $03
+        assert_eq!(branch(0xF0, 0x20, 0x02F0, 0x02, 4), 0x0312);
+    }
+
+    #[test]
+    fn offset_is_relative_to_the_next_instruction() {
+        // An offset of -2 branches back onto the branch itself
+        assert_eq!(branch(0xB0, 0xFE, 0x0200, 0x01, 3), 0x0200);
+    }
+}
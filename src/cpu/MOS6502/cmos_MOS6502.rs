@@ -0,0 +1,422 @@
+use crate::cpu::bus::Bus; 
+
+use crate::cpu::cpu::CPU;
+
+use super::addressing::{AddressingMode, Operand};
+use super::opcodes::{Opcode, OPCODES_65C02};
+use super::MOS6502;
+
+use num_enum::TryFromPrimitive;
+use num_enum::IntoPrimitive;
+use std::convert::TryFrom;
+
+// Opcodes added or changed by the 65C02. RMB, SMB, BBR and BBS are decoded from
+// the low nibble, ADC and SBC in every mode from the opcode table
+#[derive(Debug, TryFromPrimitive, IntoPrimitive)]
+#[repr(u8)]
+pub(super) enum CmosInstr {
+    BRA = 0x80,
+
+    STZ_ZP = 0x64,
+    STZ_ZPX = 0x74,
+    STZ_ABS = 0x9C,
+    STZ_ABSX = 0x9E,
+
+    PHX = 0xDA,
+    PHY = 0x5A,
+    PLX = 0xFA,
+    PLY = 0x7A,
+
+    TSB_ZP = 0x04,
+    TSB_ABS = 0x0C,
+    TRB_ZP = 0x14,
+    TRB_ABS = 0x1C,
+
+    INC_ACC = 0x1A,
+    DEC_ACC = 0x3A,
+
+    ORA_ZPI = 0x12,
+    AND_ZPI = 0x32,
+    EOR_ZPI = 0x52,
+    STA_ZPI = 0x92,
+    LDA_ZPI = 0xB2,
+    CMP_ZPI = 0xD2,
+
+    BIT_IM = 0x89,
+    BIT_ZPX = 0x34,
+    BIT_ABSX = 0x3C,
+
+    JMP_IND = 0x6C,
+    JMP_ABSX_IND = 0x7C,
+
+    // Shifts on abs,X skip the fix-up cycle when no page is crossed
+    ASL_ABSX = 0x1E,
+    LSR_ABSX = 0x5E,
+    ROL_ABSX = 0x3E,
+    ROR_ABSX = 0x7E,
+
+    WAI = 0xCB,
+    STP = 0xDB,
+}
+
+// RMBn and SMBn operations, indexed by bit number
+pub(super) const RESET_BIT: [fn(&mut MOS6502, u8) -> u8; 8] = [
+    MOS6502::rmb::<0>,
+    MOS6502::rmb::<1>,
+    MOS6502::rmb::<2>,
+    MOS6502::rmb::<3>,
+    MOS6502::rmb::<4>,
+    MOS6502::rmb::<5>,
+    MOS6502::rmb::<6>,
+    MOS6502::rmb::<7>,
+];
+
+pub(super) const SET_BIT: [fn(&mut MOS6502, u8) -> u8; 8] = [
+    MOS6502::smb::<0>,
+    MOS6502::smb::<1>,
+    MOS6502::smb::<2>,
+    MOS6502::smb::<3>,
+    MOS6502::smb::<4>,
+    MOS6502::smb::<5>,
+    MOS6502::smb::<6>,
+    MOS6502::smb::<7>,
+];
+
+// Bit number of RMB, SMB, BBR and BBS sits in bits 4-6 of the opcode, bit 7 selects the set form
+pub(super) fn bit_operation(instruction: u8) -> fn(&mut MOS6502, u8) -> u8 {
+    let bit: usize = ((instruction >> 4) & 0x07) as usize;
+    if instruction & 0x80 != 0 {
+        SET_BIT[bit]
+    } else {
+        RESET_BIT[bit]
+    }
+}
+
+pub(super) fn bit_condition(instruction: u8, value: u8) -> bool {
+    let mask: u8 = 1 << ((instruction >> 4) & 0x07);
+    (value & mask != 0) == (instruction & 0x80 != 0)
+}
+
+impl MOS6502 {
+    fn rmb<const BIT: u8>(&mut self, value: u8) -> u8 {
+        value & !(1 << BIT)
+    }
+
+    fn smb<const BIT: u8>(&mut self, value: u8) -> u8 {
+        value | (1 << BIT)
+    }
+
+    // Z from A AND value, then the bits set in A are set (TSB) or cleared (TRB) in memory
+    pub(super) fn tsb(&mut self, value: u8) -> u8 {
+        self.test_bits(value);
+        value | self.regA
+    }
+
+    pub(super) fn trb(&mut self, value: u8) -> u8 {
+        self.test_bits(value);
+        value & !self.regA
+    }
+
+    // BIT #imm only affects Z
+    pub(super) fn test_bits(&mut self, value: u8) {
+        if (self.regA & value) == 0 {
+            self.proc_status.set_zero();
+        } else {
+            self.proc_status.clear_zero();
+        }
+    }
+
+    pub(super) fn inc_acc(&mut self) -> u32 {
+        self.regA = self.inc(self.regA);
+        0
+    }
+
+    pub(super) fn dec_acc(&mut self) -> u32 {
+        self.regA = self.dec(self.regA);
+        0
+    }
+
+    // WAI ends on any interrupt request, even an IRQ masked by I
+    pub(super) fn wake_up(&mut self) -> bool {
+        if self.nmi_pending || self.irq_line {
+            self.waiting = false;
+        }
+        !self.waiting
+    }
+
+    // Executes opcodes whose behaviour differs on the 65C02 and returns their penalty cycles.
+    // None when the NMOS handler applies as is
+    pub(super) fn execute_cmos<B: Bus>(&mut self, instruction: u8, memory: &mut B) -> Option<u32> {
+        let opcode: &Opcode = &OPCODES_65C02[instruction as usize];
+        if !opcode.documented {
+            return Some(self.skip(opcode));
+        }
+
+        match instruction & 0x0F {
+            0x07 => return Some(self.modify_operand(AddressingMode::ZeroPage, memory, bit_operation(instruction))),
+            0x0F => return Some(self.branch_on_bit(instruction, memory)),
+            _ => {}
+        }
+
+        // Every addressing mode, (zp) included. The decimal mode cycle is in the table
+        match opcode.mnemonic {
+            "ADC" => return Some(self.read_operand(opcode.mode, memory, MOS6502::adc)),
+            "SBC" => return Some(self.read_operand(opcode.mode, memory, MOS6502::sbc)),
+            _ => {}
+        }
+
+        let penalty: u32 = match CmosInstr::try_from(instruction) {
+            // Always taken, the table already counts that cycle
+            Ok(CmosInstr::BRA) => self.branch(memory, true) - 1,
+            Ok(CmosInstr::STZ_ZP) => self.write_operand(AddressingMode::ZeroPage, 0, memory),
+            Ok(CmosInstr::STZ_ZPX) => self.write_operand(AddressingMode::ZeroPageX, 0, memory),
+            Ok(CmosInstr::STZ_ABS) => self.write_operand(AddressingMode::Absolute, 0, memory),
+            Ok(CmosInstr::STZ_ABSX) => self.write_operand(AddressingMode::AbsoluteX, 0, memory),
+            Ok(CmosInstr::PHX) => {
+                self.push(self.regX, memory);
+                0
+            }
+            Ok(CmosInstr::PHY) => {
+                self.push(self.regY, memory);
+                0
+            }
+            Ok(CmosInstr::PLX) => {
+                let value: u8 = self.pull(memory);
+                self.ldx(value);
+                0
+            }
+            Ok(CmosInstr::PLY) => {
+                let value: u8 = self.pull(memory);
+                self.ldy(value);
+                0
+            }
+            Ok(CmosInstr::TSB_ZP) => self.modify_operand(AddressingMode::ZeroPage, memory, MOS6502::tsb),
+            Ok(CmosInstr::TSB_ABS) => self.modify_operand(AddressingMode::Absolute, memory, MOS6502::tsb),
+            Ok(CmosInstr::TRB_ZP) => self.modify_operand(AddressingMode::ZeroPage, memory, MOS6502::trb),
+            Ok(CmosInstr::TRB_ABS) => self.modify_operand(AddressingMode::Absolute, memory, MOS6502::trb),
+            Ok(CmosInstr::INC_ACC) => self.inc_acc(),
+            Ok(CmosInstr::DEC_ACC) => self.dec_acc(),
+            Ok(CmosInstr::ORA_ZPI) => self.read_operand(AddressingMode::ZeroPageIndirect, memory, MOS6502::ora),
+            Ok(CmosInstr::AND_ZPI) => self.read_operand(AddressingMode::ZeroPageIndirect, memory, MOS6502::and),
+            Ok(CmosInstr::EOR_ZPI) => self.read_operand(AddressingMode::ZeroPageIndirect, memory, MOS6502::eor),
+            Ok(CmosInstr::STA_ZPI) => self.write_operand(AddressingMode::ZeroPageIndirect, self.regA, memory),
+            Ok(CmosInstr::LDA_ZPI) => self.read_operand(AddressingMode::ZeroPageIndirect, memory, MOS6502::lda),
+            Ok(CmosInstr::CMP_ZPI) => self.read_operand(AddressingMode::ZeroPageIndirect, memory, MOS6502::cmpa2cmp),
+            Ok(CmosInstr::BIT_IM) => self.read_operand(AddressingMode::Immediate, memory, MOS6502::test_bits),
+            Ok(CmosInstr::BIT_ZPX) => self.read_operand(AddressingMode::ZeroPageX, memory, MOS6502::bit),
+            Ok(CmosInstr::BIT_ABSX) => self.read_operand(AddressingMode::AbsoluteX, memory, MOS6502::bit),
+            Ok(CmosInstr::JMP_IND) => self.jump_operand(AddressingMode::Indirect, memory),
+            Ok(CmosInstr::JMP_ABSX_IND) => self.jump_operand(AddressingMode::AbsoluteIndexedIndirect, memory),
+            Ok(CmosInstr::ASL_ABSX) => self.shift_operand(AddressingMode::AbsoluteX, memory, MOS6502::asl),
+            Ok(CmosInstr::LSR_ABSX) => self.shift_operand(AddressingMode::AbsoluteX, memory, MOS6502::lsr),
+            Ok(CmosInstr::ROL_ABSX) => self.shift_operand(AddressingMode::AbsoluteX, memory, MOS6502::rol),
+            Ok(CmosInstr::ROR_ABSX) => self.shift_operand(AddressingMode::AbsoluteX, memory, MOS6502::ror),
+            Ok(CmosInstr::WAI) => {
+                self.read(self.regPC, memory);
+                self.read(self.regPC, memory);
+                self.waiting = true;
+                0
+            }
+            Ok(CmosInstr::STP) => {
+                self.read(self.regPC, memory);
+                self.read(self.regPC, memory);
+                self.stopped = true;
+                0
+            }
+            Err(_) => return None,
+        };
+        Some(penalty)
+    }
+
+    // Shifts on abs,X are read-modify-writes that only take the fix-up cycle when indexing
+    // crosses a page, that is the penalty cycle returned
+    fn shift_operand<B: Bus>(&mut self, mode: AddressingMode, memory: &mut B, operation: fn(&mut MOS6502, u8) -> u8) -> u32 {
+        let operand: Operand = self.resolve(mode, memory);
+        if operand.page_crossed {
+            self.read(operand.address.wrapping_sub(0x0100), memory);
+        }
+        self.read_modify_write(operand.address, memory, operation);
+        operand.page_crossed as u32
+    }
+
+    fn jump_operand<B: Bus>(&mut self, mode: AddressingMode, memory: &mut B) -> u32 {
+        let operand: Operand = self.resolve(mode, memory);
+        self.regPC = operand.address;
+        0
+    }
+
+    // BBR and BBS test a zero page bit, then branch relative
+    fn branch_on_bit<B: Bus>(&mut self, instruction: u8, memory: &mut B) -> u32 {
+        let operand: Operand = self.resolve(AddressingMode::ZeroPage, memory);
+        let value: u8 = self.read(operand.address, memory);
+        self.read(operand.address, memory);
+        self.branch(memory, bit_condition(instruction, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+    use crate::cpu::error::ExecutionError;
+    use crate::cpu::bus::{Access, RecordingBus};
+
+    use super::super::{setup_variant, CoreMode, Variant, MOS6502};
+
+    const CORES: [CoreMode; 2] = [CoreMode::Instruction, CoreMode::Cycle];
+
+    fn cmos_setup(address: u16, program: &[u8], mode: CoreMode) -> (MOS6502, RecordingBus) {
+        let (mut cpu, memory) = setup_variant(Variant::CMOS65C02, address, program);
+        cpu.set_core_mode(mode);
+        (cpu, memory)
+    }
+
+    #[test]
+    fn bra_always_branches() {
+        for mode in CORES {
+            let (mut cpu, mut memory) = cmos_setup(0x0200, &[0x80, 0x05], mode);
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 3);
+            assert_eq!(cpu.regPC, 0x0207);
+
+            // One more cycle into the next page
+            let (mut cpu, mut memory) = cmos_setup(0x02F0, &[0x80, 0x20], mode);
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 4);
+            assert_eq!(cpu.regPC, 0x0312);
+        }
+    }
+
+    #[test]
+    fn stz_stores_zero() {
+        for mode in CORES {
+            let (mut cpu, mut memory) = cmos_setup(0x0200, &[0x64, 0x10, 0x9E, 0xFF, 0x02], mode);
+            memory[0x0010] = 0xAA;
+            memory[0x0300] = 0xAA;
+            cpu.regA = 0x55;
+            cpu.regX = 0x01;
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 3);
+            assert_eq!(memory[0x0010], 0x00);
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 5);
+            assert_eq!(memory[0x0300], 0x00);
+        }
+    }
+
+    #[test]
+    fn tsb_and_trb_test_then_change_bits() {
+        for mode in CORES {
+            let (mut cpu, mut memory) = cmos_setup(0x0200, &[0x04, 0x10, 0x1C, 0x00, 0x03], mode);
+            memory[0x0010] = 0x33;
+            memory[0x0300] = 0xF0;
+            cpu.regA = 0x0F;
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 5);
+            assert_eq!(memory[0x0010], 0x3F);
+            assert!(!cpu.proc_status.zero());
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 6);
+            assert_eq!(memory[0x0300], 0xF0);
+            assert!(cpu.proc_status.zero());
+        }
+    }
+
+    #[test]
+    fn bbr_and_bbs_test_a_zero_page_bit() {
+        for mode in CORES {
+            // BBR0 not taken, BBS0 taken
+            let (mut cpu, mut memory) = cmos_setup(0x0200, &[0x0F, 0x10, 0x05, 0x8F, 0x10, 0x05], mode);
+            memory[0x0010] = 0x01;
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 5);
+            assert_eq!(cpu.regPC, 0x0203);
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 6);
+            assert_eq!(cpu.regPC, 0x020B);
+        }
+    }
+
+    #[test]
+    fn rmb_and_smb_change_one_bit() {
+        for mode in CORES {
+            let (mut cpu, mut memory) = cmos_setup(0x0200, &[0x37, 0x10, 0xC7, 0x10], mode);
+            memory[0x0010] = 0x08;
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 5);
+            assert_eq!(memory[0x0010], 0x00);
+            cpu.step(&mut memory).unwrap();
+            assert_eq!(memory[0x0010], 0x10);
+        }
+    }
+
+    #[test]
+    fn wai_waits_for_an_interrupt_request() {
+        for mode in CORES {
+            let (mut cpu, mut memory) = cmos_setup(0x0200, &[0xCB, 0xEA], mode);
+            cpu.proc_status.set_interrupt_disable();
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 3);
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 1);
+            assert_eq!(cpu.regPC, 0x0201);
+
+            // A masked IRQ still ends the wait, execution carries on with the next instruction
+            cpu.irq(true);
+            cpu.step(&mut memory).unwrap();
+            assert_eq!(cpu.regPC, 0x0202);
+        }
+    }
+
+    #[test]
+    fn stp_stops_until_reset() {
+        for mode in CORES {
+            let (mut cpu, mut memory) = cmos_setup(0x0200, &[0xDB, 0xEA], mode);
+            memory[0xFFFC] = 0x00;
+            memory[0xFFFD] = 0x04;
+            assert_eq!(cpu.step(&mut memory), Err(ExecutionError::Stopped { pc: 0x0201 }));
+            assert!(cpu.halted());
+            assert_eq!(cpu.step(&mut memory), Err(ExecutionError::Stopped { pc: 0x0201 }));
+
+            cpu.reset(&mut memory);
+            assert!(!cpu.halted());
+            assert_eq!(cpu.regPC, 0x0400);
+        }
+    }
+
+    #[test]
+    fn jmp_indirect_reads_across_the_page() {
+        for mode in CORES {
+            let (mut cpu, mut memory) = cmos_setup(0x0200, &[0x6C, 0xFF, 0x02], mode);
+            memory[0x02FF] = 0x34;
+            memory[0x0300] = 0x12;
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 6);
+            assert_eq!(cpu.regPC, 0x1234);
+        }
+    }
+
+    #[test]
+    fn read_modify_write_reads_twice() {
+        for mode in CORES {
+            let (mut cpu, mut memory) = cmos_setup(0x0200, &[0xE6, 0x10], mode);
+            memory[0x0010] = 0x41;
+            cpu.step(&mut memory).unwrap();
+            assert_eq!(memory.take(), vec![
+                Access::Read(0x0200),
+                Access::Read(0x0201),
+                Access::Read(0x0010),
+                Access::Read(0x0010),
+                Access::Write(0x0010, 0x42),
+            ]);
+        }
+    }
+
+    #[test]
+    fn decimal_mode_costs_a_cycle_and_sets_valid_flags() {
+        for mode in CORES {
+            // 99 + 01 = 00 with carry, Z and N come from the decimal result
+            let (mut cpu, mut memory) = cmos_setup(0x0200, &[0x69, 0x01, 0x69, 0x01], mode);
+            cpu.proc_status.set_decimal_mode();
+            cpu.regA = 0x99;
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 3);
+            assert_eq!(cpu.regA, 0x00);
+            assert!(cpu.proc_status.carry());
+            assert!(cpu.proc_status.zero());
+            assert!(!cpu.proc_status.negative());
+
+            // Binary mode keeps the documented timing
+            cpu.proc_status.clear_decimal_mode();
+            cpu.proc_status.clear_carry();
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 2);
+            assert_eq!(cpu.regA, 0x01);
+        }
+    }
+}
@@ -0,0 +1,1235 @@
+use crate::cpu::bus::Bus; 
+
+use crate::cpu::cpu::CPU;
+use crate::cpu::error::ExecutionError;
+
+use super::addressing::AddressingMode;
+use super::illegal_MOS6502::UndocumentedInstr;
+use super::interrupt_MOS6502::{IRQ_VECTOR, NMI_VECTOR};
+use super::cmos_MOS6502::{bit_condition, bit_operation, CmosInstr};
+use super::opcodes::{opcode, Opcode, OPCODES_65C02};
+use super::processor_status::ProcessorStatus;
+use super::stack_MOS6502::STACK_PAGE;
+use super::{Instr, UnknownOpcodePolicy, MOS6502};
+
+use std::convert::TryFrom;
+
+// What an opcode does once fetched, this decides its bus activity cycle by cycle
+#[derive(Clone, Copy)]
+enum Operation {
+    Implied(fn(&mut MOS6502) -> u32),
+    Read(AddressingMode, fn(&mut MOS6502, u8)),
+    Write(AddressingMode, fn(&mut MOS6502) -> u8),
+    // SHA, SHX, SHY and TAS: the value is ANDed with the base high byte + 1, see set_store_high_and
+    WriteHigh(AddressingMode, fn(&mut MOS6502) -> u8),
+    Modify(AddressingMode, fn(&mut MOS6502, u8) -> u8),
+    // 65C02 ADC and SBC, one more cycle in decimal mode
+    Arithmetic(AddressingMode, fn(&mut MOS6502, u8)),
+    // 65C02 shifts on abs,X: a Modify that only takes the fix-up cycle when a page is crossed
+    Shift(AddressingMode, fn(&mut MOS6502, u8) -> u8),
+    Branch(fn(&ProcessorStatus) -> bool),
+    // 65C02 BBR and BBS, the opcode selects the bit and the condition
+    BranchOnBit(u8),
+    Push(fn(&mut MOS6502) -> u8),
+    Pull(fn(&mut MOS6502, u8)),
+    JmpAbsolute,
+    JmpIndirect,
+    JmpIndexedIndirect,
+    Jsr,
+    Rts,
+    Rti,
+    Brk,
+    Interrupt(u16),
+    Jam,
+    Wait,
+    Stop,
+    // Unknown opcode under the Nop policy, or undefined on the 65C02: operand fetches, then idle reads of PC
+    Skip(&'static Opcode),
+}
+
+// Instruction in flight in the cycle-stepped core
+pub(super) struct CycleState {
+    operation: Operation,
+    // Cycles done since the opcode fetch
+    cycle: u8,
+    // Cycles done on the operand once its address is known
+    data_cycle: u8,
+    address_ready: bool,
+    // Indexed modes first access the address before the page carry is applied
+    fixup: bool,
+    page_crossed: bool,
+    base_high: u8,
+    pointer: u8,
+    address: u16,
+    value: u8,
+    // An NMI took over the vector of this BRK or IRQ
+    hijacked: bool,
+}
+
+impl CycleState {
+    fn new(operation: Operation) -> Self {
+        CycleState {
+            operation,
+            cycle: 0,
+            data_cycle: 0,
+            address_ready: false,
+            fixup: false,
+            page_crossed: false,
+            base_high: 0,
+            pointer: 0,
+            address: 0,
+            value: 0,
+            hijacked: false,
+        }
+    }
+}
+
+fn documented_operation(instruction: u8) -> Option<Operation> {
+    let operation: Operation = match Instr::try_from(instruction) {
+        Ok(Instr::LDA_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::lda),
+        Ok(Instr::LDA_ZP) => Operation::Read(AddressingMode::ZeroPage, MOS6502::lda),
+        Ok(Instr::LDA_ZPX) => Operation::Read(AddressingMode::ZeroPageX, MOS6502::lda),
+        Ok(Instr::LDA_ABS) => Operation::Read(AddressingMode::Absolute, MOS6502::lda),
+        Ok(Instr::LDA_ABSX) => Operation::Read(AddressingMode::AbsoluteX, MOS6502::lda),
+        Ok(Instr::LDA_ABSY) => Operation::Read(AddressingMode::AbsoluteY, MOS6502::lda),
+        Ok(Instr::LDA_INDX) => Operation::Read(AddressingMode::IndirectX, MOS6502::lda),
+        Ok(Instr::LDA_INDY) => Operation::Read(AddressingMode::IndirectY, MOS6502::lda),
+        Ok(Instr::LDX_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::ldx),
+        Ok(Instr::LDX_ZP) => Operation::Read(AddressingMode::ZeroPage, MOS6502::ldx),
+        Ok(Instr::LDX_ZPY) => Operation::Read(AddressingMode::ZeroPageY, MOS6502::ldx),
+        Ok(Instr::LDX_ABS) => Operation::Read(AddressingMode::Absolute, MOS6502::ldx),
+        Ok(Instr::LDX_ABSY) => Operation::Read(AddressingMode::AbsoluteY, MOS6502::ldx),
+        Ok(Instr::LDY_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::ldy),
+        Ok(Instr::LDY_ZP) => Operation::Read(AddressingMode::ZeroPage, MOS6502::ldy),
+        Ok(Instr::LDY_ZPX) => Operation::Read(AddressingMode::ZeroPageX, MOS6502::ldy),
+        Ok(Instr::LDY_ABS) => Operation::Read(AddressingMode::Absolute, MOS6502::ldy),
+        Ok(Instr::LDY_ABSX) => Operation::Read(AddressingMode::AbsoluteX, MOS6502::ldy),
+        Ok(Instr::STA_ZP) => Operation::Write(AddressingMode::ZeroPage, |cpu| cpu.regA),
+        Ok(Instr::STA_ZPX) => Operation::Write(AddressingMode::ZeroPageX, |cpu| cpu.regA),
+        Ok(Instr::STA_ABS) => Operation::Write(AddressingMode::Absolute, |cpu| cpu.regA),
+        Ok(Instr::STA_ABSX) => Operation::Write(AddressingMode::AbsoluteX, |cpu| cpu.regA),
+        Ok(Instr::STA_ABSY) => Operation::Write(AddressingMode::AbsoluteY, |cpu| cpu.regA),
+        Ok(Instr::STA_INDX) => Operation::Write(AddressingMode::IndirectX, |cpu| cpu.regA),
+        Ok(Instr::STA_INDY) => Operation::Write(AddressingMode::IndirectY, |cpu| cpu.regA),
+        Ok(Instr::STX_ZP) => Operation::Write(AddressingMode::ZeroPage, |cpu| cpu.regX),
+        Ok(Instr::STX_ZPY) => Operation::Write(AddressingMode::ZeroPageY, |cpu| cpu.regX),
+        Ok(Instr::STX_ABS) => Operation::Write(AddressingMode::Absolute, |cpu| cpu.regX),
+        Ok(Instr::STY_ZP) => Operation::Write(AddressingMode::ZeroPage, |cpu| cpu.regY),
+        Ok(Instr::STY_ZPX) => Operation::Write(AddressingMode::ZeroPageX, |cpu| cpu.regY),
+        Ok(Instr::STY_ABS) => Operation::Write(AddressingMode::Absolute, |cpu| cpu.regY),
+        Ok(Instr::TAX) => Operation::Implied(MOS6502::tax),
+        Ok(Instr::TAY) => Operation::Implied(MOS6502::tay),
+        Ok(Instr::TSX) => Operation::Implied(MOS6502::tsx),
+        Ok(Instr::TXA) => Operation::Implied(MOS6502::txa),
+        Ok(Instr::TXS) => Operation::Implied(MOS6502::txs),
+        Ok(Instr::TYA) => Operation::Implied(MOS6502::tya),
+        Ok(Instr::PHA) => Operation::Push(|cpu| cpu.regA),
+        Ok(Instr::PHP) => Operation::Push(|cpu| cpu.proc_status.stack_value(true)),
+        Ok(Instr::PLA) => Operation::Pull(MOS6502::lda),
+        Ok(Instr::PLP) => Operation::Pull(|cpu, value| cpu.proc_status.set_from_stack(value)),
+        Ok(Instr::AND_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::and),
+        Ok(Instr::AND_ZP) => Operation::Read(AddressingMode::ZeroPage, MOS6502::and),
+        Ok(Instr::AND_ZPX) => Operation::Read(AddressingMode::ZeroPageX, MOS6502::and),
+        Ok(Instr::AND_ABS) => Operation::Read(AddressingMode::Absolute, MOS6502::and),
+        Ok(Instr::AND_ABSX) => Operation::Read(AddressingMode::AbsoluteX, MOS6502::and),
+        Ok(Instr::AND_ABSY) => Operation::Read(AddressingMode::AbsoluteY, MOS6502::and),
+        Ok(Instr::AND_INDX) => Operation::Read(AddressingMode::IndirectX, MOS6502::and),
+        Ok(Instr::AND_INDY) => Operation::Read(AddressingMode::IndirectY, MOS6502::and),
+        Ok(Instr::EOR_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::eor),
+        Ok(Instr::EOR_ZP) => Operation::Read(AddressingMode::ZeroPage, MOS6502::eor),
+        Ok(Instr::EOR_ZPX) => Operation::Read(AddressingMode::ZeroPageX, MOS6502::eor),
+        Ok(Instr::EOR_ABS) => Operation::Read(AddressingMode::Absolute, MOS6502::eor),
+        Ok(Instr::EOR_ABSX) => Operation::Read(AddressingMode::AbsoluteX, MOS6502::eor),
+        Ok(Instr::EOR_ABSY) => Operation::Read(AddressingMode::AbsoluteY, MOS6502::eor),
+        Ok(Instr::EOR_INDX) => Operation::Read(AddressingMode::IndirectX, MOS6502::eor),
+        Ok(Instr::EOR_INDY) => Operation::Read(AddressingMode::IndirectY, MOS6502::eor),
+        Ok(Instr::ORA_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::ora),
+        Ok(Instr::ORA_ZP) => Operation::Read(AddressingMode::ZeroPage, MOS6502::ora),
+        Ok(Instr::ORA_ZPX) => Operation::Read(AddressingMode::ZeroPageX, MOS6502::ora),
+        Ok(Instr::ORA_ABS) => Operation::Read(AddressingMode::Absolute, MOS6502::ora),
+        Ok(Instr::ORA_ABSX) => Operation::Read(AddressingMode::AbsoluteX, MOS6502::ora),
+        Ok(Instr::ORA_ABSY) => Operation::Read(AddressingMode::AbsoluteY, MOS6502::ora),
+        Ok(Instr::ORA_INDX) => Operation::Read(AddressingMode::IndirectX, MOS6502::ora),
+        Ok(Instr::ORA_INDY) => Operation::Read(AddressingMode::IndirectY, MOS6502::ora),
+        Ok(Instr::BIT_ZP) => Operation::Read(AddressingMode::ZeroPage, MOS6502::bit),
+        Ok(Instr::BIT_ABS) => Operation::Read(AddressingMode::Absolute, MOS6502::bit),
+        Ok(Instr::ADC_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::adc),
+        Ok(Instr::ADC_ZP) => Operation::Read(AddressingMode::ZeroPage, MOS6502::adc),
+        Ok(Instr::ADC_ZPX) => Operation::Read(AddressingMode::ZeroPageX, MOS6502::adc),
+        Ok(Instr::ADC_ABS) => Operation::Read(AddressingMode::Absolute, MOS6502::adc),
+        Ok(Instr::ADC_ABSX) => Operation::Read(AddressingMode::AbsoluteX, MOS6502::adc),
+        Ok(Instr::ADC_ABSY) => Operation::Read(AddressingMode::AbsoluteY, MOS6502::adc),
+        Ok(Instr::ADC_INDX) => Operation::Read(AddressingMode::IndirectX, MOS6502::adc),
+        Ok(Instr::ADC_INDY) => Operation::Read(AddressingMode::IndirectY, MOS6502::adc),
+        Ok(Instr::SBC_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::sbc),
+        Ok(Instr::SBC_ZP) => Operation::Read(AddressingMode::ZeroPage, MOS6502::sbc),
+        Ok(Instr::SBC_ZPX) => Operation::Read(AddressingMode::ZeroPageX, MOS6502::sbc),
+        Ok(Instr::SBC_ABS) => Operation::Read(AddressingMode::Absolute, MOS6502::sbc),
+        Ok(Instr::SBC_ABSX) => Operation::Read(AddressingMode::AbsoluteX, MOS6502::sbc),
+        Ok(Instr::SBC_ABSY) => Operation::Read(AddressingMode::AbsoluteY, MOS6502::sbc),
+        Ok(Instr::SBC_INDX) => Operation::Read(AddressingMode::IndirectX, MOS6502::sbc),
+        Ok(Instr::SBC_INDY) => Operation::Read(AddressingMode::IndirectY, MOS6502::sbc),
+        Ok(Instr::CMP_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::cmpa2cmp),
+        Ok(Instr::CMP_ZP) => Operation::Read(AddressingMode::ZeroPage, MOS6502::cmpa2cmp),
+        Ok(Instr::CMP_ZPX) => Operation::Read(AddressingMode::ZeroPageX, MOS6502::cmpa2cmp),
+        Ok(Instr::CMP_ABS) => Operation::Read(AddressingMode::Absolute, MOS6502::cmpa2cmp),
+        Ok(Instr::CMP_ABSX) => Operation::Read(AddressingMode::AbsoluteX, MOS6502::cmpa2cmp),
+        Ok(Instr::CMP_ABSY) => Operation::Read(AddressingMode::AbsoluteY, MOS6502::cmpa2cmp),
+        Ok(Instr::CMP_INDX) => Operation::Read(AddressingMode::IndirectX, MOS6502::cmpa2cmp),
+        Ok(Instr::CMP_INDY) => Operation::Read(AddressingMode::IndirectY, MOS6502::cmpa2cmp),
+        Ok(Instr::CPX_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::cmpx2cmp),
+        Ok(Instr::CPX_ZP) => Operation::Read(AddressingMode::ZeroPage, MOS6502::cmpx2cmp),
+        Ok(Instr::CPX_ABS) => Operation::Read(AddressingMode::Absolute, MOS6502::cmpx2cmp),
+        Ok(Instr::CPY_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::cmpy2cmp),
+        Ok(Instr::CPY_ZP) => Operation::Read(AddressingMode::ZeroPage, MOS6502::cmpy2cmp),
+        Ok(Instr::CPY_ABS) => Operation::Read(AddressingMode::Absolute, MOS6502::cmpy2cmp),
+        Ok(Instr::BCC) => Operation::Branch(|p| !p.carry()),
+        Ok(Instr::BCS) => Operation::Branch(|p| p.carry()),
+        Ok(Instr::BNE) => Operation::Branch(|p| !p.zero()),
+        Ok(Instr::BEQ) => Operation::Branch(|p| p.zero()),
+        Ok(Instr::BPL) => Operation::Branch(|p| !p.negative()),
+        Ok(Instr::BMI) => Operation::Branch(|p| p.negative()),
+        Ok(Instr::BVC) => Operation::Branch(|p| !p.overflow()),
+        Ok(Instr::BVS) => Operation::Branch(|p| p.overflow()),
+        Ok(Instr::JMP_ABS) => Operation::JmpAbsolute,
+        Ok(Instr::JMP_IND) => Operation::JmpIndirect,
+        Ok(Instr::JSR) => Operation::Jsr,
+        Ok(Instr::RTS) => Operation::Rts,
+        Ok(Instr::BRK) => Operation::Brk,
+        Ok(Instr::RTI) => Operation::Rti,
+        Ok(Instr::ASL_ACC) => Operation::Implied(MOS6502::asl_acc),
+        Ok(Instr::ASL_ZP) => Operation::Modify(AddressingMode::ZeroPage, MOS6502::asl),
+        Ok(Instr::ASL_ZPX) => Operation::Modify(AddressingMode::ZeroPageX, MOS6502::asl),
+        Ok(Instr::ASL_ABS) => Operation::Modify(AddressingMode::Absolute, MOS6502::asl),
+        Ok(Instr::ASL_ABSX) => Operation::Modify(AddressingMode::AbsoluteX, MOS6502::asl),
+        Ok(Instr::LSR_ACC) => Operation::Implied(MOS6502::lsr_acc),
+        Ok(Instr::LSR_ZP) => Operation::Modify(AddressingMode::ZeroPage, MOS6502::lsr),
+        Ok(Instr::LSR_ZPX) => Operation::Modify(AddressingMode::ZeroPageX, MOS6502::lsr),
+        Ok(Instr::LSR_ABS) => Operation::Modify(AddressingMode::Absolute, MOS6502::lsr),
+        Ok(Instr::LSR_ABSX) => Operation::Modify(AddressingMode::AbsoluteX, MOS6502::lsr),
+        Ok(Instr::ROL_ACC) => Operation::Implied(MOS6502::rol_acc),
+        Ok(Instr::ROL_ZP) => Operation::Modify(AddressingMode::ZeroPage, MOS6502::rol),
+        Ok(Instr::ROL_ZPX) => Operation::Modify(AddressingMode::ZeroPageX, MOS6502::rol),
+        Ok(Instr::ROL_ABS) => Operation::Modify(AddressingMode::Absolute, MOS6502::rol),
+        Ok(Instr::ROL_ABSX) => Operation::Modify(AddressingMode::AbsoluteX, MOS6502::rol),
+        Ok(Instr::ROR_ACC) => Operation::Implied(MOS6502::ror_acc),
+        Ok(Instr::ROR_ZP) => Operation::Modify(AddressingMode::ZeroPage, MOS6502::ror),
+        Ok(Instr::ROR_ZPX) => Operation::Modify(AddressingMode::ZeroPageX, MOS6502::ror),
+        Ok(Instr::ROR_ABS) => Operation::Modify(AddressingMode::Absolute, MOS6502::ror),
+        Ok(Instr::ROR_ABSX) => Operation::Modify(AddressingMode::AbsoluteX, MOS6502::ror),
+        Ok(Instr::INC_ZP) => Operation::Modify(AddressingMode::ZeroPage, MOS6502::inc),
+        Ok(Instr::INC_ZPX) => Operation::Modify(AddressingMode::ZeroPageX, MOS6502::inc),
+        Ok(Instr::INC_ABS) => Operation::Modify(AddressingMode::Absolute, MOS6502::inc),
+        Ok(Instr::INC_ABSX) => Operation::Modify(AddressingMode::AbsoluteX, MOS6502::inc),
+        Ok(Instr::INX) => Operation::Implied(MOS6502::inx),
+        Ok(Instr::INY) => Operation::Implied(MOS6502::iny),
+        Ok(Instr::DEC_ZP) => Operation::Modify(AddressingMode::ZeroPage, MOS6502::dec),
+        Ok(Instr::DEC_ZPX) => Operation::Modify(AddressingMode::ZeroPageX, MOS6502::dec),
+        Ok(Instr::DEC_ABS) => Operation::Modify(AddressingMode::Absolute, MOS6502::dec),
+        Ok(Instr::DEC_ABSX) => Operation::Modify(AddressingMode::AbsoluteX, MOS6502::dec),
+        Ok(Instr::DEX) => Operation::Implied(MOS6502::dex),
+        Ok(Instr::DEY) => Operation::Implied(MOS6502::dey),
+        Ok(Instr::CLC) => Operation::Implied(MOS6502::clc),
+        Ok(Instr::SEC) => Operation::Implied(MOS6502::sec),
+        Ok(Instr::CLI) => Operation::Implied(MOS6502::cli),
+        Ok(Instr::SEI) => Operation::Implied(MOS6502::sei),
+        Ok(Instr::CLD) => Operation::Implied(MOS6502::cld),
+        Ok(Instr::SED) => Operation::Implied(MOS6502::sed),
+        Ok(Instr::CLV) => Operation::Implied(MOS6502::clv),
+        Ok(Instr::NOP) => Operation::Implied(MOS6502::nop),
+        Err(_) => return None,
+    };
+    Some(operation)
+}
+
+fn undocumented_operation(instruction: u8) -> Option<Operation> {
+    let operation: Operation = match UndocumentedInstr::try_from(instruction) {
+        Ok(UndocumentedInstr::SLO_ZP) => Operation::Modify(AddressingMode::ZeroPage, MOS6502::slo),
+        Ok(UndocumentedInstr::SLO_ZPX) => Operation::Modify(AddressingMode::ZeroPageX, MOS6502::slo),
+        Ok(UndocumentedInstr::SLO_ABS) => Operation::Modify(AddressingMode::Absolute, MOS6502::slo),
+        Ok(UndocumentedInstr::SLO_ABSX) => Operation::Modify(AddressingMode::AbsoluteX, MOS6502::slo),
+        Ok(UndocumentedInstr::SLO_ABSY) => Operation::Modify(AddressingMode::AbsoluteY, MOS6502::slo),
+        Ok(UndocumentedInstr::SLO_INDX) => Operation::Modify(AddressingMode::IndirectX, MOS6502::slo),
+        Ok(UndocumentedInstr::SLO_INDY) => Operation::Modify(AddressingMode::IndirectY, MOS6502::slo),
+        Ok(UndocumentedInstr::RLA_ZP) => Operation::Modify(AddressingMode::ZeroPage, MOS6502::rla),
+        Ok(UndocumentedInstr::RLA_ZPX) => Operation::Modify(AddressingMode::ZeroPageX, MOS6502::rla),
+        Ok(UndocumentedInstr::RLA_ABS) => Operation::Modify(AddressingMode::Absolute, MOS6502::rla),
+        Ok(UndocumentedInstr::RLA_ABSX) => Operation::Modify(AddressingMode::AbsoluteX, MOS6502::rla),
+        Ok(UndocumentedInstr::RLA_ABSY) => Operation::Modify(AddressingMode::AbsoluteY, MOS6502::rla),
+        Ok(UndocumentedInstr::RLA_INDX) => Operation::Modify(AddressingMode::IndirectX, MOS6502::rla),
+        Ok(UndocumentedInstr::RLA_INDY) => Operation::Modify(AddressingMode::IndirectY, MOS6502::rla),
+        Ok(UndocumentedInstr::SRE_ZP) => Operation::Modify(AddressingMode::ZeroPage, MOS6502::sre),
+        Ok(UndocumentedInstr::SRE_ZPX) => Operation::Modify(AddressingMode::ZeroPageX, MOS6502::sre),
+        Ok(UndocumentedInstr::SRE_ABS) => Operation::Modify(AddressingMode::Absolute, MOS6502::sre),
+        Ok(UndocumentedInstr::SRE_ABSX) => Operation::Modify(AddressingMode::AbsoluteX, MOS6502::sre),
+        Ok(UndocumentedInstr::SRE_ABSY) => Operation::Modify(AddressingMode::AbsoluteY, MOS6502::sre),
+        Ok(UndocumentedInstr::SRE_INDX) => Operation::Modify(AddressingMode::IndirectX, MOS6502::sre),
+        Ok(UndocumentedInstr::SRE_INDY) => Operation::Modify(AddressingMode::IndirectY, MOS6502::sre),
+        Ok(UndocumentedInstr::RRA_ZP) => Operation::Modify(AddressingMode::ZeroPage, MOS6502::rra),
+        Ok(UndocumentedInstr::RRA_ZPX) => Operation::Modify(AddressingMode::ZeroPageX, MOS6502::rra),
+        Ok(UndocumentedInstr::RRA_ABS) => Operation::Modify(AddressingMode::Absolute, MOS6502::rra),
+        Ok(UndocumentedInstr::RRA_ABSX) => Operation::Modify(AddressingMode::AbsoluteX, MOS6502::rra),
+        Ok(UndocumentedInstr::RRA_ABSY) => Operation::Modify(AddressingMode::AbsoluteY, MOS6502::rra),
+        Ok(UndocumentedInstr::RRA_INDX) => Operation::Modify(AddressingMode::IndirectX, MOS6502::rra),
+        Ok(UndocumentedInstr::RRA_INDY) => Operation::Modify(AddressingMode::IndirectY, MOS6502::rra),
+        Ok(UndocumentedInstr::DCP_ZP) => Operation::Modify(AddressingMode::ZeroPage, MOS6502::dcp),
+        Ok(UndocumentedInstr::DCP_ZPX) => Operation::Modify(AddressingMode::ZeroPageX, MOS6502::dcp),
+        Ok(UndocumentedInstr::DCP_ABS) => Operation::Modify(AddressingMode::Absolute, MOS6502::dcp),
+        Ok(UndocumentedInstr::DCP_ABSX) => Operation::Modify(AddressingMode::AbsoluteX, MOS6502::dcp),
+        Ok(UndocumentedInstr::DCP_ABSY) => Operation::Modify(AddressingMode::AbsoluteY, MOS6502::dcp),
+        Ok(UndocumentedInstr::DCP_INDX) => Operation::Modify(AddressingMode::IndirectX, MOS6502::dcp),
+        Ok(UndocumentedInstr::DCP_INDY) => Operation::Modify(AddressingMode::IndirectY, MOS6502::dcp),
+        Ok(UndocumentedInstr::ISC_ZP) => Operation::Modify(AddressingMode::ZeroPage, MOS6502::isc),
+        Ok(UndocumentedInstr::ISC_ZPX) => Operation::Modify(AddressingMode::ZeroPageX, MOS6502::isc),
+        Ok(UndocumentedInstr::ISC_ABS) => Operation::Modify(AddressingMode::Absolute, MOS6502::isc),
+        Ok(UndocumentedInstr::ISC_ABSX) => Operation::Modify(AddressingMode::AbsoluteX, MOS6502::isc),
+        Ok(UndocumentedInstr::ISC_ABSY) => Operation::Modify(AddressingMode::AbsoluteY, MOS6502::isc),
+        Ok(UndocumentedInstr::ISC_INDX) => Operation::Modify(AddressingMode::IndirectX, MOS6502::isc),
+        Ok(UndocumentedInstr::ISC_INDY) => Operation::Modify(AddressingMode::IndirectY, MOS6502::isc),
+        Ok(UndocumentedInstr::LAX_ZP) => Operation::Read(AddressingMode::ZeroPage, MOS6502::lax),
+        Ok(UndocumentedInstr::LAX_ZPY) => Operation::Read(AddressingMode::ZeroPageY, MOS6502::lax),
+        Ok(UndocumentedInstr::LAX_ABS) => Operation::Read(AddressingMode::Absolute, MOS6502::lax),
+        Ok(UndocumentedInstr::LAX_ABSY) => Operation::Read(AddressingMode::AbsoluteY, MOS6502::lax),
+        Ok(UndocumentedInstr::LAX_INDX) => Operation::Read(AddressingMode::IndirectX, MOS6502::lax),
+        Ok(UndocumentedInstr::LAX_INDY) => Operation::Read(AddressingMode::IndirectY, MOS6502::lax),
+        Ok(UndocumentedInstr::SAX_ZP) => Operation::Write(AddressingMode::ZeroPage, |cpu| cpu.regA & cpu.regX),
+        Ok(UndocumentedInstr::SAX_ZPY) => Operation::Write(AddressingMode::ZeroPageY, |cpu| cpu.regA & cpu.regX),
+        Ok(UndocumentedInstr::SAX_ABS) => Operation::Write(AddressingMode::Absolute, |cpu| cpu.regA & cpu.regX),
+        Ok(UndocumentedInstr::SAX_INDX) => Operation::Write(AddressingMode::IndirectX, |cpu| cpu.regA & cpu.regX),
+        Ok(UndocumentedInstr::ANC_IM
+           | UndocumentedInstr::ANC_IM_ALT) => Operation::Read(AddressingMode::Immediate, MOS6502::anc),
+        Ok(UndocumentedInstr::ALR_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::alr),
+        Ok(UndocumentedInstr::ARR_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::arr),
+        Ok(UndocumentedInstr::SBX_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::sbx),
+        Ok(UndocumentedInstr::USBC_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::sbc),
+        Ok(UndocumentedInstr::NOP_IMP_1A
+           | UndocumentedInstr::NOP_IMP_3A
+           | UndocumentedInstr::NOP_IMP_5A
+           | UndocumentedInstr::NOP_IMP_7A
+           | UndocumentedInstr::NOP_IMP_DA
+           | UndocumentedInstr::NOP_IMP_FA) => Operation::Implied(MOS6502::nop),
+        Ok(UndocumentedInstr::NOP_IM_80
+           | UndocumentedInstr::NOP_IM_82
+           | UndocumentedInstr::NOP_IM_89
+           | UndocumentedInstr::NOP_IM_C2
+           | UndocumentedInstr::NOP_IM_E2) => Operation::Read(AddressingMode::Immediate, MOS6502::ignore),
+        Ok(UndocumentedInstr::NOP_ZP_04
+           | UndocumentedInstr::NOP_ZP_44
+           | UndocumentedInstr::NOP_ZP_64) => Operation::Read(AddressingMode::ZeroPage, MOS6502::ignore),
+        Ok(UndocumentedInstr::NOP_ZPX_14
+           | UndocumentedInstr::NOP_ZPX_34
+           | UndocumentedInstr::NOP_ZPX_54
+           | UndocumentedInstr::NOP_ZPX_74
+           | UndocumentedInstr::NOP_ZPX_D4
+           | UndocumentedInstr::NOP_ZPX_F4) => Operation::Read(AddressingMode::ZeroPageX, MOS6502::ignore),
+        Ok(UndocumentedInstr::NOP_ABS) => Operation::Read(AddressingMode::Absolute, MOS6502::ignore),
+        Ok(UndocumentedInstr::NOP_ABSX_1C
+           | UndocumentedInstr::NOP_ABSX_3C
+           | UndocumentedInstr::NOP_ABSX_5C
+           | UndocumentedInstr::NOP_ABSX_7C
+           | UndocumentedInstr::NOP_ABSX_DC
+           | UndocumentedInstr::NOP_ABSX_FC) => Operation::Read(AddressingMode::AbsoluteX, MOS6502::ignore),
+        Ok(UndocumentedInstr::XAA_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::xaa),
+        Ok(UndocumentedInstr::LAX_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::lxa),
+        Ok(UndocumentedInstr::LAS_ABSY) => Operation::Read(AddressingMode::AbsoluteY, MOS6502::las),
+        Ok(UndocumentedInstr::AHX_ABSY) => Operation::WriteHigh(AddressingMode::AbsoluteY, |cpu| cpu.regA & cpu.regX),
+        Ok(UndocumentedInstr::AHX_INDY) => Operation::WriteHigh(AddressingMode::IndirectY, |cpu| cpu.regA & cpu.regX),
+        Ok(UndocumentedInstr::TAS_ABSY) => Operation::WriteHigh(AddressingMode::AbsoluteY, |cpu| {
+            cpu.regSP = cpu.regA & cpu.regX;
+            cpu.regSP
+        }),
+        Ok(UndocumentedInstr::SHX_ABSY) => Operation::WriteHigh(AddressingMode::AbsoluteY, |cpu| cpu.regX),
+        Ok(UndocumentedInstr::SHY_ABSX) => Operation::WriteHigh(AddressingMode::AbsoluteX, |cpu| cpu.regY),
+        Ok(UndocumentedInstr::JAM_02
+           | UndocumentedInstr::JAM_12
+           | UndocumentedInstr::JAM_22
+           | UndocumentedInstr::JAM_32
+           | UndocumentedInstr::JAM_42
+           | UndocumentedInstr::JAM_52
+           | UndocumentedInstr::JAM_62
+           | UndocumentedInstr::JAM_72
+           | UndocumentedInstr::JAM_92
+           | UndocumentedInstr::JAM_B2
+           | UndocumentedInstr::JAM_D2
+           | UndocumentedInstr::JAM_F2) => Operation::Jam,
+        Err(_) => return None,
+    };
+    Some(operation)
+}
+
+// Every opcode is defined on the 65C02, the ones it shares with the NMOS set decode the same
+fn cmos_operation(instruction: u8) -> Option<Operation> {
+    let opcode: &'static Opcode = &OPCODES_65C02[instruction as usize];
+    if !opcode.documented {
+        return Some(Operation::Skip(opcode));
+    }
+
+    match instruction & 0x0F {
+        0x07 => return Some(Operation::Modify(AddressingMode::ZeroPage, bit_operation(instruction))),
+        0x0F => return Some(Operation::BranchOnBit(instruction)),
+        _ => {}
+    }
+
+    match opcode.mnemonic {
+        "ADC" => return Some(Operation::Arithmetic(opcode.mode, MOS6502::adc)),
+        "SBC" => return Some(Operation::Arithmetic(opcode.mode, MOS6502::sbc)),
+        _ => {}
+    }
+
+    let operation: Operation = match CmosInstr::try_from(instruction) {
+        Ok(CmosInstr::BRA) => Operation::Branch(|_| true),
+        Ok(CmosInstr::STZ_ZP) => Operation::Write(AddressingMode::ZeroPage, |_| 0),
+        Ok(CmosInstr::STZ_ZPX) => Operation::Write(AddressingMode::ZeroPageX, |_| 0),
+        Ok(CmosInstr::STZ_ABS) => Operation::Write(AddressingMode::Absolute, |_| 0),
+        Ok(CmosInstr::STZ_ABSX) => Operation::Write(AddressingMode::AbsoluteX, |_| 0),
+        Ok(CmosInstr::PHX) => Operation::Push(|cpu| cpu.regX),
+        Ok(CmosInstr::PHY) => Operation::Push(|cpu| cpu.regY),
+        Ok(CmosInstr::PLX) => Operation::Pull(MOS6502::ldx),
+        Ok(CmosInstr::PLY) => Operation::Pull(MOS6502::ldy),
+        Ok(CmosInstr::TSB_ZP) => Operation::Modify(AddressingMode::ZeroPage, MOS6502::tsb),
+        Ok(CmosInstr::TSB_ABS) => Operation::Modify(AddressingMode::Absolute, MOS6502::tsb),
+        Ok(CmosInstr::TRB_ZP) => Operation::Modify(AddressingMode::ZeroPage, MOS6502::trb),
+        Ok(CmosInstr::TRB_ABS) => Operation::Modify(AddressingMode::Absolute, MOS6502::trb),
+        Ok(CmosInstr::INC_ACC) => Operation::Implied(MOS6502::inc_acc),
+        Ok(CmosInstr::DEC_ACC) => Operation::Implied(MOS6502::dec_acc),
+        Ok(CmosInstr::ORA_ZPI) => Operation::Read(AddressingMode::ZeroPageIndirect, MOS6502::ora),
+        Ok(CmosInstr::AND_ZPI) => Operation::Read(AddressingMode::ZeroPageIndirect, MOS6502::and),
+        Ok(CmosInstr::EOR_ZPI) => Operation::Read(AddressingMode::ZeroPageIndirect, MOS6502::eor),
+        Ok(CmosInstr::STA_ZPI) => Operation::Write(AddressingMode::ZeroPageIndirect, |cpu| cpu.regA),
+        Ok(CmosInstr::LDA_ZPI) => Operation::Read(AddressingMode::ZeroPageIndirect, MOS6502::lda),
+        Ok(CmosInstr::CMP_ZPI) => Operation::Read(AddressingMode::ZeroPageIndirect, MOS6502::cmpa2cmp),
+        Ok(CmosInstr::BIT_IM) => Operation::Read(AddressingMode::Immediate, MOS6502::test_bits),
+        Ok(CmosInstr::BIT_ZPX) => Operation::Read(AddressingMode::ZeroPageX, MOS6502::bit),
+        Ok(CmosInstr::BIT_ABSX) => Operation::Read(AddressingMode::AbsoluteX, MOS6502::bit),
+        Ok(CmosInstr::JMP_IND) => Operation::JmpIndirect,
+        Ok(CmosInstr::JMP_ABSX_IND) => Operation::JmpIndexedIndirect,
+        Ok(CmosInstr::ASL_ABSX) => Operation::Shift(AddressingMode::AbsoluteX, MOS6502::asl),
+        Ok(CmosInstr::LSR_ABSX) => Operation::Shift(AddressingMode::AbsoluteX, MOS6502::lsr),
+        Ok(CmosInstr::ROL_ABSX) => Operation::Shift(AddressingMode::AbsoluteX, MOS6502::rol),
+        Ok(CmosInstr::ROR_ABSX) => Operation::Shift(AddressingMode::AbsoluteX, MOS6502::ror),
+        Ok(CmosInstr::WAI) => Operation::Wait,
+        Ok(CmosInstr::STP) => Operation::Stop,
+        Err(_) => return documented_operation(instruction),
+    };
+    Some(operation)
+}
+
+impl MOS6502 {
+    // Advances the processor by one clock cycle, which is exactly one read or write on the bus
+    pub fn tick<B: Bus>(&mut self, memory: &mut B) -> Result<(), ExecutionError> {
+        if let Some(error) = self.halt_error() {
+            return Err(error);
+        }
+        if self.waiting && !self.wake_up() {
+            self.step_pc = self.regPC;
+            self.step_opcode = None;
+            self.total_cycles += 1;
+            return Ok(());
+        }
+
+        self.step_accesses = 0;
+        match self.cycle_state.take() {
+            None => {
+                // The opcode fetch is never the last cycle
+                self.begin_instruction(memory)?;
+                self.poll_interrupt_lines();
+            }
+            Some(mut state) => {
+                if !self.run_cycle(&mut state, memory) {
+                    self.cycle_state = Some(state);
+                    self.poll_interrupt_lines();
+                }
+            }
+        }
+        self.total_cycles += 1;
+        if let Some(address) = memory.fault() {
+            return Err(ExecutionError::BusFault { address });
+        }
+
+        match self.halt_error() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    // Ticks until the instruction in flight, or the next one, completes. Returns the cycles spent
+    pub(super) fn tick_instruction<B: Bus>(&mut self, memory: &mut B) -> Result<u32, ExecutionError> {
+        let mut cycles: u32 = 0;
+        loop {
+            self.tick(memory)?;
+            cycles += 1;
+            if self.cycle_state.is_none() {
+                return Ok(cycles);
+            }
+        }
+    }
+
+    fn decode(&self, instruction: u8) -> Option<Operation> {
+        if self.quirks.cmos_instructions {
+            return cmos_operation(instruction);
+        }
+        if let Some(operation) = documented_operation(instruction) {
+            return Some(operation);
+        }
+        match self.quirks.illegal_opcodes {
+            UnknownOpcodePolicy::Undocumented => undocumented_operation(instruction),
+            UnknownOpcodePolicy::Nop => Some(Operation::Skip(opcode(instruction))),
+            UnknownOpcodePolicy::Error => None,
+        }
+    }
+
+    // An instruction that changes I, like CLI or SEI, does it on its last cycle,
+    // so the next instruction still sees the old value
+    fn poll_interrupt_lines(&mut self) {
+        self.interrupt_poll = self.nmi_pending || (self.irq_line && !self.proc_status.interrupt_disable());
+    }
+
+    // First cycle: opcode fetch, or a dummy fetch when the previous instruction
+    // polled an interrupt
+    fn begin_instruction<B: Bus>(&mut self, memory: &mut B) -> Result<(), ExecutionError> {
+        self.step_pc = self.regPC;
+        self.step_opcode = None;
+        let operation: Operation = if self.interrupt_poll {
+            self.interrupt_poll = false;
+            self.read(self.regPC, memory);
+            if self.nmi_pending {
+                self.nmi_pending = false;
+                Operation::Interrupt(NMI_VECTOR)
+            } else {
+                Operation::Interrupt(IRQ_VECTOR)
+            }
+        } else {
+            let instruction: u8 = self.fetch(memory);
+            self.step_opcode = Some(instruction);
+            match self.decode(instruction) {
+                Some(operation) => operation,
+                None => {
+                    let pc: u16 = self.regPC.wrapping_sub(1);
+                    self.regPC = pc;
+                    return Err(ExecutionError::UnknownOpcode { pc: pc as u32, opcode: instruction });
+                }
+            }
+        };
+        // 65C02 single-cycle NOPs are done with the fetch
+        if let Operation::Skip(opcode) = operation
+            && opcode.cycles == 1
+        {
+            return Ok(());
+        }
+        self.cycle_state = Some(CycleState::new(operation));
+        Ok(())
+    }
+
+    // Runs one cycle of the instruction in flight, returns true on its last cycle
+    fn run_cycle<B: Bus>(&mut self, state: &mut CycleState, memory: &mut B) -> bool {
+        let cycle: u8 = state.cycle;
+        state.cycle += 1;
+
+        match state.operation {
+            Operation::Implied(operation) => {
+                self.read(self.regPC, memory);
+                operation(self);
+                true
+            }
+            Operation::Read(mode, _)
+            | Operation::Write(mode, _)
+            | Operation::WriteHigh(mode, _)
+            | Operation::Modify(mode, _)
+            | Operation::Arithmetic(mode, _)
+            | Operation::Shift(mode, _) => {
+                if state.address_ready {
+                    self.data_cycle(state, memory)
+                } else {
+                    self.address_cycle(state, mode, cycle, memory)
+                }
+            }
+            Operation::Branch(condition) => match cycle {
+                0 => {
+                    state.value = self.fetch(memory);
+                    !condition(&self.proc_status)
+                }
+                _ => self.branch_cycle(state, cycle, memory),
+            },
+            Operation::BranchOnBit(instruction) => match cycle {
+                0 => {
+                    state.address = self.fetch(memory) as u16;
+                    false
+                }
+                1 => {
+                    state.value = self.read(state.address, memory);
+                    false
+                }
+                2 => {
+                    self.read(state.address, memory);
+                    false
+                }
+                3 => {
+                    let condition: bool = bit_condition(instruction, state.value);
+                    state.value = self.fetch(memory);
+                    !condition
+                }
+                _ => self.branch_cycle(state, cycle - 3, memory),
+            },
+            Operation::Push(value) => match cycle {
+                0 => {
+                    self.read(self.regPC, memory);
+                    false
+                }
+                _ => {
+                    let value: u8 = value(self);
+                    self.push(value, memory);
+                    true
+                }
+            },
+            Operation::Pull(operation) => match cycle {
+                0 => {
+                    self.read(self.regPC, memory);
+                    false
+                }
+                1 => {
+                    self.read(STACK_PAGE | self.regSP as u16, memory);
+                    false
+                }
+                _ => {
+                    let value: u8 = self.pull(memory);
+                    operation(self, value);
+                    true
+                }
+            },
+            Operation::JmpAbsolute => match cycle {
+                0 => {
+                    state.address = self.fetch(memory) as u16;
+                    false
+                }
+                _ => {
+                    let high_byte: u8 = self.fetch(memory);
+                    self.regPC = ((high_byte as u16) << 8) | state.address;
+                    true
+                }
+            },
+            Operation::JmpIndirect | Operation::JmpIndexedIndirect => {
+                // Without the NMOS bug one more cycle is spent to carry into the pointer high byte.
+                // JMP (abs,X) only exists on the 65C02, which never has the bug
+                let cmos: bool = !self.quirks.jmp_indirect_bug || matches!(state.operation, Operation::JmpIndexedIndirect);
+                match cycle {
+                    0 => {
+                        state.address = self.fetch(memory) as u16;
+                        false
+                    }
+                    1 => {
+                        let high_byte: u8 = self.fetch(memory);
+                        state.address |= (high_byte as u16) << 8;
+                        false
+                    }
+                    2 if cmos => {
+                        self.read(self.regPC.wrapping_sub(1), memory);
+                        if let Operation::JmpIndexedIndirect = state.operation {
+                            state.address = state.address.wrapping_add(self.regX as u16);
+                        }
+                        false
+                    }
+                    _ if cycle == 2 + cmos as u8 => {
+                        state.value = self.read(state.address, memory);
+                        false
+                    }
+                    _ => {
+                        // Same NMOS page wrap as the instruction-stepped JMP ($xxFF)
+                        let pointer: u16 = if cmos {
+                            state.address.wrapping_add(1)
+                        } else {
+                            (state.address & 0xFF00) | (state.address.wrapping_add(1) & 0x00FF)
+                        };
+                        let high_byte: u8 = self.read(pointer, memory);
+                        self.regPC = ((high_byte as u16) << 8) | (state.value as u16);
+                        true
+                    }
+                }
+            }
+            Operation::Jsr => match cycle {
+                0 => {
+                    state.address = self.fetch(memory) as u16;
+                    false
+                }
+                1 => {
+                    self.read(STACK_PAGE | self.regSP as u16, memory);
+                    false
+                }
+                2 => {
+                    self.push((self.regPC >> 8) as u8, memory);
+                    false
+                }
+                3 => {
+                    self.push(self.regPC as u8, memory);
+                    false
+                }
+                _ => {
+                    let high_byte: u8 = self.read(self.regPC, memory);
+                    self.regPC = ((high_byte as u16) << 8) | state.address;
+                    true
+                }
+            },
+            Operation::Rts => match cycle {
+                0 => {
+                    self.read(self.regPC, memory);
+                    false
+                }
+                1 => {
+                    self.read(STACK_PAGE | self.regSP as u16, memory);
+                    false
+                }
+                2 => {
+                    state.address = self.pull(memory) as u16;
+                    false
+                }
+                3 => {
+                    let high_byte: u8 = self.pull(memory);
+                    self.regPC = ((high_byte as u16) << 8) | state.address;
+                    false
+                }
+                _ => {
+                    self.read(self.regPC, memory);
+                    self.regPC = self.regPC.wrapping_add(1);
+                    true
+                }
+            },
+            Operation::Rti => match cycle {
+                0 => {
+                    self.read(self.regPC, memory);
+                    false
+                }
+                1 => {
+                    self.read(STACK_PAGE | self.regSP as u16, memory);
+                    false
+                }
+                2 => {
+                    let status: u8 = self.pull(memory);
+                    self.proc_status.set_from_stack(status);
+                    false
+                }
+                3 => {
+                    state.address = self.pull(memory) as u16;
+                    false
+                }
+                _ => {
+                    let high_byte: u8 = self.pull(memory);
+                    self.regPC = ((high_byte as u16) << 8) | state.address;
+                    true
+                }
+            },
+            Operation::Brk => match cycle {
+                0 => {
+                    // Padding byte, skipped by the return address
+                    self.fetch(memory);
+                    false
+                }
+                _ => self.interrupt_cycle(state, cycle - 1, IRQ_VECTOR, true, memory),
+            },
+            Operation::Interrupt(vector) => match cycle {
+                0 => {
+                    self.read(self.regPC, memory);
+                    false
+                }
+                _ => self.interrupt_cycle(state, cycle - 1, vector, false, memory),
+            },
+            Operation::Skip(opcode) => {
+                if cycle + 1 < opcode.bytes {
+                    self.fetch(memory);
+                } else {
+                    self.read(self.regPC, memory);
+                }
+                cycle + 2 >= opcode.cycles
+            }
+            Operation::Wait | Operation::Stop => {
+                self.read(self.regPC, memory);
+                if cycle == 0 {
+                    return false;
+                }
+                if let Operation::Wait = state.operation {
+                    self.waiting = true;
+                } else {
+                    self.stopped = true;
+                }
+                true
+            }
+            Operation::Jam => {
+                self.regPC = self.regPC.wrapping_sub(1);
+                self.jammed = true;
+                true
+            }
+        }
+    }
+
+    // Taken branch: PCL is fixed up first, crossing a page costs another cycle for PCH
+    fn branch_cycle<B: Bus>(&mut self, state: &mut CycleState, stage: u8, memory: &mut B) -> bool {
+        self.read(self.regPC, memory);
+        if stage > 1 {
+            self.regPC = state.address;
+            return true;
+        }
+        let target: u16 = self.regPC.wrapping_add(state.value as i8 as u16);
+        if (target & 0xFF00) == (self.regPC & 0xFF00) {
+            self.regPC = target;
+            return true;
+        }
+        self.regPC = (self.regPC & 0xFF00) | (target & 0x00FF);
+        state.address = target;
+        false
+    }
+
+    // Pushes PC and status, then loads PC from the vector. On NMOS an NMI that
+    // is pending by the vector fetch replaces the IRQ vector, B is still pushed for BRK
+    fn interrupt_cycle<B: Bus>(&mut self, state: &mut CycleState, stage: u8, vector: u16, break_command: bool, memory: &mut B) -> bool {
+        if stage == 3 && vector == IRQ_VECTOR && self.nmi_pending && self.quirks.interrupt_hijacking {
+            self.nmi_pending = false;
+            state.hijacked = true;
+        }
+        let vector: u16 = if state.hijacked { NMI_VECTOR } else { vector };
+        match stage {
+            0 => {
+                self.push((self.regPC >> 8) as u8, memory);
+                false
+            }
+            1 => {
+                self.push(self.regPC as u8, memory);
+                false
+            }
+            2 => {
+                self.push(self.proc_status.stack_value(break_command), memory);
+                self.proc_status.set_interrupt_disable();
+                if self.quirks.clear_decimal_on_interrupt {
+                    self.proc_status.clear_decimal_mode();
+                }
+                false
+            }
+            3 => {
+                state.address = self.read(vector, memory) as u16;
+                false
+            }
+            _ => {
+                let high_byte: u8 = self.read(vector.wrapping_add(1), memory);
+                self.regPC = ((high_byte as u16) << 8) | state.address;
+                true
+            }
+        }
+    }
+
+    // One cycle of effective address calculation
+    fn address_cycle<B: Bus>(&mut self, state: &mut CycleState, mode: AddressingMode, cycle: u8, memory: &mut B) -> bool {
+        match (mode, cycle) {
+            (AddressingMode::Immediate, _) => {
+                // The operand fetch is the data access itself
+                state.address = self.regPC;
+                self.regPC = self.regPC.wrapping_add(1);
+                state.address_ready = true;
+                return self.data_cycle(state, memory);
+            }
+            (AddressingMode::ZeroPage, _) => {
+                state.address = self.fetch(memory) as u16;
+                state.address_ready = true;
+            }
+            (AddressingMode::ZeroPageX
+            | AddressingMode::ZeroPageY
+            | AddressingMode::IndirectX
+            | AddressingMode::IndirectY
+            | AddressingMode::ZeroPageIndirect, 0) => {
+                state.pointer = self.fetch(memory);
+            }
+            (AddressingMode::ZeroPageX, _) => {
+                self.read(state.pointer as u16, memory);
+                state.address = state.pointer.wrapping_add(self.regX) as u16;
+                state.address_ready = true;
+            }
+            (AddressingMode::ZeroPageY, _) => {
+                self.read(state.pointer as u16, memory);
+                state.address = state.pointer.wrapping_add(self.regY) as u16;
+                state.address_ready = true;
+            }
+            (AddressingMode::Absolute | AddressingMode::AbsoluteX | AddressingMode::AbsoluteY, 0) => {
+                state.address = self.fetch(memory) as u16;
+            }
+            (AddressingMode::Absolute, _) => {
+                let high_byte: u8 = self.fetch(memory);
+                state.address |= (high_byte as u16) << 8;
+                state.address_ready = true;
+            }
+            (AddressingMode::AbsoluteX, _) => {
+                let high_byte: u8 = self.fetch(memory);
+                self.index_address(state, high_byte, self.regX);
+            }
+            (AddressingMode::AbsoluteY, _) => {
+                let high_byte: u8 = self.fetch(memory);
+                self.index_address(state, high_byte, self.regY);
+            }
+            (AddressingMode::IndirectX, 1) => {
+                self.read(state.pointer as u16, memory);
+                state.pointer = state.pointer.wrapping_add(self.regX);
+            }
+            (AddressingMode::IndirectX, 2) => {
+                state.address = self.read(state.pointer as u16, memory) as u16;
+            }
+            (AddressingMode::IndirectX, _) => {
+                let high_byte: u8 = self.read(state.pointer.wrapping_add(1) as u16, memory);
+                state.address |= (high_byte as u16) << 8;
+                state.address_ready = true;
+            }
+            (AddressingMode::ZeroPageIndirect, 1) => {
+                state.address = self.read(state.pointer as u16, memory) as u16;
+            }
+            (AddressingMode::ZeroPageIndirect, _) => {
+                let high_byte: u8 = self.read(state.pointer.wrapping_add(1) as u16, memory);
+                state.address |= (high_byte as u16) << 8;
+                state.address_ready = true;
+            }
+            (AddressingMode::IndirectY, 1) => {
+                state.address = self.read(state.pointer as u16, memory) as u16;
+            }
+            (AddressingMode::IndirectY, _) => {
+                let high_byte: u8 = self.read(state.pointer.wrapping_add(1) as u16, memory);
+                self.index_address(state, high_byte, self.regY);
+            }
+            _ => unreachable!("{:?} is not a data addressing mode", mode),
+        }
+        false
+    }
+
+    // Adds the index to the low byte only, the carry into the high byte is applied on the next cycle
+    fn index_address(&mut self, state: &mut CycleState, high_byte: u8, index: u8) {
+        let (low_byte, page_crossed) = (state.address as u8).overflowing_add(index);
+        state.base_high = high_byte;
+        state.page_crossed = page_crossed;
+        state.address = ((high_byte as u16) << 8) | (low_byte as u16);
+        // Reads skip the fix-up cycle when no page is crossed, writes always take it
+        state.fixup = page_crossed
+            || !matches!(state.operation, Operation::Read(..) | Operation::Arithmetic(..) | Operation::Shift(..));
+        state.address_ready = true;
+    }
+
+    // One cycle on the operand once its address is known
+    fn data_cycle<B: Bus>(&mut self, state: &mut CycleState, memory: &mut B) -> bool {
+        if state.fixup {
+            // Dummy read from the address before the page carry
+            self.read(state.address, memory);
+            if state.page_crossed {
+                state.address = state.address.wrapping_add(0x0100);
+            }
+            state.fixup = false;
+            return false;
+        }
+
+        match state.operation {
+            Operation::Read(_, operation) => {
+                let value: u8 = self.read(state.address, memory);
+                operation(self, value);
+                true
+            }
+            Operation::Arithmetic(_, operation) => {
+                if state.data_cycle == 0 {
+                    state.data_cycle = 1;
+                    let value: u8 = self.read(state.address, memory);
+                    operation(self, value);
+                    return !self.decimal_arithmetic();
+                }
+                self.read(state.address, memory);
+                true
+            }
+            Operation::Write(_, value) => {
+                let value: u8 = value(self);
+                self.write(state.address, value, memory);
+                true
+            }
+            Operation::WriteHigh(_, value) => {
+                let value: u8 = value(self);
+                let value: u8 = self.store_high(value, state.base_high);
+                let address: u16 = if state.page_crossed {
+                    ((value as u16) << 8) | (state.address & 0x00FF)
+                } else {
+                    state.address
+                };
+                self.write(address, value, memory);
+                true
+            }
+            Operation::Modify(_, operation) | Operation::Shift(_, operation) => {
+                state.data_cycle += 1;
+                match state.data_cycle {
+                    1 => {
+                        state.value = self.read(state.address, memory);
+                        false
+                    }
+                    2 => {
+                        // NMOS double write: the unmodified value goes back first, the 65C02 reads it again
+                        if self.quirks.rmw_double_write {
+                            self.write(state.address, state.value, memory);
+                        } else {
+                            self.read(state.address, memory);
+                        }
+                        state.value = operation(self, state.value);
+                        false
+                    }
+                    _ => {
+                        self.write(state.address, state.value, memory);
+                        true
+                    }
+                }
+            }
+            _ => unreachable!("only memory operations have a data phase"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+    use crate::cpu::bus::{Access, RecordingBus};
+
+    use super::super::{setup, setup_variant, CoreMode, Variant, MOS6502};
+
+    fn cycle_setup(address: u16, program: &[u8]) -> (MOS6502, RecordingBus) {
+        let (mut cpu, memory) = setup(address, program);
+        cpu.set_core_mode(CoreMode::Cycle);
+        (cpu, memory)
+    }
+
+    // Runs one instruction and returns its bus activity, one access per cycle
+    fn trace(cpu: &mut MOS6502, memory: &mut RecordingBus) -> Vec<Access> {
+        memory.accesses.clear();
+        let cycles: u32 = cpu.step(memory).unwrap().cycles;
+        let accesses: Vec<Access> = memory.take();
+        assert_eq!(accesses.len(), cycles as usize);
+        accesses
+    }
+
+    #[test]
+    fn absolute_x_read_without_page_cross() {
+        // LDA $02F0,X
+        let (mut cpu, mut memory) = cycle_setup(0x0200, &[0xBD, 0xF0, 0x02]);
+        cpu.regX = 0x05;
+        memory[0x02F5] = 0x42;
+        assert_eq!(trace(&mut cpu, &mut memory), vec![
+            Access::Read(0x0200), Access::Read(0x0201), Access::Read(0x0202), Access::Read(0x02F5),
+        ]);
+        assert_eq!(cpu.regA, 0x42);
+    }
+
+    #[test]
+    fn absolute_x_read_page_cross_dummy_read() {
+        // LDA $02F0,X
+        let (mut cpu, mut memory) = cycle_setup(0x0200, &[0xBD, 0xF0, 0x02]);
+        cpu.regX = 0x20;
+        memory[0x0310] = 0x42;
+        assert_eq!(trace(&mut cpu, &mut memory), vec![
+            Access::Read(0x0200), Access::Read(0x0201), Access::Read(0x0202),
+            Access::Read(0x0210), Access::Read(0x0310),
+        ]);
+        assert_eq!(cpu.regA, 0x42);
+    }
+
+    #[test]
+    fn absolute_x_store_always_does_the_fixup_read() {
+        // STA $0300,X
+        let (mut cpu, mut memory) = cycle_setup(0x0200, &[0x9D, 0x00, 0x03]);
+        cpu.regA = 0x42;
+        cpu.regX = 0x01;
+        assert_eq!(trace(&mut cpu, &mut memory), vec![
+            Access::Read(0x0200), Access::Read(0x0201), Access::Read(0x0202),
+            Access::Read(0x0301), Access::Write(0x0301, 0x42),
+        ]);
+    }
+
+    #[test]
+    fn zero_page_x_dummy_read_and_wrap() {
+        // LDA $80,X
+        let (mut cpu, mut memory) = cycle_setup(0x0200, &[0xB5, 0x80]);
+        cpu.regX = 0x90;
+        memory[0x0010] = 0x42;
+        assert_eq!(trace(&mut cpu, &mut memory), vec![
+            Access::Read(0x0200), Access::Read(0x0201), Access::Read(0x0080), Access::Read(0x0010),
+        ]);
+        assert_eq!(cpu.regA, 0x42);
+    }
+
+    #[test]
+    fn indexed_indirect_dummy_read() {
+        // LDA ($80,X)
+        let (mut cpu, mut memory) = cycle_setup(0x0200, &[0xA1, 0x80]);
+        cpu.regX = 0x04;
+        memory[0x0084] = 0x00;
+        memory[0x0085] = 0x03;
+        memory[0x0300] = 0x42;
+        assert_eq!(trace(&mut cpu, &mut memory), vec![
+            Access::Read(0x0200), Access::Read(0x0201), Access::Read(0x0080),
+            Access::Read(0x0084), Access::Read(0x0085), Access::Read(0x0300),
+        ]);
+        assert_eq!(cpu.regA, 0x42);
+    }
+
+    #[test]
+    fn indirect_indexed_page_cross_dummy_read() {
+        // LDA ($80),Y
+        let (mut cpu, mut memory) = cycle_setup(0x0200, &[0xB1, 0x80]);
+        cpu.regY = 0x20;
+        memory[0x0080] = 0xF0;
+        memory[0x0081] = 0x02;
+        memory[0x0310] = 0x42;
+        assert_eq!(trace(&mut cpu, &mut memory), vec![
+            Access::Read(0x0200), Access::Read(0x0201), Access::Read(0x0080),
+            Access::Read(0x0081), Access::Read(0x0210), Access::Read(0x0310),
+        ]);
+        assert_eq!(cpu.regA, 0x42);
+    }
+
+    #[test]
+    fn read_modify_write_double_write() {
+        // INC $0300
+        let (mut cpu, mut memory) = cycle_setup(0x0200, &[0xEE, 0x00, 0x03]);
+        memory[0x0300] = 0x41;
+        assert_eq!(trace(&mut cpu, &mut memory), vec![
+            Access::Read(0x0200), Access::Read(0x0201), Access::Read(0x0202),
+            Access::Read(0x0300), Access::Write(0x0300, 0x41), Access::Write(0x0300, 0x42),
+        ]);
+    }
+
+    #[test]
+    fn jsr_and_rts_stack_accesses() {
+        // JSR $0300 ... RTS
+        let (mut cpu, mut memory) = cycle_setup(0x0200, &[0x20, 0x00, 0x03]);
+        memory[0x0300] = 0x60;
+        assert_eq!(trace(&mut cpu, &mut memory), vec![
+            Access::Read(0x0200), Access::Read(0x0201), Access::Read(0x01FF),
+            Access::Write(0x01FF, 0x02), Access::Write(0x01FE, 0x02), Access::Read(0x0202),
+        ]);
+        assert_eq!(cpu.regPC, 0x0300);
+        assert_eq!(trace(&mut cpu, &mut memory), vec![
+            Access::Read(0x0300), Access::Read(0x0301), Access::Read(0x01FD),
+            Access::Read(0x01FE), Access::Read(0x01FF), Access::Read(0x0202),
+        ]);
+        assert_eq!(cpu.regPC, 0x0203);
+        assert_eq!(cpu.regSP, 0xFF);
+    }
+
+    #[test]
+    fn rti_stack_accesses() {
+        // RTI
+        let (mut cpu, mut memory) = cycle_setup(0x0200, &[0x40]);
+        cpu.regSP = 0xFC;
+        memory[0x01FD] = 0x00;
+        memory[0x01FE] = 0x34;
+        memory[0x01FF] = 0x12;
+        assert_eq!(trace(&mut cpu, &mut memory), vec![
+            Access::Read(0x0200), Access::Read(0x0201), Access::Read(0x01FC),
+            Access::Read(0x01FD), Access::Read(0x01FE), Access::Read(0x01FF),
+        ]);
+        assert_eq!(cpu.regPC, 0x1234);
+        assert_eq!(cpu.regSP, 0xFF);
+    }
+
+    #[test]
+    fn brk_pushes_and_loads_the_vector() {
+        // BRK
+        let (mut cpu, mut memory) = cycle_setup(0x0200, &[0x00, 0xEA]);
+        memory[0xFFFE] = 0x00;
+        memory[0xFFFF] = 0x04;
+        let status: u8 = cpu.status() | 0x30;
+        assert_eq!(trace(&mut cpu, &mut memory), vec![
+            Access::Read(0x0200), Access::Read(0x0201), Access::Write(0x01FF, 0x02),
+            Access::Write(0x01FE, 0x02), Access::Write(0x01FD, status),
+            Access::Read(0xFFFE), Access::Read(0xFFFF),
+        ]);
+        assert_eq!(cpu.regPC, 0x0400);
+        assert!(cpu.proc_status.interrupt_disable());
+    }
+
+    #[test]
+    fn nmi_during_brk_hijacks_the_vector() {
+        for (variant, pc) in [(Variant::NMOS6502, 0x0500), (Variant::CMOS65C02, 0x0400)] {
+            // BRK, with NMI raised while the return address is pushed
+            let (mut cpu, mut memory) = setup_variant(variant, 0x0200, &[0x00, 0xEA]);
+            cpu.set_core_mode(CoreMode::Cycle);
+            memory[0xFFFA] = 0x00;
+            memory[0xFFFB] = 0x05;
+            memory[0xFFFE] = 0x00;
+            memory[0xFFFF] = 0x04;
+            for _ in 0..3 {
+                cpu.tick(&mut memory).unwrap();
+            }
+            cpu.nmi(true);
+            while cpu.cycle_state.is_some() {
+                cpu.tick(&mut memory).unwrap();
+            }
+            assert_eq!(cpu.regPC, pc);
+            // B is pushed either way, the NMI is only still pending without hijacking
+            assert_eq!(memory[0x01FD] & 0x10, 0x10);
+            assert_eq!(cpu.nmi_pending, variant == Variant::CMOS65C02);
+        }
+    }
+
+    #[test]
+    fn branch_page_cross_takes_four_cycles() {
+        // BNE +$20 from the end of a page
+        let (mut cpu, mut memory) = cycle_setup(0x02F0, &[0xD0, 0x20]);
+        assert_eq!(trace(&mut cpu, &mut memory), vec![
+            Access::Read(0x02F0), Access::Read(0x02F1), Access::Read(0x02F2), Access::Read(0x0212),
+        ]);
+        assert_eq!(cpu.regPC, 0x0312);
+    }
+
+    #[test]
+    fn cli_delays_a_pending_irq_by_one_instruction() {
+        // CLI; NOP; NOP
+        let (mut cpu, mut memory) = cycle_setup(0x0200, &[0x58, 0xEA, 0xEA]);
+        memory[0xFFFE] = 0x00;
+        memory[0xFFFF] = 0x04;
+        cpu.proc_status.set_interrupt_disable();
+        cpu.irq(true);
+        cpu.step(&mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0201);
+        // The IRQ was polled before CLI cleared I, the first NOP still runs
+        cpu.step(&mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0202);
+        cpu.step(&mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0400);
+    }
+
+    #[test]
+    fn sei_still_lets_a_pending_irq_in() {
+        // SEI; NOP
+        let (mut cpu, mut memory) = cycle_setup(0x0200, &[0x78, 0xEA]);
+        memory[0xFFFE] = 0x00;
+        memory[0xFFFF] = 0x04;
+        cpu.irq(true);
+        cpu.step(&mut memory).unwrap();
+        assert!(cpu.proc_status.interrupt_disable());
+        cpu.step(&mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0400);
+        assert_eq!(memory[0x01FE], 0x01);
+    }
+
+    #[test]
+    fn irq_raised_on_the_last_cycle_waits_an_instruction() {
+        // LDA #$00; NOP
+        let (mut cpu, mut memory) = cycle_setup(0x0200, &[0xA9, 0x00, 0xEA]);
+        memory[0xFFFE] = 0x00;
+        memory[0xFFFF] = 0x04;
+        cpu.tick(&mut memory).unwrap();
+        cpu.irq(true);
+        cpu.tick(&mut memory).unwrap();
+        cpu.step(&mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0203);
+        cpu.step(&mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0400);
+    }
+
+    #[test]
+    fn cycle_and_instruction_cores_agree() {
+        // LDX #$03; loop: DEX; BNE loop; INC $0300; STA $0300,X; NOP
+        let program: [u8; 12] = [0xA2, 0x03, 0xCA, 0xD0, 0xFD, 0xEE, 0x00, 0x03, 0x9D, 0x00, 0x03, 0xEA];
+        let (mut stepped, mut stepped_memory) = setup(0x0200, &program);
+        let (mut ticked, mut ticked_memory) = cycle_setup(0x0200, &program);
+        for _ in 0..10 {
+            assert_eq!(stepped.step(&mut stepped_memory).unwrap(), ticked.step(&mut ticked_memory).unwrap());
+        }
+        assert_eq!(stepped.pc(), ticked.pc());
+        assert_eq!(stepped.status(), ticked.status());
+        assert_eq!(stepped.total_cycles(), ticked.total_cycles());
+        assert_eq!(stepped_memory.writes(), ticked_memory.writes());
+    }
+}
@@ -0,0 +1,103 @@
+use super::MOS6502;
+
+impl MOS6502 {
+    // Carry flag
+    pub fn clc(&mut self) -> u32 {
+        self.proc_status.clear_carry();
+        0
+    }
+
+    pub fn sec(&mut self) -> u32 {
+        self.proc_status.set_carry();
+        0
+    }
+
+    // Interrupt disable flag
+    pub fn cli(&mut self) -> u32 {
+        self.proc_status.clear_interrupt_disable();
+        0
+    }
+
+    pub fn sei(&mut self) -> u32 {
+        self.proc_status.set_interrupt_disable();
+        0
+    }
+
+    // Decimal mode flag
+    pub fn cld(&mut self) -> u32 {
+        self.proc_status.clear_decimal_mode();
+        0
+    }
+
+    pub fn sed(&mut self) -> u32 {
+        self.proc_status.set_decimal_mode();
+        0
+    }
+
+    // Overflow flag, it can only be cleared
+    pub fn clv(&mut self) -> u32 {
+        self.proc_status.clear_overflow();
+        0
+    }
+
+    // No operation
+    pub fn nop(&mut self) -> u32 {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+
+    use super::super::setup;
+
+    #[test]
+    fn set_and_clear_each_flag() {
+        // SEC; SEI; SED; CLC; CLI; CLD
+        let (mut cpu, mut memory) = setup(0x0200, &[0x38, 0x78, 0xF8, 0x18, 0x58, 0xD8]);
+        cpu.execute(6, &mut memory).unwrap();
+        assert!(cpu.proc_status.carry());
+        assert!(cpu.proc_status.interrupt_disable());
+        assert!(cpu.proc_status.decimal_mode());
+        cpu.execute(6, &mut memory).unwrap();
+        assert!(!cpu.proc_status.carry());
+        assert!(!cpu.proc_status.interrupt_disable());
+        assert!(!cpu.proc_status.decimal_mode());
+        assert_eq!(cpu.regPC, 0x0206);
+    }
+
+    #[test]
+    fn clv_clears_overflow() {
+        let (mut cpu, mut memory) = setup(0x0200, &[0xB8]);
+        cpu.proc_status.set_overflow();
+        cpu.execute(2, &mut memory).unwrap();
+        assert!(!cpu.proc_status.overflow());
+    }
+
+    #[test]
+    fn nop_only_advances_pc() {
+        let (mut cpu, mut memory) = setup(0x0200, &[0xEA, 0xEA]);
+        cpu.regA = 0x12;
+        let status: u8 = u8::from(cpu.proc_status);
+        cpu.execute(4, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0202);
+        assert_eq!(cpu.regA, 0x12);
+        assert_eq!(u8::from(cpu.proc_status), status);
+    }
+
+    #[test]
+    fn cli_lets_a_held_irq_in() {
+        // SEI; CLI; NOP
+        let (mut cpu, mut memory) = setup(0x0200, &[0x78, 0x58, 0xEA]);
+        memory[0xFFFE] = 0x00;
+        memory[0xFFFF] = 0x80;
+        cpu.irq(true);
+        // The IRQ is taken before SEI, so start with I set
+        cpu.proc_status.set_interrupt_disable();
+        cpu.execute(4, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0202);
+        cpu.execute(7, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x8000);
+    }
+}
@@ -0,0 +1,912 @@
+use crate::cpu::bus::Bus; 
+
+use crate::cpu::cpu::CPU;
+
+use super::addressing::{AddressingMode, Operand};
+use super::processor_status::ProcessorStatus;
+use super::MOS6502;
+
+use num_enum::TryFromPrimitive;
+use num_enum::IntoPrimitive;
+use std::convert::TryFrom;
+
+// Stable undocumented NMOS opcodes, the unstable XAA, LAX #imm, AHX, TAS, SHX, SHY and LAS,
+// and the JAM opcodes that lock up the processor
+#[derive(Debug, TryFromPrimitive, IntoPrimitive)]
+#[repr(u8)]
+pub(super) enum UndocumentedInstr {
+    SLO_ZP = 0x07,
+    SLO_ZPX = 0x17,
+    SLO_ABS = 0x0F,
+    SLO_ABSX = 0x1F,
+    SLO_ABSY = 0x1B,
+    SLO_INDX = 0x03,
+    SLO_INDY = 0x13,
+    RLA_ZP = 0x27,
+    RLA_ZPX = 0x37,
+    RLA_ABS = 0x2F,
+    RLA_ABSX = 0x3F,
+    RLA_ABSY = 0x3B,
+    RLA_INDX = 0x23,
+    RLA_INDY = 0x33,
+    SRE_ZP = 0x47,
+    SRE_ZPX = 0x57,
+    SRE_ABS = 0x4F,
+    SRE_ABSX = 0x5F,
+    SRE_ABSY = 0x5B,
+    SRE_INDX = 0x43,
+    SRE_INDY = 0x53,
+    RRA_ZP = 0x67,
+    RRA_ZPX = 0x77,
+    RRA_ABS = 0x6F,
+    RRA_ABSX = 0x7F,
+    RRA_ABSY = 0x7B,
+    RRA_INDX = 0x63,
+    RRA_INDY = 0x73,
+    DCP_ZP = 0xC7,
+    DCP_ZPX = 0xD7,
+    DCP_ABS = 0xCF,
+    DCP_ABSX = 0xDF,
+    DCP_ABSY = 0xDB,
+    DCP_INDX = 0xC3,
+    DCP_INDY = 0xD3,
+    ISC_ZP = 0xE7,
+    ISC_ZPX = 0xF7,
+    ISC_ABS = 0xEF,
+    ISC_ABSX = 0xFF,
+    ISC_ABSY = 0xFB,
+    ISC_INDX = 0xE3,
+    ISC_INDY = 0xF3,
+    LAX_ZP = 0xA7,
+    LAX_ZPY = 0xB7,
+    LAX_ABS = 0xAF,
+    LAX_ABSY = 0xBF,
+    LAX_INDX = 0xA3,
+    LAX_INDY = 0xB3,
+    SAX_ZP = 0x87,
+    SAX_ZPY = 0x97,
+    SAX_ABS = 0x8F,
+    SAX_INDX = 0x83,
+    ANC_IM = 0x0B,
+    ANC_IM_ALT = 0x2B,
+    ALR_IM = 0x4B,
+    ARR_IM = 0x6B,
+    SBX_IM = 0xCB,
+    USBC_IM = 0xEB,
+    NOP_IMP_1A = 0x1A,
+    NOP_IMP_3A = 0x3A,
+    NOP_IMP_5A = 0x5A,
+    NOP_IMP_7A = 0x7A,
+    NOP_IMP_DA = 0xDA,
+    NOP_IMP_FA = 0xFA,
+    NOP_IM_80 = 0x80,
+    NOP_IM_82 = 0x82,
+    NOP_IM_89 = 0x89,
+    NOP_IM_C2 = 0xC2,
+    NOP_IM_E2 = 0xE2,
+    NOP_ZP_04 = 0x04,
+    NOP_ZP_44 = 0x44,
+    NOP_ZP_64 = 0x64,
+    NOP_ZPX_14 = 0x14,
+    NOP_ZPX_34 = 0x34,
+    NOP_ZPX_54 = 0x54,
+    NOP_ZPX_74 = 0x74,
+    NOP_ZPX_D4 = 0xD4,
+    NOP_ZPX_F4 = 0xF4,
+    NOP_ABS = 0x0C,
+    NOP_ABSX_1C = 0x1C,
+    NOP_ABSX_3C = 0x3C,
+    NOP_ABSX_5C = 0x5C,
+    NOP_ABSX_7C = 0x7C,
+    NOP_ABSX_DC = 0xDC,
+    NOP_ABSX_FC = 0xFC,
+    XAA_IM = 0x8B,
+    LAX_IM = 0xAB,
+    LAS_ABSY = 0xBB,
+    AHX_ABSY = 0x9F,
+    AHX_INDY = 0x93,
+    TAS_ABSY = 0x9B,
+    SHX_ABSY = 0x9E,
+    SHY_ABSX = 0x9C,
+    JAM_02 = 0x02,
+    JAM_12 = 0x12,
+    JAM_22 = 0x22,
+    JAM_32 = 0x32,
+    JAM_42 = 0x42,
+    JAM_52 = 0x52,
+    JAM_62 = 0x62,
+    JAM_72 = 0x72,
+    JAM_92 = 0x92,
+    JAM_B2 = 0xB2,
+    JAM_D2 = 0xD2,
+    JAM_F2 = 0xF2,
+}
+
+fn on_undoc_set_status(proc_status: &mut ProcessorStatus, value: u8) {
+    if value == 0 {
+        proc_status.set_zero();
+    } else {
+        proc_status.clear_zero();
+    }
+    if value & 0b1000_0000 != 0 {
+        proc_status.set_negative();
+    } else {
+        proc_status.clear_negative();
+    }
+}
+
+impl MOS6502 {
+    // Executes an undocumented opcode and returns its penalty cycles, None when the opcode is not one
+    pub(super) fn execute_undocumented<B: Bus>(&mut self, instruction: u8, memory: &mut B) -> Option<u32> {
+        let penalty: u32 = match UndocumentedInstr::try_from(instruction) {
+            Ok(UndocumentedInstr::SLO_ZP) => self.slo_zp(memory),
+            Ok(UndocumentedInstr::SLO_ZPX) => self.slo_zpx(memory),
+            Ok(UndocumentedInstr::SLO_ABS) => self.slo_abs(memory),
+            Ok(UndocumentedInstr::SLO_ABSX) => self.slo_absx(memory),
+            Ok(UndocumentedInstr::SLO_ABSY) => self.slo_absy(memory),
+            Ok(UndocumentedInstr::SLO_INDX) => self.slo_indx(memory),
+            Ok(UndocumentedInstr::SLO_INDY) => self.slo_indy(memory),
+            Ok(UndocumentedInstr::RLA_ZP) => self.rla_zp(memory),
+            Ok(UndocumentedInstr::RLA_ZPX) => self.rla_zpx(memory),
+            Ok(UndocumentedInstr::RLA_ABS) => self.rla_abs(memory),
+            Ok(UndocumentedInstr::RLA_ABSX) => self.rla_absx(memory),
+            Ok(UndocumentedInstr::RLA_ABSY) => self.rla_absy(memory),
+            Ok(UndocumentedInstr::RLA_INDX) => self.rla_indx(memory),
+            Ok(UndocumentedInstr::RLA_INDY) => self.rla_indy(memory),
+            Ok(UndocumentedInstr::SRE_ZP) => self.sre_zp(memory),
+            Ok(UndocumentedInstr::SRE_ZPX) => self.sre_zpx(memory),
+            Ok(UndocumentedInstr::SRE_ABS) => self.sre_abs(memory),
+            Ok(UndocumentedInstr::SRE_ABSX) => self.sre_absx(memory),
+            Ok(UndocumentedInstr::SRE_ABSY) => self.sre_absy(memory),
+            Ok(UndocumentedInstr::SRE_INDX) => self.sre_indx(memory),
+            Ok(UndocumentedInstr::SRE_INDY) => self.sre_indy(memory),
+            Ok(UndocumentedInstr::RRA_ZP) => self.rra_zp(memory),
+            Ok(UndocumentedInstr::RRA_ZPX) => self.rra_zpx(memory),
+            Ok(UndocumentedInstr::RRA_ABS) => self.rra_abs(memory),
+            Ok(UndocumentedInstr::RRA_ABSX) => self.rra_absx(memory),
+            Ok(UndocumentedInstr::RRA_ABSY) => self.rra_absy(memory),
+            Ok(UndocumentedInstr::RRA_INDX) => self.rra_indx(memory),
+            Ok(UndocumentedInstr::RRA_INDY) => self.rra_indy(memory),
+            Ok(UndocumentedInstr::DCP_ZP) => self.dcp_zp(memory),
+            Ok(UndocumentedInstr::DCP_ZPX) => self.dcp_zpx(memory),
+            Ok(UndocumentedInstr::DCP_ABS) => self.dcp_abs(memory),
+            Ok(UndocumentedInstr::DCP_ABSX) => self.dcp_absx(memory),
+            Ok(UndocumentedInstr::DCP_ABSY) => self.dcp_absy(memory),
+            Ok(UndocumentedInstr::DCP_INDX) => self.dcp_indx(memory),
+            Ok(UndocumentedInstr::DCP_INDY) => self.dcp_indy(memory),
+            Ok(UndocumentedInstr::ISC_ZP) => self.isc_zp(memory),
+            Ok(UndocumentedInstr::ISC_ZPX) => self.isc_zpx(memory),
+            Ok(UndocumentedInstr::ISC_ABS) => self.isc_abs(memory),
+            Ok(UndocumentedInstr::ISC_ABSX) => self.isc_absx(memory),
+            Ok(UndocumentedInstr::ISC_ABSY) => self.isc_absy(memory),
+            Ok(UndocumentedInstr::ISC_INDX) => self.isc_indx(memory),
+            Ok(UndocumentedInstr::ISC_INDY) => self.isc_indy(memory),
+            Ok(UndocumentedInstr::LAX_ZP) => self.lax_zp(memory),
+            Ok(UndocumentedInstr::LAX_ZPY) => self.lax_zpy(memory),
+            Ok(UndocumentedInstr::LAX_ABS) => self.lax_abs(memory),
+            Ok(UndocumentedInstr::LAX_ABSY) => self.lax_absy(memory),
+            Ok(UndocumentedInstr::LAX_INDX) => self.lax_indx(memory),
+            Ok(UndocumentedInstr::LAX_INDY) => self.lax_indy(memory),
+            Ok(UndocumentedInstr::SAX_ZP) => self.sax_zp(memory),
+            Ok(UndocumentedInstr::SAX_ZPY) => self.sax_zpy(memory),
+            Ok(UndocumentedInstr::SAX_ABS) => self.sax_abs(memory),
+            Ok(UndocumentedInstr::SAX_INDX) => self.sax_indx(memory),
+            Ok(UndocumentedInstr::ANC_IM | UndocumentedInstr::ANC_IM_ALT) => self.anc_im(memory),
+            Ok(UndocumentedInstr::ALR_IM) => self.alr_im(memory),
+            Ok(UndocumentedInstr::ARR_IM) => self.arr_im(memory),
+            Ok(UndocumentedInstr::SBX_IM) => self.sbx_im(memory),
+            Ok(UndocumentedInstr::USBC_IM) => self.usbc_im(memory),
+            Ok(UndocumentedInstr::NOP_IMP_1A | UndocumentedInstr::NOP_IMP_3A | UndocumentedInstr::NOP_IMP_5A | UndocumentedInstr::NOP_IMP_7A | UndocumentedInstr::NOP_IMP_DA | UndocumentedInstr::NOP_IMP_FA) => self.nop(),
+            Ok(UndocumentedInstr::NOP_IM_80 | UndocumentedInstr::NOP_IM_82 | UndocumentedInstr::NOP_IM_89 | UndocumentedInstr::NOP_IM_C2 | UndocumentedInstr::NOP_IM_E2) => self.nop_im(memory),
+            Ok(UndocumentedInstr::NOP_ZP_04 | UndocumentedInstr::NOP_ZP_44 | UndocumentedInstr::NOP_ZP_64) => self.nop_zp(memory),
+            Ok(UndocumentedInstr::NOP_ZPX_14 | UndocumentedInstr::NOP_ZPX_34 | UndocumentedInstr::NOP_ZPX_54 | UndocumentedInstr::NOP_ZPX_74 | UndocumentedInstr::NOP_ZPX_D4 | UndocumentedInstr::NOP_ZPX_F4) => self.nop_zpx(memory),
+            Ok(UndocumentedInstr::NOP_ABS) => self.nop_abs(memory),
+            Ok(UndocumentedInstr::NOP_ABSX_1C | UndocumentedInstr::NOP_ABSX_3C | UndocumentedInstr::NOP_ABSX_5C | UndocumentedInstr::NOP_ABSX_7C | UndocumentedInstr::NOP_ABSX_DC | UndocumentedInstr::NOP_ABSX_FC) => self.nop_absx(memory),
+            Ok(UndocumentedInstr::XAA_IM) => self.xaa_im(memory),
+            Ok(UndocumentedInstr::LAX_IM) => self.lax_im(memory),
+            Ok(UndocumentedInstr::LAS_ABSY) => self.las_absy(memory),
+            Ok(UndocumentedInstr::AHX_ABSY) => self.ahx_absy(memory),
+            Ok(UndocumentedInstr::AHX_INDY) => self.ahx_indy(memory),
+            Ok(UndocumentedInstr::TAS_ABSY) => self.tas_absy(memory),
+            Ok(UndocumentedInstr::SHX_ABSY) => self.shx_absy(memory),
+            Ok(UndocumentedInstr::SHY_ABSX) => self.shy_absx(memory),
+            Ok(UndocumentedInstr::JAM_02
+               | UndocumentedInstr::JAM_12
+               | UndocumentedInstr::JAM_22
+               | UndocumentedInstr::JAM_32
+               | UndocumentedInstr::JAM_42
+               | UndocumentedInstr::JAM_52
+               | UndocumentedInstr::JAM_62
+               | UndocumentedInstr::JAM_72
+               | UndocumentedInstr::JAM_92
+               | UndocumentedInstr::JAM_B2
+               | UndocumentedInstr::JAM_D2
+               | UndocumentedInstr::JAM_F2) => self.jam(),
+            Err(_) => return None,
+        };
+        Some(penalty)
+    }
+
+    // SLO: shift left memory, then OR into the accumulator
+    pub(super) fn slo(&mut self, value: u8) -> u8 {
+        let result: u8 = self.asl(value);
+        self.regA |= result;
+        on_undoc_set_status(&mut self.proc_status, self.regA);
+        result
+    }
+
+    pub fn slo_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPage, memory, MOS6502::slo)
+    }
+
+    pub fn slo_zpx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPageX, memory, MOS6502::slo)
+    }
+
+    pub fn slo_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::Absolute, memory, MOS6502::slo)
+    }
+
+    pub fn slo_absx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteX, memory, MOS6502::slo)
+    }
+
+    pub fn slo_absy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteY, memory, MOS6502::slo)
+    }
+
+    pub fn slo_indx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::IndirectX, memory, MOS6502::slo)
+    }
+
+    pub fn slo_indy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::IndirectY, memory, MOS6502::slo)
+    }
+
+    // RLA: rotate left memory, then AND into the accumulator
+    pub(super) fn rla(&mut self, value: u8) -> u8 {
+        let result: u8 = self.rol(value);
+        self.regA &= result;
+        on_undoc_set_status(&mut self.proc_status, self.regA);
+        result
+    }
+
+    pub fn rla_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPage, memory, MOS6502::rla)
+    }
+
+    pub fn rla_zpx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPageX, memory, MOS6502::rla)
+    }
+
+    pub fn rla_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::Absolute, memory, MOS6502::rla)
+    }
+
+    pub fn rla_absx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteX, memory, MOS6502::rla)
+    }
+
+    pub fn rla_absy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteY, memory, MOS6502::rla)
+    }
+
+    pub fn rla_indx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::IndirectX, memory, MOS6502::rla)
+    }
+
+    pub fn rla_indy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::IndirectY, memory, MOS6502::rla)
+    }
+
+    // SRE: shift right memory, then EOR into the accumulator
+    pub(super) fn sre(&mut self, value: u8) -> u8 {
+        let result: u8 = self.lsr(value);
+        self.regA ^= result;
+        on_undoc_set_status(&mut self.proc_status, self.regA);
+        result
+    }
+
+    pub fn sre_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPage, memory, MOS6502::sre)
+    }
+
+    pub fn sre_zpx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPageX, memory, MOS6502::sre)
+    }
+
+    pub fn sre_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::Absolute, memory, MOS6502::sre)
+    }
+
+    pub fn sre_absx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteX, memory, MOS6502::sre)
+    }
+
+    pub fn sre_absy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteY, memory, MOS6502::sre)
+    }
+
+    pub fn sre_indx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::IndirectX, memory, MOS6502::sre)
+    }
+
+    pub fn sre_indy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::IndirectY, memory, MOS6502::sre)
+    }
+
+    // RRA: rotate right memory, then add it to the accumulator with the carry it shifted out
+    pub(super) fn rra(&mut self, value: u8) -> u8 {
+        let result: u8 = self.ror(value);
+        self.adc(result);
+        result
+    }
+
+    pub fn rra_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPage, memory, MOS6502::rra)
+    }
+
+    pub fn rra_zpx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPageX, memory, MOS6502::rra)
+    }
+
+    pub fn rra_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::Absolute, memory, MOS6502::rra)
+    }
+
+    pub fn rra_absx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteX, memory, MOS6502::rra)
+    }
+
+    pub fn rra_absy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteY, memory, MOS6502::rra)
+    }
+
+    pub fn rra_indx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::IndirectX, memory, MOS6502::rra)
+    }
+
+    pub fn rra_indy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::IndirectY, memory, MOS6502::rra)
+    }
+
+    // DCP: decrement memory, then compare it with the accumulator
+    pub(super) fn dcp(&mut self, value: u8) -> u8 {
+        let result: u8 = value.wrapping_sub(1);
+        self.cmpa2cmp(result);
+        result
+    }
+
+    pub fn dcp_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPage, memory, MOS6502::dcp)
+    }
+
+    pub fn dcp_zpx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPageX, memory, MOS6502::dcp)
+    }
+
+    pub fn dcp_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::Absolute, memory, MOS6502::dcp)
+    }
+
+    pub fn dcp_absx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteX, memory, MOS6502::dcp)
+    }
+
+    pub fn dcp_absy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteY, memory, MOS6502::dcp)
+    }
+
+    pub fn dcp_indx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::IndirectX, memory, MOS6502::dcp)
+    }
+
+    pub fn dcp_indy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::IndirectY, memory, MOS6502::dcp)
+    }
+
+    // ISC: increment memory, then subtract it from the accumulator
+    pub(super) fn isc(&mut self, value: u8) -> u8 {
+        let result: u8 = value.wrapping_add(1);
+        self.sbc(result);
+        result
+    }
+
+    pub fn isc_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPage, memory, MOS6502::isc)
+    }
+
+    pub fn isc_zpx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPageX, memory, MOS6502::isc)
+    }
+
+    pub fn isc_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::Absolute, memory, MOS6502::isc)
+    }
+
+    pub fn isc_absx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteX, memory, MOS6502::isc)
+    }
+
+    pub fn isc_absy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteY, memory, MOS6502::isc)
+    }
+
+    pub fn isc_indx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::IndirectX, memory, MOS6502::isc)
+    }
+
+    pub fn isc_indy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::IndirectY, memory, MOS6502::isc)
+    }
+
+    // LAX: load the accumulator and X with the same value
+    pub(super) fn lax(&mut self, value: u8) {
+        self.regA = value;
+        self.regX = value;
+        on_undoc_set_status(&mut self.proc_status, value);
+    }
+
+    pub fn lax_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::ZeroPage, memory, MOS6502::lax)
+    }
+
+    pub fn lax_zpy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::ZeroPageY, memory, MOS6502::lax)
+    }
+
+    pub fn lax_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Absolute, memory, MOS6502::lax)
+    }
+
+    pub fn lax_absy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::AbsoluteY, memory, MOS6502::lax)
+    }
+
+    pub fn lax_indx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::IndirectX, memory, MOS6502::lax)
+    }
+
+    pub fn lax_indy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::IndirectY, memory, MOS6502::lax)
+    }
+
+    // SAX: store the accumulator ANDed with X, flags are not affected
+    pub fn sax_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.write_operand(AddressingMode::ZeroPage, self.regA & self.regX, memory)
+    }
+
+    pub fn sax_zpy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.write_operand(AddressingMode::ZeroPageY, self.regA & self.regX, memory)
+    }
+
+    pub fn sax_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.write_operand(AddressingMode::Absolute, self.regA & self.regX, memory)
+    }
+
+    pub fn sax_indx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.write_operand(AddressingMode::IndirectX, self.regA & self.regX, memory)
+    }
+
+    // ANC: AND immediate, then copy N into C
+    pub(super) fn anc(&mut self, value: u8) {
+        self.regA &= value;
+        on_undoc_set_status(&mut self.proc_status, self.regA);
+        if self.proc_status.negative() {
+            self.proc_status.set_carry();
+        } else {
+            self.proc_status.clear_carry();
+        }
+    }
+
+    pub fn anc_im<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Immediate, memory, MOS6502::anc)
+    }
+
+    // ALR: AND immediate, then shift the accumulator right
+    pub(super) fn alr(&mut self, value: u8) {
+        self.regA = self.lsr(self.regA & value);
+    }
+
+    pub fn alr_im<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Immediate, memory, MOS6502::alr)
+    }
+
+    // ARR: AND immediate, then rotate the accumulator right.
+    // C comes from bit 6 and V from bit 6 XOR bit 5 of the result,
+    // in decimal mode the NMOS adder also applies a BCD fixup
+    pub(super) fn arr(&mut self, value: u8) {
+        let and: u8 = self.regA & value;
+        let carry: u8 = if self.proc_status.carry() { 1 } else { 0 };
+        let result: u8 = (and >> 1) | (carry << 7);
+        on_undoc_set_status(&mut self.proc_status, result);
+
+        if !self.decimal_arithmetic() {
+            if result & 0b0100_0000 != 0 {
+                self.proc_status.set_carry();
+            } else {
+                self.proc_status.clear_carry();
+            }
+            if ((result >> 6) ^ (result >> 5)) & 1 != 0 {
+                self.proc_status.set_overflow();
+            } else {
+                self.proc_status.clear_overflow();
+            }
+            self.regA = result;
+            return;
+        }
+
+        if (and ^ result) & 0b0100_0000 != 0 {
+            self.proc_status.set_overflow();
+        } else {
+            self.proc_status.clear_overflow();
+        }
+        let mut result: u8 = result;
+        let low_nibble: u8 = and & 0x0F;
+        let high_nibble: u8 = and >> 4;
+        if low_nibble + (low_nibble & 1) > 5 {
+            result = (result & 0xF0) | (result.wrapping_add(6) & 0x0F);
+        }
+        if high_nibble + (high_nibble & 1) > 5 {
+            self.proc_status.set_carry();
+            result = result.wrapping_add(0x60);
+        } else {
+            self.proc_status.clear_carry();
+        }
+        self.regA = result;
+    }
+
+    pub fn arr_im<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Immediate, memory, MOS6502::arr)
+    }
+
+    // SBX: X = (A AND X) - immediate, carry as in CMP, decimal mode is ignored
+    pub(super) fn sbx(&mut self, value: u8) {
+        let and: u8 = self.regA & self.regX;
+        if and >= value {
+            self.proc_status.set_carry();
+        } else {
+            self.proc_status.clear_carry();
+        }
+        self.regX = and.wrapping_sub(value);
+        on_undoc_set_status(&mut self.proc_status, self.regX);
+    }
+
+    pub fn sbx_im<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Immediate, memory, MOS6502::sbx)
+    }
+
+    // $EB behaves exactly like the documented SBC immediate
+    pub fn usbc_im<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Immediate, memory, MOS6502::sbc)
+    }
+
+    // Multi-byte NOPs still perform the read of their addressing mode
+    pub(super) fn ignore(&mut self, _value: u8) {}
+
+    pub fn nop_im<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Immediate, memory, MOS6502::ignore)
+    }
+
+    pub fn nop_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::ZeroPage, memory, MOS6502::ignore)
+    }
+
+    pub fn nop_zpx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::ZeroPageX, memory, MOS6502::ignore)
+    }
+
+    pub fn nop_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Absolute, memory, MOS6502::ignore)
+    }
+
+    pub fn nop_absx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::AbsoluteX, memory, MOS6502::ignore)
+    }
+
+    // JAM: the processor stops fetching instructions and ignores interrupts until it is reset
+    pub fn jam(&mut self) -> u32 {
+        self.regPC = self.regPC.wrapping_sub(1);
+        self.jammed = true;
+        0
+    }
+
+    // XAA: unstable, A = (A OR magic) AND X AND immediate
+    pub(super) fn xaa(&mut self, value: u8) {
+        self.regA = (self.regA | self.quirks.unstable_magic) & self.regX & value;
+        on_undoc_set_status(&mut self.proc_status, self.regA);
+    }
+
+    pub fn xaa_im<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Immediate, memory, MOS6502::xaa)
+    }
+
+    // LAX #imm: unstable, A = X = (A OR magic) AND immediate
+    pub(super) fn lxa(&mut self, value: u8) {
+        self.regA = (self.regA | self.quirks.unstable_magic) & value;
+        self.regX = self.regA;
+        on_undoc_set_status(&mut self.proc_status, self.regA);
+    }
+
+    pub fn lax_im<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::Immediate, memory, MOS6502::lxa)
+    }
+
+    // LAS: A = X = SP = memory AND SP
+    pub(super) fn las(&mut self, value: u8) {
+        let result: u8 = value & self.regSP;
+        self.regA = result;
+        self.regX = result;
+        self.regSP = result;
+        on_undoc_set_status(&mut self.proc_status, result);
+    }
+
+    pub fn las_absy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.read_operand(AddressingMode::AbsoluteY, memory, MOS6502::las)
+    }
+
+    // Value stored by SHA, SHX, SHY and TAS, the AND with the high byte + 1 is unstable
+    pub(super) fn store_high(&self, value: u8, high_byte: u8) -> u8 {
+        if self.quirks.store_high_and {
+            value & high_byte.wrapping_add(1)
+        } else {
+            value
+        }
+    }
+
+    // SHA, SHX, SHY and TAS store a value ANDed with the high byte of the base address plus one.
+    // When the index crosses a page the stored value also replaces the high byte of the target.
+    // The fix-up read always happens, so there is never a penalty to return
+    fn store_high_operand<B: Bus>(&mut self, mode: AddressingMode, value: u8, memory: &mut B) -> u32 {
+        let operand: Operand = self.resolve(mode, memory);
+        let unfixed_address: u16 = if operand.page_crossed {
+            operand.address.wrapping_sub(0x0100)
+        } else {
+            operand.address
+        };
+        self.read(unfixed_address, memory);
+        let value: u8 = self.store_high(value, (unfixed_address >> 8) as u8);
+        let target_address: u16 = if operand.page_crossed {
+            ((value as u16) << 8) | (operand.address & 0x00FF)
+        } else {
+            operand.address
+        };
+        self.write(target_address, value, memory);
+        0
+    }
+
+    // AHX (SHA): store A AND X AND (high byte + 1)
+    pub fn ahx_absy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.store_high_operand(AddressingMode::AbsoluteY, self.regA & self.regX, memory)
+    }
+
+    pub fn ahx_indy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.store_high_operand(AddressingMode::IndirectY, self.regA & self.regX, memory)
+    }
+
+    // TAS (SHS): SP = A AND X, then store SP AND (high byte + 1)
+    pub fn tas_absy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.regSP = self.regA & self.regX;
+        self.store_high_operand(AddressingMode::AbsoluteY, self.regSP, memory)
+    }
+
+    // SHX: store X AND (high byte + 1)
+    pub fn shx_absy<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.store_high_operand(AddressingMode::AbsoluteY, self.regX, memory)
+    }
+
+    // SHY: store Y AND (high byte + 1)
+    pub fn shy_absx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.store_high_operand(AddressingMode::AbsoluteX, self.regY, memory)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+    use crate::cpu::error::ExecutionError;
+
+    use super::super::setup;
+
+    #[test]
+    fn lax_and_sax() {
+        // LAX $10; SAX $11
+        let (mut cpu, mut memory) = setup(0x0200, &[0xA7, 0x10, 0x87, 0x11]);
+        memory[0x0010] = 0x8F;
+        cpu.execute(3, &mut memory).unwrap();
+        assert_eq!((cpu.regA, cpu.regX), (0x8F, 0x8F));
+        assert!(cpu.proc_status.negative());
+
+        cpu.regX = 0x0F;
+        cpu.proc_status.clear_negative();
+        cpu.execute(3, &mut memory).unwrap();
+        assert_eq!(memory[0x0011], 0x0F);
+        assert!(!cpu.proc_status.negative());
+    }
+
+    #[test]
+    fn dcp_and_isc() {
+        // DCP $10; ISC $11
+        let (mut cpu, mut memory) = setup(0x0200, &[0xC7, 0x10, 0xE7, 0x11]);
+        memory[0x0010] = 0x11;
+        memory[0x0011] = 0x0F;
+        cpu.regA = 0x10;
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(memory[0x0010], 0x10);
+        assert!(cpu.proc_status.zero());
+        assert!(cpu.proc_status.carry());
+
+        cpu.regA = 0x20;
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(memory[0x0011], 0x10);
+        assert_eq!(cpu.regA, 0x10);
+        assert!(cpu.proc_status.carry());
+    }
+
+    #[test]
+    fn slo_rla_sre_rra() {
+        // SLO $10; RLA $11; SRE $12; RRA $13
+        let (mut cpu, mut memory) = setup(0x0200, &[0x07, 0x10, 0x27, 0x11, 0x47, 0x12, 0x67, 0x13]);
+        memory[0x0010] = 0x81;
+        memory[0x0011] = 0x40;
+        memory[0x0012] = 0x03;
+        memory[0x0013] = 0x02;
+
+        cpu.regA = 0x01;
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(memory[0x0010], 0x02);
+        assert_eq!(cpu.regA, 0x03);
+        assert!(cpu.proc_status.carry());
+
+        cpu.regA = 0xFF;
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(memory[0x0011], 0x81);
+        assert_eq!(cpu.regA, 0x81);
+        assert!(!cpu.proc_status.carry());
+        assert!(cpu.proc_status.negative());
+
+        cpu.regA = 0x01;
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(memory[0x0012], 0x01);
+        assert_eq!(cpu.regA, 0x00);
+        assert!(cpu.proc_status.carry());
+        assert!(cpu.proc_status.zero());
+
+        // The carry shifted in by ROR is the one ADC then uses
+        cpu.regA = 0x01;
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(memory[0x0013], 0x81);
+        assert_eq!(cpu.regA, 0x82);
+        assert!(!cpu.proc_status.carry());
+    }
+
+    #[test]
+    fn immediate_combinations() {
+        // ANC #$80; ALR #$03; ARR #$C0; SBX #$10
+        let (mut cpu, mut memory) = setup(0x0200, &[0x0B, 0x80, 0x4B, 0x03, 0x6B, 0xC0, 0xCB, 0x10]);
+        cpu.regA = 0xFF;
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regA, 0x80);
+        assert!(cpu.proc_status.carry());
+        assert!(cpu.proc_status.negative());
+
+        cpu.regA = 0xFF;
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regA, 0x01);
+        assert!(cpu.proc_status.carry());
+
+        cpu.regA = 0xFF;
+        cpu.proc_status.clear_carry();
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regA, 0x60);
+        assert!(cpu.proc_status.carry());
+        assert!(!cpu.proc_status.overflow());
+
+        cpu.regA = 0xF0;
+        cpu.regX = 0x3C;
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regX, 0x20);
+        assert_eq!(cpu.regA, 0xF0);
+        assert!(cpu.proc_status.carry());
+        assert_eq!(cpu.regPC, 0x0208);
+    }
+
+    #[test]
+    fn xaa_uses_the_magic_constant() {
+        // XAA #$FF twice
+        let (mut cpu, mut memory) = setup(0x0200, &[0x8B, 0xFF, 0x8B, 0xFF]);
+        cpu.regX = 0xFF;
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regA, 0xEE);
+
+        cpu.regA = 0x00;
+        cpu.set_unstable_magic(0xFF);
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regA, 0xFF);
+    }
+
+    #[test]
+    fn shx_ands_with_the_high_byte() {
+        // SHX $0200,Y
+        let (mut cpu, mut memory) = setup(0x0400, &[0x9E, 0x00, 0x02]);
+        cpu.regX = 0xFF;
+        cpu.regY = 0x01;
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(memory[0x0201], 0x03);
+    }
+
+    #[test]
+    fn shx_page_cross_corrupts_the_target() {
+        // SHX $02FF,Y lands on $0101 instead of $0301
+        let (mut cpu, mut memory) = setup(0x0400, &[0x9E, 0xFF, 0x02]);
+        cpu.regX = 0x01;
+        cpu.regY = 0x02;
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(memory[0x0101], 0x01);
+        assert_eq!(memory[0x0301], 0x00);
+    }
+
+    #[test]
+    fn store_high_and_can_be_disabled() {
+        // SHX $0200,Y
+        let (mut cpu, mut memory) = setup(0x0400, &[0x9E, 0x00, 0x02]);
+        cpu.set_store_high_and(false);
+        cpu.regX = 0xFF;
+        cpu.regY = 0x01;
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(memory[0x0201], 0xFF);
+    }
+
+    #[test]
+    fn nop_lengths_and_timings() {
+        // Opcode, bytes, cycles
+        let nops: [(u8, u16, u32); 6] = [
+            (0x1A, 1, 2), (0x80, 2, 2), (0x04, 2, 3), (0x14, 2, 4), (0x0C, 3, 4), (0x1C, 3, 4),
+        ];
+        for (opcode, length, cycles) in nops {
+            let (mut cpu, mut memory) = setup(0x0200, &[opcode, 0x00, 0x03]);
+            cpu.execute(cycles, &mut memory).unwrap();
+            assert_eq!(cpu.regPC, 0x0200 + length, "{:#04X}", opcode);
+        }
+        // NOP $03FF,X crossing a page
+        let (mut cpu, mut memory) = setup(0x0200, &[0x1C, 0xFF, 0x03]);
+        cpu.regX = 0x01;
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0203);
+    }
+
+    #[test]
+    fn every_jam_opcode_halts() {
+        for opcode in [0x02, 0x12, 0x22, 0x32, 0x42, 0x52, 0x62, 0x72, 0x92, 0xB2, 0xD2, 0xF2] {
+            let (mut cpu, mut memory) = setup(0x0200, &[opcode]);
+            assert_eq!(cpu.execute(2, &mut memory), Err(ExecutionError::Jammed { pc: 0x0200 }), "{:#04X}", opcode);
+            assert!(cpu.halted(), "{:#04X}", opcode);
+            assert_eq!(cpu.regPC, 0x0200, "{:#04X}", opcode);
+        }
+    }
+
+    #[test]
+    fn jammed_cpu_ignores_interrupts_until_reset() {
+        // JAM
+        let (mut cpu, mut memory) = setup(0x0200, &[0x02, 0xEA]);
+        memory[0xFFFA] = 0x00;
+        memory[0xFFFB] = 0x90;
+        memory[0xFFFC] = 0x01;
+        memory[0xFFFD] = 0x02;
+        assert!(cpu.execute(2, &mut memory).is_err());
+        assert!(cpu.halted());
+
+        cpu.nmi(true);
+        cpu.irq(true);
+        assert_eq!(cpu.step(&mut memory), Err(ExecutionError::Jammed { pc: 0x0200 }));
+        assert_eq!(cpu.regPC, 0x0200);
+        assert_eq!(cpu.regSP, 0xFF);
+
+        cpu.irq(false);
+        cpu.reset(&mut memory);
+        assert!(!cpu.halted());
+        assert_eq!(cpu.regPC, 0x0201);
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0202);
+    }
+}
@@ -1,7 +1,6 @@
-use crate::cpu::memory::Memory; 
-
-use crate::cpu::cpu::CPU;
+use crate::cpu::bus::Bus; 
 
+use super::addressing::AddressingMode;
 use super::processor_status::ProcessorStatus;
 use super::MOS6502;
 
@@ -18,158 +17,139 @@ fn on_incdec_set_status(proc_status: &mut ProcessorStatus, value: u8) {
     }
 }
 
+impl MOS6502 {
+    // Increment in memory
+    pub(super) fn inc(&mut self, value: u8) -> u8 {
+        let result: u8 = value.wrapping_add(1);
+        on_incdec_set_status(&mut self.proc_status, result);
+        result
+    }
 
+    pub fn inc_zp<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPage, memory, MOS6502::inc)
+    }
 
-macro_rules! arit_im {
-    ($self:ident, $memory:ident, $call:tt) => {{
-        let byte: u8 = $self.fetch($memory);
-        $self.$call(byte);
-        1
-    }};
-}
-
-macro_rules! arit_zp {
-    ($self:ident, $memory:ident, $call:ident) => {{
-        let zero_page_address: u16 = $self.fetch($memory) as u16;
-        let value = $self.read(zero_page_address, $memory);
-        $self.$call(value);
-        2
-    }};
-}
+    pub fn inc_zpx<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPageX, memory, MOS6502::inc)
+    }
 
-macro_rules! arit_zpx {
-    ($self:ident, $memory:ident, $call:ident) => {{
-        let base_address: u8 = $self.fetch($memory);
-        let effective_address: u8 = base_address.wrapping_add($self.regX);
-        let value: u8 = $memory[effective_address as usize];
-        $self.$call(value);
-        3
-    }};
-}
+    pub fn inc_abs<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.modify_operand(AddressingMode::Absolute, memory, MOS6502::inc)
+    }
 
-macro_rules! arit_abs {
-    ($self:ident, $memory:ident, $call:ident) => {{
-        let low_byte: u8 = $self.fetch($memory);
-        let high_byte: u8 = $self.fetch($memory);
-        let address: u16 = ((high_byte as u16) << 8) | (low_byte as u16);
-        let value: u8 = $self.read(address, $memory);
-        $self.$call(value);
-        3
-    }};
-}
+    pub fn inc_absx<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteX, memory, MOS6502::inc)
+    }
 
-macro_rules! arit_absx {
-    ($self:ident, $memory:ident, $call:ident) => {{
-        let low_byte: u8 = $self.fetch($memory);
-        let high_byte: u8 = $self.fetch($memory);
-        let base_address: u16 = ((high_byte as u16) << 8) | (low_byte as u16);
-        let effective_address: u16 = base_address.wrapping_add($self.regX as u16);
-        let value: u8 = $self.read(effective_address, $memory);
-        $self.$call(value);
-
-        if (base_address & 0xFF00) != (effective_address & 0xFF00) {
-            4
-        } else {
-            3
-        }
-    }};
-}
+    // Increment registers
+    pub fn inx(&mut self) -> u32 {
+        self.regX = self.regX.wrapping_add(1);
+        on_incdec_set_status(&mut self.proc_status, self.regX);
+        0
+    }
 
-macro_rules! arit_absy {
-    ($self:ident, $memory:ident, $call:ident) => {{
-        let low_byte: u8 = $self.fetch($memory);
-        let high_byte: u8 = $self.fetch($memory);
-        let base_address: u16 = ((high_byte as u16) << 8) | (low_byte as u16);
-        let effective_address: u16 = base_address.wrapping_add($self.regY as u16);
-        let value: u8 = $self.read(effective_address, $memory);
-        $self.$call(value);
-
-        if (base_address & 0xFF00) != (effective_address & 0xFF00) {
-            4
-        } else {
-            3
-        }
-    }};
-}
+    pub fn iny(&mut self) -> u32 {
+        self.regY = self.regY.wrapping_add(1);
+        on_incdec_set_status(&mut self.proc_status, self.regY);
+        0
+    }
 
-macro_rules! arit_indx {
-    ($self:ident, $memory:ident, $call:ident) => {{
-        let base_address: u8 = $self.fetch($memory);
-        let indirect_address: u8 = base_address.wrapping_add($self.regX);
-        let low_byte: u8 = $memory[indirect_address as usize];
-        let high_byte: u8 = $memory[indirect_address.wrapping_add(1) as usize];
-        let final_address: u16 = ((high_byte as u16) << 8) | (low_byte as u16);
-        let value: u8 = $self.read(final_address, $memory);
-        $self.$call(value);
-        5
-    }};
-}
+    // Decrement in memory
+    pub(super) fn dec(&mut self, value: u8) -> u8 {
+        let result: u8 = value.wrapping_sub(1);
+        on_incdec_set_status(&mut self.proc_status, result);
+        result
+    }
 
-macro_rules! arit_indy {
-    ($self:ident, $memory:ident, $call:ident) => {{
-        let base_address: u8 = $self.fetch($memory);
-        let low_byte: u8 = $memory[base_address as usize];
-        let high_byte: u8 = $memory[base_address.wrapping_add(1) as usize];
-        let base_address: u16 = ((high_byte as u16) << 8) | (low_byte as u16);
-        let effective_address: u16 = base_address.wrapping_add($self.regY as u16);
-        let value: u8 = $self.read(effective_address, $memory);
-        $self.$call(value);
-
-        if (base_address & 0xFF00) != (effective_address & 0xFF00) {
-            5
-        } else {
-            4
-        }
-    }};
-}
+    pub fn dec_zp<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPage, memory, MOS6502::dec)
+    }
 
-impl MOS6502 {
-    // Increment in memory
-    pub fn inc_zp(&mut self, memory : &mut Memory) -> u8 {
-        let zero_page_address: u16 = self.fetch(memory) as u16;
-        let value = self.read(zero_page_address, memory);
-        memory[zero_page_address] = value+1;
-        on_incdec_set_status(self.proc_status, value+1);
-        4
+    pub fn dec_zpx<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPageX, memory, MOS6502::dec)
     }
 
-    pub fn inc_zpx(&mut self, memory : &mut Memory) -> u8 {
-        let base_address: u8 = self.fetch(memory);
-        let effective_address: u8 = base_address.wrapping_add(self.regX);
-        let value = self.read(zero_page_address, memory);
-        memory[zero_page_address] = value+1;
-        on_incdec_set_status(self.proc_status, value+1);
-        5
+    pub fn dec_abs<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.modify_operand(AddressingMode::Absolute, memory, MOS6502::dec)
     }
 
-    ...
-    
-    // Increment registes
-    pub fn inx(&mut self) -> u8 {
-        self.regX += 1;
-        on_incdec_set_status(self.proc_status, self.regX);
-        1
+    pub fn dec_absx<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteX, memory, MOS6502::dec)
     }
 
-    pub fn iny(&mut self) -> u8 {
-        self.regy += 1;
-        on_incdec_set_status(self.proc_status, self.regY);
-        1
+    // Decrement registers
+    pub fn dex(&mut self) -> u32 {
+        self.regX = self.regX.wrapping_sub(1);
+        on_incdec_set_status(&mut self.proc_status, self.regX);
+        0
     }
 
-    // Decrement in memory
+    pub fn dey(&mut self) -> u32 {
+        self.regY = self.regY.wrapping_sub(1);
+        on_incdec_set_status(&mut self.proc_status, self.regY);
+        0
+    }
+}
 
-    // Decrement registes
-    pub fn dex(&mut self) -> u8 {
-        self.regX -= 1;
-        on_incdec_set_status(self.proc_status, self.regX);
-        1
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+
+    use super::super::setup;
+
+    #[test]
+    fn memory_increment_and_decrement() {
+        // INC $10; DEC $10,X; INC $0300; DEC $0300,X
+        let (mut cpu, mut memory) = setup(0x0200, &[0xE6, 0x10, 0xD6, 0x10, 0xEE, 0x00, 0x03, 0xDE, 0x00, 0x03]);
+        cpu.regX = 0x01;
+        memory[0x0010] = 0x7F;
+        memory[0x0011] = 0x01;
+        memory[0x0300] = 0xFF;
+        memory[0x0301] = 0x00;
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(memory[0x0010], 0x80);
+        assert!(cpu.proc_status.negative());
+        cpu.execute(6, &mut memory).unwrap();
+        assert_eq!(memory[0x0011], 0x00);
+        assert!(cpu.proc_status.zero());
+        cpu.execute(6, &mut memory).unwrap();
+        assert_eq!(memory[0x0300], 0x00);
+        assert!(cpu.proc_status.zero());
+        cpu.execute(7, &mut memory).unwrap();
+        assert_eq!(memory[0x0301], 0xFF);
+        assert!(cpu.proc_status.negative());
+        assert!(!cpu.proc_status.zero());
+        assert_eq!(cpu.regPC, 0x020A);
     }
 
-    pub fn dey(&mut self) -> u8 {
-        self.regy -= 1;
-        on_incdec_set_status(self.proc_status, self.regY);
-        1
+    #[test]
+    fn memory_increment_writes_twice() {
+        // INC $10
+        let (mut cpu, mut memory) = setup(0x0200, &[0xE6, 0x10]);
+        memory[0x0010] = 0x41;
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(memory.writes(), vec![(0x0010, 0x41), (0x0010, 0x42)]);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn register_increment_and_decrement_wrap() {
+        // INX; INY; DEX; DEY
+        let (mut cpu, mut memory) = setup(0x0200, &[0xE8, 0xC8, 0xCA, 0x88]);
+        cpu.regX = 0xFF;
+        cpu.regY = 0x7F;
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regX, 0x00);
+        assert!(cpu.proc_status.zero());
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regY, 0x80);
+        assert!(cpu.proc_status.negative());
+        assert!(!cpu.proc_status.zero());
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regX, 0xFF);
+        assert!(cpu.proc_status.negative());
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regY, 0x7F);
+        assert!(!cpu.proc_status.negative());
+        assert_eq!(cpu.regPC, 0x0204);
+    }
+}
@@ -0,0 +1,228 @@
+use crate::cpu::bus::Bus; 
+
+use crate::cpu::cpu::CPU;
+
+use super::MOS6502;
+
+pub(super) const NMI_VECTOR: u16 = 0xFFFA;
+const RESET_VECTOR: u16 = 0xFFFC;
+pub(super) const IRQ_VECTOR: u16 = 0xFFFE;
+
+impl MOS6502 {
+    fn interrupt<B: Bus>(&mut self, memory: &mut B, vector: u16, break_command: bool) {
+        self.push((self.regPC >> 8) as u8, memory);
+        self.push(self.regPC as u8, memory);
+        self.push(self.proc_status.stack_value(break_command), memory);
+        self.proc_status.set_interrupt_disable();
+        if self.quirks.clear_decimal_on_interrupt {
+            self.proc_status.clear_decimal_mode();
+        }
+
+        let low_byte: u8 = self.read(vector, memory);
+        let high_byte: u8 = self.read(vector.wrapping_add(1), memory);
+        self.regPC = ((high_byte as u16) << 8) | (low_byte as u16);
+    }
+
+    // Reset runs the interrupt sequence with the bus held in read mode:
+    // SP is decremented three times but nothing is written to the stack
+    pub(super) fn reset_sequence<B: Bus>(&mut self, memory: &mut B) {
+        self.regSP = self.regSP.wrapping_sub(3);
+        self.proc_status.set_interrupt_disable();
+        self.nmi_pending = false;
+        self.jammed = false;
+        self.stopped = false;
+        self.waiting = false;
+        self.cycle_state = None;
+        self.interrupt_poll = false;
+        self.io_port.reset(self.total_cycles);
+        if self.quirks.clear_decimal_on_interrupt {
+            self.proc_status.clear_decimal_mode();
+        }
+
+        let low_byte: u8 = self.read(RESET_VECTOR, memory);
+        let high_byte: u8 = self.read(RESET_VECTOR.wrapping_add(1), memory);
+        self.regPC = ((high_byte as u16) << 8) | (low_byte as u16);
+    }
+
+    // Services a pending interrupt before the next opcode fetch, NMI takes priority over IRQ.
+    // Returns the cycles spent, 0 if no interrupt was taken
+    pub(super) fn poll_interrupts<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        if self.nmi_pending {
+            self.nmi_pending = false;
+            self.interrupt(memory, NMI_VECTOR, false);
+            7
+        } else if self.irq_line && !self.proc_status.interrupt_disable() {
+            self.interrupt(memory, IRQ_VECTOR, false);
+            7
+        } else {
+            0
+        }
+    }
+
+    // Break, the byte following the opcode is padding and is skipped by the return address
+    pub fn brk<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.fetch(memory);
+        self.interrupt(memory, IRQ_VECTOR, true);
+        0
+    }
+
+    // Return from interrupt
+    pub fn rti<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        let status: u8 = self.pull(memory);
+        self.proc_status.set_from_stack(status);
+        let low_byte: u8 = self.pull(memory);
+        let high_byte: u8 = self.pull(memory);
+        self.regPC = ((high_byte as u16) << 8) | (low_byte as u16);
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+    use crate::cpu::bus::RecordingBus;
+    use crate::cpu::memory::Memory;
+
+    use super::super::{setup, MOS6502};
+
+    // LDA #$00 in the main program, RTI in both handlers
+    fn boot() -> (MOS6502, RecordingBus) {
+        let (cpu, mut memory) = setup(0x0200, &[0xA9, 0x00, 0xA9, 0x00, 0xA9, 0x00]);
+        memory[0xFFFA] = 0x00;
+        memory[0xFFFB] = 0x90;
+        memory[0xFFFE] = 0x00;
+        memory[0xFFFF] = 0x80;
+        memory[0x8000] = 0x40;
+        memory[0x9000] = 0x40;
+        (cpu, memory)
+    }
+
+    #[test]
+    fn irq_pushes_pc_and_status_without_b() {
+        let (mut cpu, mut memory) = boot();
+        cpu.proc_status.set_carry();
+        cpu.irq(true);
+        cpu.execute(7, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x8000);
+        assert_eq!(cpu.regSP, 0xFC);
+        assert_eq!(memory[0x01FF], 0x02);
+        assert_eq!(memory[0x01FE], 0x00);
+        assert_eq!(memory[0x01FD], 0x21);
+        assert!(cpu.proc_status.interrupt_disable());
+    }
+
+    #[test]
+    fn irq_is_masked_by_the_i_flag() {
+        let (mut cpu, mut memory) = boot();
+        cpu.proc_status.set_interrupt_disable();
+        cpu.irq(true);
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0202);
+        assert_eq!(cpu.regSP, 0xFF);
+    }
+
+    #[test]
+    fn irq_is_level_triggered() {
+        let (mut cpu, mut memory) = boot();
+        cpu.irq(true);
+        // RTI restores I clear, so the held line interrupts again straight away
+        cpu.execute(7 + 6 + 7, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x8000);
+        assert_eq!(cpu.regSP, 0xFC);
+
+        cpu.irq(false);
+        cpu.execute(6 + 2, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0202);
+    }
+
+    #[test]
+    fn nmi_is_edge_triggered() {
+        let (mut cpu, mut memory) = boot();
+        cpu.proc_status.set_interrupt_disable();
+        cpu.nmi(true);
+        cpu.execute(7, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x9000);
+
+        // Still held: returning does not raise a second NMI
+        cpu.execute(6 + 2, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0202);
+
+        cpu.nmi(true);
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0204);
+
+        cpu.nmi(false);
+        cpu.nmi(true);
+        cpu.execute(7, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x9000);
+    }
+
+    #[test]
+    fn nmi_wins_over_irq() {
+        let (mut cpu, mut memory) = boot();
+        cpu.irq(true);
+        cpu.nmi(true);
+        cpu.execute(7, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x9000);
+    }
+
+    #[test]
+    fn brk_pushes_status_with_b_and_skips_padding() {
+        let (mut cpu, mut memory) = boot();
+        memory[0x0200] = 0x00;
+        cpu.execute(7, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x8000);
+        assert_eq!(memory[0x01FF], 0x02);
+        assert_eq!(memory[0x01FE], 0x02);
+        assert_eq!(memory[0x01FD], 0x30);
+        assert!(cpu.proc_status.interrupt_disable());
+    }
+
+    #[test]
+    fn rti_restores_status_and_pc() {
+        let (mut cpu, mut memory) = boot();
+        memory[0x0200] = 0x00;
+        cpu.execute(7 + 6, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0202);
+        assert_eq!(cpu.regSP, 0xFF);
+        assert!(!cpu.proc_status.interrupt_disable());
+        // B and bit 5 from the pushed value are not latched
+        assert_eq!(u8::from(cpu.proc_status) & 0x30, 0x00);
+    }
+
+    #[test]
+    fn power_on_state() {
+        let cpu: MOS6502 = MOS6502::new();
+        assert_eq!(cpu.regSP, 0x00);
+        assert_eq!((cpu.regA, cpu.regX, cpu.regY), (0, 0, 0));
+        assert_eq!(u8::from(cpu.proc_status), 0x04);
+    }
+
+    #[test]
+    fn reset_loads_pc_from_the_vector() {
+        let mut cpu: MOS6502 = MOS6502::new();
+        let mut memory: RecordingBus = RecordingBus::new(Memory::new());
+        memory[0xFFFC] = 0x34;
+        memory[0xFFFD] = 0x12;
+        cpu.reset(&mut memory);
+        assert_eq!(cpu.regPC, 0x1234);
+        // Three phantom pushes from the power-on SP of $00
+        assert_eq!(cpu.regSP, 0xFD);
+        assert!(cpu.proc_status.interrupt_disable());
+        assert!(memory.writes().is_empty());
+    }
+
+    #[test]
+    fn reset_sets_i_and_drops_a_pending_nmi() {
+        let (mut cpu, mut memory) = boot();
+        memory[0xFFFC] = 0x00;
+        memory[0xFFFD] = 0x02;
+        cpu.regSP = 0xFF;
+        cpu.nmi(true);
+        cpu.reset(&mut memory);
+        assert_eq!(cpu.regSP, 0xFC);
+        assert!(cpu.proc_status.interrupt_disable());
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0202);
+    }
+}
@@ -0,0 +1,181 @@
+// On-chip I/O port of the 6510: the data direction register at $0000 and the
+// data register at $0001. Only P0-P5 are bonded out, bits 6 and 7 exist as
+// register bits with nothing connected to them
+pub(super) const PORT_PINS: u8 = 0x3F;
+
+// An input that nothing drives keeps the level it last had on the pin
+// capacitance for roughly this many cycles, then reads as 0
+const FADE_CYCLES: u64 = 350_000;
+
+#[derive(Copy, Clone)]
+pub(super) struct IoPort {
+    // 1 = output
+    direction: u8,
+    data: u8,
+    // Levels the host drives on input pins, for the pins set in external_driven
+    external_levels: u8,
+    external_driven: u8,
+    // Levels held by undriven inputs and the cycle at which each one has faded
+    floating: u8,
+    fade_at: [u64; 8],
+}
+
+impl IoPort {
+    pub fn new() -> Self {
+        IoPort {
+            direction : 0,
+            data : 0,
+            external_levels : 0,
+            external_driven : 0,
+            floating : 0,
+            fade_at : [0; 8],
+        }
+    }
+
+    // Reset makes every bit an input, outputs that were high start to fade
+    pub fn reset(&mut self, cycle: u64) {
+        self.write(0x0000, 0, cycle);
+        self.data = 0;
+    }
+
+    pub fn read(&self, address: u16, cycle: u64) -> u8 {
+        if address == 0x0000 {
+            self.direction
+        } else {
+            self.levels(cycle)
+        }
+    }
+
+    pub fn write(&mut self, address: u16, value: u8, cycle: u64) {
+        if address == 0x0000 {
+            let released: u8 = self.direction & !value;
+            for bit in 0..8 {
+                let mask: u8 = 1 << bit;
+                if released & mask != 0 {
+                    self.floating = (self.floating & !mask) | (self.data & mask);
+                    self.fade_at[bit] = cycle + FADE_CYCLES;
+                }
+            }
+            self.direction = value;
+        } else {
+            self.data = value;
+        }
+    }
+
+    pub fn set_external(&mut self, levels: u8, driven: u8) {
+        self.external_levels = levels;
+        self.external_driven = driven & PORT_PINS;
+    }
+
+    pub fn direction(&self) -> u8 {
+        self.direction
+    }
+
+    // Level of every bit: outputs from the data register, inputs from the host
+    // when it drives them, otherwise whatever charge is left on the pin
+    pub fn levels(&self, cycle: u64) -> u8 {
+        let inputs: u8 = !self.direction;
+        let driven: u8 = inputs & self.external_driven;
+        let mut value: u8 = (self.data & self.direction) | (self.external_levels & driven);
+        for bit in 0..8 {
+            let mask: u8 = 1 << bit;
+            if inputs & !driven & self.floating & mask != 0 && cycle < self.fade_at[bit] {
+                value |= mask;
+            }
+        }
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+    use crate::cpu::bus::RecordingBus;
+
+    use super::super::{setup, setup_variant, CoreMode, Variant, MOS6502};
+    use super::FADE_CYCLES;
+
+    fn port_setup(program: &[u8], mode: CoreMode) -> (MOS6502, RecordingBus) {
+        let (mut cpu, memory) = setup_variant(Variant::MOS6510, 0x0200, program);
+        cpu.set_core_mode(mode);
+        (cpu, memory)
+    }
+
+    #[test]
+    fn registers_are_mapped_at_zero_and_one() {
+        for mode in [CoreMode::Instruction, CoreMode::Cycle] {
+            // LDA #$2F; STA $00; LDA #$37; STA $01; LDA $01
+            let (mut cpu, mut memory) = port_setup(&[0xA9, 0x2F, 0x85, 0x00, 0xA9, 0x37, 0x85, 0x01, 0xA5, 0x01], mode);
+            cpu.run_instructions(4, &mut memory).unwrap();
+            assert_eq!(cpu.port_direction(), 0x2F);
+            assert_eq!(cpu.port_pins(), 0x27);
+
+            // Inputs read what the host drives on them
+            cpu.set_port_input(0x10, 0x10);
+            cpu.step(&mut memory).unwrap();
+            assert_eq!(cpu.regA, 0x37);
+            assert_eq!(cpu.port_pins(), 0x37);
+        }
+    }
+
+    #[test]
+    fn writes_also_reach_the_ram_underneath() {
+        // LDA #$2F; STA $00; LDA #$37; STA $01
+        let (mut cpu, mut memory) = port_setup(&[0xA9, 0x2F, 0x85, 0x00, 0xA9, 0x37, 0x85, 0x01], CoreMode::Instruction);
+        memory[0x0000] = 0xAA;
+        memory[0x0001] = 0xAA;
+        cpu.run_instructions(4, &mut memory).unwrap();
+        assert_eq!(memory[0x0000], 0x2F);
+        assert_eq!(memory[0x0001], 0x37);
+    }
+
+    #[test]
+    fn other_variants_have_no_port() {
+        // LDA #$2F; STA $00; LDA $00
+        let (mut cpu, mut memory) = setup(0x0200, &[0xA9, 0x2F, 0x85, 0x00, 0xA9, 0x00, 0xA5, 0x00]);
+        memory[0x0000] = 0xAA;
+        cpu.run_instructions(4, &mut memory).unwrap();
+        assert_eq!(cpu.regA, 0x2F);
+        assert_eq!(cpu.port_direction(), 0x00);
+    }
+
+    #[test]
+    fn released_outputs_fade_after_350000_cycles() {
+        for mode in [CoreMode::Instruction, CoreMode::Cycle] {
+            // LDA #$FF; STA $01; STA $00; LDA #$00; STA $00; LDA $01
+            let (mut cpu, mut memory) = port_setup(&[0xA9, 0xFF, 0x85, 0x01, 0x85, 0x00, 0xA9, 0x00, 0x85, 0x00, 0xA5, 0x01], mode);
+            cpu.run_instructions(4, &mut memory).unwrap();
+
+            // The release is written on the third cycle of STA $00
+            let fade_at: u64 = cpu.total_cycles + 2 + FADE_CYCLES;
+            cpu.step(&mut memory).unwrap();
+            assert_eq!(cpu.port_pins(), 0x3F);
+
+            cpu.total_cycles = fade_at - 1;
+            assert_eq!(cpu.port_pins(), 0x3F);
+            cpu.total_cycles = fade_at;
+            assert_eq!(cpu.port_pins(), 0x00);
+
+            // LDA $01 reads on its third cycle, not at the start of the instruction
+            cpu.total_cycles = fade_at - 3;
+            cpu.step(&mut memory).unwrap();
+            assert_eq!(cpu.regA, 0xFF);
+
+            cpu.regPC = 0x020A;
+            cpu.total_cycles = fade_at - 2;
+            cpu.step(&mut memory).unwrap();
+            assert_eq!(cpu.regA, 0x00);
+        }
+    }
+
+    #[test]
+    fn released_outputs_fade_while_running() {
+        // LDA #$FF; STA $01; STA $00; LDA #$00; STA $00; JMP $020A
+        let (mut cpu, mut memory) = port_setup(&[0xA9, 0xFF, 0x85, 0x01, 0x85, 0x00, 0xA9, 0x00, 0x85, 0x00, 0x4C, 0x0A, 0x02], CoreMode::Instruction);
+        cpu.run_instructions(5, &mut memory).unwrap();
+        cpu.execute(349_990, &mut memory).unwrap();
+        assert_eq!(cpu.port_pins(), 0x3F);
+        cpu.execute(20, &mut memory).unwrap();
+        assert_eq!(cpu.port_pins(), 0x00);
+    }
+}
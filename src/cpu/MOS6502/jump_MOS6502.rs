@@ -0,0 +1,97 @@
+use crate::cpu::bus::Bus; 
+
+use crate::cpu::cpu::CPU;
+
+use super::addressing::{AddressingMode, Operand};
+use super::MOS6502;
+
+impl MOS6502 {
+    // Jump
+    pub fn jmp_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        let low_byte: u8 = self.fetch(memory);
+        let high_byte: u8 = self.fetch(memory);
+        self.regPC = ((high_byte as u16) << 8) | (low_byte as u16);
+        0
+    }
+
+    // The NMOS 6502 does not carry into the high byte of the pointer:
+    // JMP ($xxFF) takes its high byte from $xx00 instead of the next page,
+    // unless the quirks say otherwise
+    pub fn jmp_ind<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        let operand: Operand = self.resolve(AddressingMode::Indirect, memory);
+        self.regPC = operand.address;
+        0
+    }
+
+    // Jump to subroutine, the pushed return address points to the last byte of the JSR
+    pub fn jsr<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        let low_byte: u8 = self.fetch(memory);
+        let high_byte: u8 = self.fetch(memory);
+        let return_address: u16 = self.regPC.wrapping_sub(1);
+        self.push((return_address >> 8) as u8, memory);
+        self.push(return_address as u8, memory);
+        self.regPC = ((high_byte as u16) << 8) | (low_byte as u16);
+        0
+    }
+
+    // Return from subroutine
+    pub fn rts<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        let low_byte: u8 = self.pull(memory);
+        let high_byte: u8 = self.pull(memory);
+        let return_address: u16 = ((high_byte as u16) << 8) | (low_byte as u16);
+        self.regPC = return_address.wrapping_add(1);
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+
+    use super::super::setup;
+
+    #[test]
+    fn jmp_absolute() {
+        let (mut cpu, mut memory) = setup(0x0200, &[0x4C, 0x34, 0x12]);
+        cpu.execute(3, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x1234);
+    }
+
+    #[test]
+    fn jmp_indirect() {
+        let (mut cpu, mut memory) = setup(0x0200, &[0x6C, 0x80, 0x03]);
+        memory[0x0380] = 0x78;
+        memory[0x0381] = 0x56;
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x5678);
+    }
+
+    #[test]
+    fn jmp_indirect_wraps_within_the_pointer_page() {
+        let (mut cpu, mut memory) = setup(0x0200, &[0x6C, 0xFF, 0x03]);
+        memory[0x03FF] = 0x78;
+        memory[0x0300] = 0x56;
+        memory[0x0400] = 0xAA;
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x5678);
+    }
+
+    #[test]
+    fn jsr_pushes_the_last_byte_of_the_instruction() {
+        let (mut cpu, mut memory) = setup(0x0200, &[0x20, 0x00, 0x30]);
+        cpu.execute(6, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x3000);
+        assert_eq!(cpu.regSP, 0xFD);
+        assert_eq!(memory[0x01FF], 0x02);
+        assert_eq!(memory[0x01FE], 0x02);
+    }
+
+    #[test]
+    fn rts_returns_after_the_jsr() {
+        let (mut cpu, mut memory) = setup(0x0200, &[0x20, 0x00, 0x30]);
+        memory[0x3000] = 0x60;
+        cpu.execute(12, &mut memory).unwrap();
+        assert_eq!(cpu.regPC, 0x0203);
+        assert_eq!(cpu.regSP, 0xFF);
+    }
+}
@@ -0,0 +1,653 @@
+use super::addressing::AddressingMode;
+
+// Static description of one opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opcode {
+    pub mnemonic: &'static str,
+    pub mode: AddressingMode,
+    // Instruction length in bytes, opcode included
+    pub bytes: u8,
+    // Cycles including the opcode fetch, without page-cross or branch penalties
+    pub cycles: u8,
+    // One more cycle when indexing crosses a page. Branches also take one more when taken
+    pub page_penalty: bool,
+    // One more cycle in decimal mode, 65C02 ADC and SBC
+    pub decimal_penalty: bool,
+    pub documented: bool,
+}
+
+const fn documented(mnemonic: &'static str, mode: AddressingMode, cycles: u8, page_penalty: bool) -> Opcode {
+    Opcode { mnemonic, mode, bytes : mode.bytes(), cycles, page_penalty, decimal_penalty : false, documented : true }
+}
+
+const fn undocumented(mnemonic: &'static str, mode: AddressingMode, cycles: u8, page_penalty: bool) -> Opcode {
+    Opcode { mnemonic, mode, bytes : mode.bytes(), cycles, page_penalty, decimal_penalty : false, documented : false }
+}
+
+const fn decimal(mnemonic: &'static str, mode: AddressingMode, cycles: u8, page_penalty: bool) -> Opcode {
+    Opcode { mnemonic, mode, bytes : mode.bytes(), cycles, page_penalty, decimal_penalty : true, documented : true }
+}
+
+// All 256 NMOS 6502 opcodes, indexed by opcode byte. JAM lists the cycles until the bus locks up
+pub static OPCODES: [Opcode; 256] = [
+    /* 0x00 */ documented("BRK", AddressingMode::Implied, 7, false),
+    /* 0x01 */ documented("ORA", AddressingMode::IndirectX, 6, false),
+    /* 0x02 */ undocumented("JAM", AddressingMode::Implied, 2, false),
+    /* 0x03 */ undocumented("SLO", AddressingMode::IndirectX, 8, false),
+    /* 0x04 */ undocumented("NOP", AddressingMode::ZeroPage, 3, false),
+    /* 0x05 */ documented("ORA", AddressingMode::ZeroPage, 3, false),
+    /* 0x06 */ documented("ASL", AddressingMode::ZeroPage, 5, false),
+    /* 0x07 */ undocumented("SLO", AddressingMode::ZeroPage, 5, false),
+    /* 0x08 */ documented("PHP", AddressingMode::Implied, 3, false),
+    /* 0x09 */ documented("ORA", AddressingMode::Immediate, 2, false),
+    /* 0x0A */ documented("ASL", AddressingMode::Accumulator, 2, false),
+    /* 0x0B */ undocumented("ANC", AddressingMode::Immediate, 2, false),
+    /* 0x0C */ undocumented("NOP", AddressingMode::Absolute, 4, false),
+    /* 0x0D */ documented("ORA", AddressingMode::Absolute, 4, false),
+    /* 0x0E */ documented("ASL", AddressingMode::Absolute, 6, false),
+    /* 0x0F */ undocumented("SLO", AddressingMode::Absolute, 6, false),
+    /* 0x10 */ documented("BPL", AddressingMode::Relative, 2, true),
+    /* 0x11 */ documented("ORA", AddressingMode::IndirectY, 5, true),
+    /* 0x12 */ undocumented("JAM", AddressingMode::Implied, 2, false),
+    /* 0x13 */ undocumented("SLO", AddressingMode::IndirectY, 8, false),
+    /* 0x14 */ undocumented("NOP", AddressingMode::ZeroPageX, 4, false),
+    /* 0x15 */ documented("ORA", AddressingMode::ZeroPageX, 4, false),
+    /* 0x16 */ documented("ASL", AddressingMode::ZeroPageX, 6, false),
+    /* 0x17 */ undocumented("SLO", AddressingMode::ZeroPageX, 6, false),
+    /* 0x18 */ documented("CLC", AddressingMode::Implied, 2, false),
+    /* 0x19 */ documented("ORA", AddressingMode::AbsoluteY, 4, true),
+    /* 0x1A */ undocumented("NOP", AddressingMode::Implied, 2, false),
+    /* 0x1B */ undocumented("SLO", AddressingMode::AbsoluteY, 7, false),
+    /* 0x1C */ undocumented("NOP", AddressingMode::AbsoluteX, 4, true),
+    /* 0x1D */ documented("ORA", AddressingMode::AbsoluteX, 4, true),
+    /* 0x1E */ documented("ASL", AddressingMode::AbsoluteX, 7, false),
+    /* 0x1F */ undocumented("SLO", AddressingMode::AbsoluteX, 7, false),
+    /* 0x20 */ documented("JSR", AddressingMode::Absolute, 6, false),
+    /* 0x21 */ documented("AND", AddressingMode::IndirectX, 6, false),
+    /* 0x22 */ undocumented("JAM", AddressingMode::Implied, 2, false),
+    /* 0x23 */ undocumented("RLA", AddressingMode::IndirectX, 8, false),
+    /* 0x24 */ documented("BIT", AddressingMode::ZeroPage, 3, false),
+    /* 0x25 */ documented("AND", AddressingMode::ZeroPage, 3, false),
+    /* 0x26 */ documented("ROL", AddressingMode::ZeroPage, 5, false),
+    /* 0x27 */ undocumented("RLA", AddressingMode::ZeroPage, 5, false),
+    /* 0x28 */ documented("PLP", AddressingMode::Implied, 4, false),
+    /* 0x29 */ documented("AND", AddressingMode::Immediate, 2, false),
+    /* 0x2A */ documented("ROL", AddressingMode::Accumulator, 2, false),
+    /* 0x2B */ undocumented("ANC", AddressingMode::Immediate, 2, false),
+    /* 0x2C */ documented("BIT", AddressingMode::Absolute, 4, false),
+    /* 0x2D */ documented("AND", AddressingMode::Absolute, 4, false),
+    /* 0x2E */ documented("ROL", AddressingMode::Absolute, 6, false),
+    /* 0x2F */ undocumented("RLA", AddressingMode::Absolute, 6, false),
+    /* 0x30 */ documented("BMI", AddressingMode::Relative, 2, true),
+    /* 0x31 */ documented("AND", AddressingMode::IndirectY, 5, true),
+    /* 0x32 */ undocumented("JAM", AddressingMode::Implied, 2, false),
+    /* 0x33 */ undocumented("RLA", AddressingMode::IndirectY, 8, false),
+    /* 0x34 */ undocumented("NOP", AddressingMode::ZeroPageX, 4, false),
+    /* 0x35 */ documented("AND", AddressingMode::ZeroPageX, 4, false),
+    /* 0x36 */ documented("ROL", AddressingMode::ZeroPageX, 6, false),
+    /* 0x37 */ undocumented("RLA", AddressingMode::ZeroPageX, 6, false),
+    /* 0x38 */ documented("SEC", AddressingMode::Implied, 2, false),
+    /* 0x39 */ documented("AND", AddressingMode::AbsoluteY, 4, true),
+    /* 0x3A */ undocumented("NOP", AddressingMode::Implied, 2, false),
+    /* 0x3B */ undocumented("RLA", AddressingMode::AbsoluteY, 7, false),
+    /* 0x3C */ undocumented("NOP", AddressingMode::AbsoluteX, 4, true),
+    /* 0x3D */ documented("AND", AddressingMode::AbsoluteX, 4, true),
+    /* 0x3E */ documented("ROL", AddressingMode::AbsoluteX, 7, false),
+    /* 0x3F */ undocumented("RLA", AddressingMode::AbsoluteX, 7, false),
+    /* 0x40 */ documented("RTI", AddressingMode::Implied, 6, false),
+    /* 0x41 */ documented("EOR", AddressingMode::IndirectX, 6, false),
+    /* 0x42 */ undocumented("JAM", AddressingMode::Implied, 2, false),
+    /* 0x43 */ undocumented("SRE", AddressingMode::IndirectX, 8, false),
+    /* 0x44 */ undocumented("NOP", AddressingMode::ZeroPage, 3, false),
+    /* 0x45 */ documented("EOR", AddressingMode::ZeroPage, 3, false),
+    /* 0x46 */ documented("LSR", AddressingMode::ZeroPage, 5, false),
+    /* 0x47 */ undocumented("SRE", AddressingMode::ZeroPage, 5, false),
+    /* 0x48 */ documented("PHA", AddressingMode::Implied, 3, false),
+    /* 0x49 */ documented("EOR", AddressingMode::Immediate, 2, false),
+    /* 0x4A */ documented("LSR", AddressingMode::Accumulator, 2, false),
+    /* 0x4B */ undocumented("ALR", AddressingMode::Immediate, 2, false),
+    /* 0x4C */ documented("JMP", AddressingMode::Absolute, 3, false),
+    /* 0x4D */ documented("EOR", AddressingMode::Absolute, 4, false),
+    /* 0x4E */ documented("LSR", AddressingMode::Absolute, 6, false),
+    /* 0x4F */ undocumented("SRE", AddressingMode::Absolute, 6, false),
+    /* 0x50 */ documented("BVC", AddressingMode::Relative, 2, true),
+    /* 0x51 */ documented("EOR", AddressingMode::IndirectY, 5, true),
+    /* 0x52 */ undocumented("JAM", AddressingMode::Implied, 2, false),
+    /* 0x53 */ undocumented("SRE", AddressingMode::IndirectY, 8, false),
+    /* 0x54 */ undocumented("NOP", AddressingMode::ZeroPageX, 4, false),
+    /* 0x55 */ documented("EOR", AddressingMode::ZeroPageX, 4, false),
+    /* 0x56 */ documented("LSR", AddressingMode::ZeroPageX, 6, false),
+    /* 0x57 */ undocumented("SRE", AddressingMode::ZeroPageX, 6, false),
+    /* 0x58 */ documented("CLI", AddressingMode::Implied, 2, false),
+    /* 0x59 */ documented("EOR", AddressingMode::AbsoluteY, 4, true),
+    /* 0x5A */ undocumented("NOP", AddressingMode::Implied, 2, false),
+    /* 0x5B */ undocumented("SRE", AddressingMode::AbsoluteY, 7, false),
+    /* 0x5C */ undocumented("NOP", AddressingMode::AbsoluteX, 4, true),
+    /* 0x5D */ documented("EOR", AddressingMode::AbsoluteX, 4, true),
+    /* 0x5E */ documented("LSR", AddressingMode::AbsoluteX, 7, false),
+    /* 0x5F */ undocumented("SRE", AddressingMode::AbsoluteX, 7, false),
+    /* 0x60 */ documented("RTS", AddressingMode::Implied, 6, false),
+    /* 0x61 */ documented("ADC", AddressingMode::IndirectX, 6, false),
+    /* 0x62 */ undocumented("JAM", AddressingMode::Implied, 2, false),
+    /* 0x63 */ undocumented("RRA", AddressingMode::IndirectX, 8, false),
+    /* 0x64 */ undocumented("NOP", AddressingMode::ZeroPage, 3, false),
+    /* 0x65 */ documented("ADC", AddressingMode::ZeroPage, 3, false),
+    /* 0x66 */ documented("ROR", AddressingMode::ZeroPage, 5, false),
+    /* 0x67 */ undocumented("RRA", AddressingMode::ZeroPage, 5, false),
+    /* 0x68 */ documented("PLA", AddressingMode::Implied, 4, false),
+    /* 0x69 */ documented("ADC", AddressingMode::Immediate, 2, false),
+    /* 0x6A */ documented("ROR", AddressingMode::Accumulator, 2, false),
+    /* 0x6B */ undocumented("ARR", AddressingMode::Immediate, 2, false),
+    /* 0x6C */ documented("JMP", AddressingMode::Indirect, 5, false),
+    /* 0x6D */ documented("ADC", AddressingMode::Absolute, 4, false),
+    /* 0x6E */ documented("ROR", AddressingMode::Absolute, 6, false),
+    /* 0x6F */ undocumented("RRA", AddressingMode::Absolute, 6, false),
+    /* 0x70 */ documented("BVS", AddressingMode::Relative, 2, true),
+    /* 0x71 */ documented("ADC", AddressingMode::IndirectY, 5, true),
+    /* 0x72 */ undocumented("JAM", AddressingMode::Implied, 2, false),
+    /* 0x73 */ undocumented("RRA", AddressingMode::IndirectY, 8, false),
+    /* 0x74 */ undocumented("NOP", AddressingMode::ZeroPageX, 4, false),
+    /* 0x75 */ documented("ADC", AddressingMode::ZeroPageX, 4, false),
+    /* 0x76 */ documented("ROR", AddressingMode::ZeroPageX, 6, false),
+    /* 0x77 */ undocumented("RRA", AddressingMode::ZeroPageX, 6, false),
+    /* 0x78 */ documented("SEI", AddressingMode::Implied, 2, false),
+    /* 0x79 */ documented("ADC", AddressingMode::AbsoluteY, 4, true),
+    /* 0x7A */ undocumented("NOP", AddressingMode::Implied, 2, false),
+    /* 0x7B */ undocumented("RRA", AddressingMode::AbsoluteY, 7, false),
+    /* 0x7C */ undocumented("NOP", AddressingMode::AbsoluteX, 4, true),
+    /* 0x7D */ documented("ADC", AddressingMode::AbsoluteX, 4, true),
+    /* 0x7E */ documented("ROR", AddressingMode::AbsoluteX, 7, false),
+    /* 0x7F */ undocumented("RRA", AddressingMode::AbsoluteX, 7, false),
+    /* 0x80 */ undocumented("NOP", AddressingMode::Immediate, 2, false),
+    /* 0x81 */ documented("STA", AddressingMode::IndirectX, 6, false),
+    /* 0x82 */ undocumented("NOP", AddressingMode::Immediate, 2, false),
+    /* 0x83 */ undocumented("SAX", AddressingMode::IndirectX, 6, false),
+    /* 0x84 */ documented("STY", AddressingMode::ZeroPage, 3, false),
+    /* 0x85 */ documented("STA", AddressingMode::ZeroPage, 3, false),
+    /* 0x86 */ documented("STX", AddressingMode::ZeroPage, 3, false),
+    /* 0x87 */ undocumented("SAX", AddressingMode::ZeroPage, 3, false),
+    /* 0x88 */ documented("DEY", AddressingMode::Implied, 2, false),
+    /* 0x89 */ undocumented("NOP", AddressingMode::Immediate, 2, false),
+    /* 0x8A */ documented("TXA", AddressingMode::Implied, 2, false),
+    /* 0x8B */ undocumented("XAA", AddressingMode::Immediate, 2, false),
+    /* 0x8C */ documented("STY", AddressingMode::Absolute, 4, false),
+    /* 0x8D */ documented("STA", AddressingMode::Absolute, 4, false),
+    /* 0x8E */ documented("STX", AddressingMode::Absolute, 4, false),
+    /* 0x8F */ undocumented("SAX", AddressingMode::Absolute, 4, false),
+    /* 0x90 */ documented("BCC", AddressingMode::Relative, 2, true),
+    /* 0x91 */ documented("STA", AddressingMode::IndirectY, 6, false),
+    /* 0x92 */ undocumented("JAM", AddressingMode::Implied, 2, false),
+    /* 0x93 */ undocumented("AHX", AddressingMode::IndirectY, 6, false),
+    /* 0x94 */ documented("STY", AddressingMode::ZeroPageX, 4, false),
+    /* 0x95 */ documented("STA", AddressingMode::ZeroPageX, 4, false),
+    /* 0x96 */ documented("STX", AddressingMode::ZeroPageY, 4, false),
+    /* 0x97 */ undocumented("SAX", AddressingMode::ZeroPageY, 4, false),
+    /* 0x98 */ documented("TYA", AddressingMode::Implied, 2, false),
+    /* 0x99 */ documented("STA", AddressingMode::AbsoluteY, 5, false),
+    /* 0x9A */ documented("TXS", AddressingMode::Implied, 2, false),
+    /* 0x9B */ undocumented("TAS", AddressingMode::AbsoluteY, 5, false),
+    /* 0x9C */ undocumented("SHY", AddressingMode::AbsoluteX, 5, false),
+    /* 0x9D */ documented("STA", AddressingMode::AbsoluteX, 5, false),
+    /* 0x9E */ undocumented("SHX", AddressingMode::AbsoluteY, 5, false),
+    /* 0x9F */ undocumented("AHX", AddressingMode::AbsoluteY, 5, false),
+    /* 0xA0 */ documented("LDY", AddressingMode::Immediate, 2, false),
+    /* 0xA1 */ documented("LDA", AddressingMode::IndirectX, 6, false),
+    /* 0xA2 */ documented("LDX", AddressingMode::Immediate, 2, false),
+    /* 0xA3 */ undocumented("LAX", AddressingMode::IndirectX, 6, false),
+    /* 0xA4 */ documented("LDY", AddressingMode::ZeroPage, 3, false),
+    /* 0xA5 */ documented("LDA", AddressingMode::ZeroPage, 3, false),
+    /* 0xA6 */ documented("LDX", AddressingMode::ZeroPage, 3, false),
+    /* 0xA7 */ undocumented("LAX", AddressingMode::ZeroPage, 3, false),
+    /* 0xA8 */ documented("TAY", AddressingMode::Implied, 2, false),
+    /* 0xA9 */ documented("LDA", AddressingMode::Immediate, 2, false),
+    /* 0xAA */ documented("TAX", AddressingMode::Implied, 2, false),
+    /* 0xAB */ undocumented("LAX", AddressingMode::Immediate, 2, false),
+    /* 0xAC */ documented("LDY", AddressingMode::Absolute, 4, false),
+    /* 0xAD */ documented("LDA", AddressingMode::Absolute, 4, false),
+    /* 0xAE */ documented("LDX", AddressingMode::Absolute, 4, false),
+    /* 0xAF */ undocumented("LAX", AddressingMode::Absolute, 4, false),
+    /* 0xB0 */ documented("BCS", AddressingMode::Relative, 2, true),
+    /* 0xB1 */ documented("LDA", AddressingMode::IndirectY, 5, true),
+    /* 0xB2 */ undocumented("JAM", AddressingMode::Implied, 2, false),
+    /* 0xB3 */ undocumented("LAX", AddressingMode::IndirectY, 5, true),
+    /* 0xB4 */ documented("LDY", AddressingMode::ZeroPageX, 4, false),
+    /* 0xB5 */ documented("LDA", AddressingMode::ZeroPageX, 4, false),
+    /* 0xB6 */ documented("LDX", AddressingMode::ZeroPageY, 4, false),
+    /* 0xB7 */ undocumented("LAX", AddressingMode::ZeroPageY, 4, false),
+    /* 0xB8 */ documented("CLV", AddressingMode::Implied, 2, false),
+    /* 0xB9 */ documented("LDA", AddressingMode::AbsoluteY, 4, true),
+    /* 0xBA */ documented("TSX", AddressingMode::Implied, 2, false),
+    /* 0xBB */ undocumented("LAS", AddressingMode::AbsoluteY, 4, true),
+    /* 0xBC */ documented("LDY", AddressingMode::AbsoluteX, 4, true),
+    /* 0xBD */ documented("LDA", AddressingMode::AbsoluteX, 4, true),
+    /* 0xBE */ documented("LDX", AddressingMode::AbsoluteY, 4, true),
+    /* 0xBF */ undocumented("LAX", AddressingMode::AbsoluteY, 4, true),
+    /* 0xC0 */ documented("CPY", AddressingMode::Immediate, 2, false),
+    /* 0xC1 */ documented("CMP", AddressingMode::IndirectX, 6, false),
+    /* 0xC2 */ undocumented("NOP", AddressingMode::Immediate, 2, false),
+    /* 0xC3 */ undocumented("DCP", AddressingMode::IndirectX, 8, false),
+    /* 0xC4 */ documented("CPY", AddressingMode::ZeroPage, 3, false),
+    /* 0xC5 */ documented("CMP", AddressingMode::ZeroPage, 3, false),
+    /* 0xC6 */ documented("DEC", AddressingMode::ZeroPage, 5, false),
+    /* 0xC7 */ undocumented("DCP", AddressingMode::ZeroPage, 5, false),
+    /* 0xC8 */ documented("INY", AddressingMode::Implied, 2, false),
+    /* 0xC9 */ documented("CMP", AddressingMode::Immediate, 2, false),
+    /* 0xCA */ documented("DEX", AddressingMode::Implied, 2, false),
+    /* 0xCB */ undocumented("SBX", AddressingMode::Immediate, 2, false),
+    /* 0xCC */ documented("CPY", AddressingMode::Absolute, 4, false),
+    /* 0xCD */ documented("CMP", AddressingMode::Absolute, 4, false),
+    /* 0xCE */ documented("DEC", AddressingMode::Absolute, 6, false),
+    /* 0xCF */ undocumented("DCP", AddressingMode::Absolute, 6, false),
+    /* 0xD0 */ documented("BNE", AddressingMode::Relative, 2, true),
+    /* 0xD1 */ documented("CMP", AddressingMode::IndirectY, 5, true),
+    /* 0xD2 */ undocumented("JAM", AddressingMode::Implied, 2, false),
+    /* 0xD3 */ undocumented("DCP", AddressingMode::IndirectY, 8, false),
+    /* 0xD4 */ undocumented("NOP", AddressingMode::ZeroPageX, 4, false),
+    /* 0xD5 */ documented("CMP", AddressingMode::ZeroPageX, 4, false),
+    /* 0xD6 */ documented("DEC", AddressingMode::ZeroPageX, 6, false),
+    /* 0xD7 */ undocumented("DCP", AddressingMode::ZeroPageX, 6, false),
+    /* 0xD8 */ documented("CLD", AddressingMode::Implied, 2, false),
+    /* 0xD9 */ documented("CMP", AddressingMode::AbsoluteY, 4, true),
+    /* 0xDA */ undocumented("NOP", AddressingMode::Implied, 2, false),
+    /* 0xDB */ undocumented("DCP", AddressingMode::AbsoluteY, 7, false),
+    /* 0xDC */ undocumented("NOP", AddressingMode::AbsoluteX, 4, true),
+    /* 0xDD */ documented("CMP", AddressingMode::AbsoluteX, 4, true),
+    /* 0xDE */ documented("DEC", AddressingMode::AbsoluteX, 7, false),
+    /* 0xDF */ undocumented("DCP", AddressingMode::AbsoluteX, 7, false),
+    /* 0xE0 */ documented("CPX", AddressingMode::Immediate, 2, false),
+    /* 0xE1 */ documented("SBC", AddressingMode::IndirectX, 6, false),
+    /* 0xE2 */ undocumented("NOP", AddressingMode::Immediate, 2, false),
+    /* 0xE3 */ undocumented("ISC", AddressingMode::IndirectX, 8, false),
+    /* 0xE4 */ documented("CPX", AddressingMode::ZeroPage, 3, false),
+    /* 0xE5 */ documented("SBC", AddressingMode::ZeroPage, 3, false),
+    /* 0xE6 */ documented("INC", AddressingMode::ZeroPage, 5, false),
+    /* 0xE7 */ undocumented("ISC", AddressingMode::ZeroPage, 5, false),
+    /* 0xE8 */ documented("INX", AddressingMode::Implied, 2, false),
+    /* 0xE9 */ documented("SBC", AddressingMode::Immediate, 2, false),
+    /* 0xEA */ documented("NOP", AddressingMode::Implied, 2, false),
+    /* 0xEB */ undocumented("USBC", AddressingMode::Immediate, 2, false),
+    /* 0xEC */ documented("CPX", AddressingMode::Absolute, 4, false),
+    /* 0xED */ documented("SBC", AddressingMode::Absolute, 4, false),
+    /* 0xEE */ documented("INC", AddressingMode::Absolute, 6, false),
+    /* 0xEF */ undocumented("ISC", AddressingMode::Absolute, 6, false),
+    /* 0xF0 */ documented("BEQ", AddressingMode::Relative, 2, true),
+    /* 0xF1 */ documented("SBC", AddressingMode::IndirectY, 5, true),
+    /* 0xF2 */ undocumented("JAM", AddressingMode::Implied, 2, false),
+    /* 0xF3 */ undocumented("ISC", AddressingMode::IndirectY, 8, false),
+    /* 0xF4 */ undocumented("NOP", AddressingMode::ZeroPageX, 4, false),
+    /* 0xF5 */ documented("SBC", AddressingMode::ZeroPageX, 4, false),
+    /* 0xF6 */ documented("INC", AddressingMode::ZeroPageX, 6, false),
+    /* 0xF7 */ undocumented("ISC", AddressingMode::ZeroPageX, 6, false),
+    /* 0xF8 */ documented("SED", AddressingMode::Implied, 2, false),
+    /* 0xF9 */ documented("SBC", AddressingMode::AbsoluteY, 4, true),
+    /* 0xFA */ undocumented("NOP", AddressingMode::Implied, 2, false),
+    /* 0xFB */ undocumented("ISC", AddressingMode::AbsoluteY, 7, false),
+    /* 0xFC */ undocumented("NOP", AddressingMode::AbsoluteX, 4, true),
+    /* 0xFD */ documented("SBC", AddressingMode::AbsoluteX, 4, true),
+    /* 0xFE */ documented("INC", AddressingMode::AbsoluteX, 7, false),
+    /* 0xFF */ undocumented("ISC", AddressingMode::AbsoluteX, 7, false),
+];
+
+// All 256 WDC 65C02 opcodes. Undefined ones are NOPs of fixed length and timing,
+// ADC and SBC take one more cycle in decimal mode
+pub static OPCODES_65C02: [Opcode; 256] = [
+    /* 0x00 */ documented("BRK", AddressingMode::Implied, 7, false),
+    /* 0x01 */ documented("ORA", AddressingMode::IndirectX, 6, false),
+    /* 0x02 */ undocumented("NOP", AddressingMode::Immediate, 2, false),
+    /* 0x03 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x04 */ documented("TSB", AddressingMode::ZeroPage, 5, false),
+    /* 0x05 */ documented("ORA", AddressingMode::ZeroPage, 3, false),
+    /* 0x06 */ documented("ASL", AddressingMode::ZeroPage, 5, false),
+    /* 0x07 */ documented("RMB0", AddressingMode::ZeroPage, 5, false),
+    /* 0x08 */ documented("PHP", AddressingMode::Implied, 3, false),
+    /* 0x09 */ documented("ORA", AddressingMode::Immediate, 2, false),
+    /* 0x0A */ documented("ASL", AddressingMode::Accumulator, 2, false),
+    /* 0x0B */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x0C */ documented("TSB", AddressingMode::Absolute, 6, false),
+    /* 0x0D */ documented("ORA", AddressingMode::Absolute, 4, false),
+    /* 0x0E */ documented("ASL", AddressingMode::Absolute, 6, false),
+    /* 0x0F */ documented("BBR0", AddressingMode::ZeroPageRelative, 5, true),
+    /* 0x10 */ documented("BPL", AddressingMode::Relative, 2, true),
+    /* 0x11 */ documented("ORA", AddressingMode::IndirectY, 5, true),
+    /* 0x12 */ documented("ORA", AddressingMode::ZeroPageIndirect, 5, false),
+    /* 0x13 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x14 */ documented("TRB", AddressingMode::ZeroPage, 5, false),
+    /* 0x15 */ documented("ORA", AddressingMode::ZeroPageX, 4, false),
+    /* 0x16 */ documented("ASL", AddressingMode::ZeroPageX, 6, false),
+    /* 0x17 */ documented("RMB1", AddressingMode::ZeroPage, 5, false),
+    /* 0x18 */ documented("CLC", AddressingMode::Implied, 2, false),
+    /* 0x19 */ documented("ORA", AddressingMode::AbsoluteY, 4, true),
+    /* 0x1A */ documented("INC", AddressingMode::Accumulator, 2, false),
+    /* 0x1B */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x1C */ documented("TRB", AddressingMode::Absolute, 6, false),
+    /* 0x1D */ documented("ORA", AddressingMode::AbsoluteX, 4, true),
+    /* 0x1E */ documented("ASL", AddressingMode::AbsoluteX, 6, true),
+    /* 0x1F */ documented("BBR1", AddressingMode::ZeroPageRelative, 5, true),
+    /* 0x20 */ documented("JSR", AddressingMode::Absolute, 6, false),
+    /* 0x21 */ documented("AND", AddressingMode::IndirectX, 6, false),
+    /* 0x22 */ undocumented("NOP", AddressingMode::Immediate, 2, false),
+    /* 0x23 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x24 */ documented("BIT", AddressingMode::ZeroPage, 3, false),
+    /* 0x25 */ documented("AND", AddressingMode::ZeroPage, 3, false),
+    /* 0x26 */ documented("ROL", AddressingMode::ZeroPage, 5, false),
+    /* 0x27 */ documented("RMB2", AddressingMode::ZeroPage, 5, false),
+    /* 0x28 */ documented("PLP", AddressingMode::Implied, 4, false),
+    /* 0x29 */ documented("AND", AddressingMode::Immediate, 2, false),
+    /* 0x2A */ documented("ROL", AddressingMode::Accumulator, 2, false),
+    /* 0x2B */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x2C */ documented("BIT", AddressingMode::Absolute, 4, false),
+    /* 0x2D */ documented("AND", AddressingMode::Absolute, 4, false),
+    /* 0x2E */ documented("ROL", AddressingMode::Absolute, 6, false),
+    /* 0x2F */ documented("BBR2", AddressingMode::ZeroPageRelative, 5, true),
+    /* 0x30 */ documented("BMI", AddressingMode::Relative, 2, true),
+    /* 0x31 */ documented("AND", AddressingMode::IndirectY, 5, true),
+    /* 0x32 */ documented("AND", AddressingMode::ZeroPageIndirect, 5, false),
+    /* 0x33 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x34 */ documented("BIT", AddressingMode::ZeroPageX, 4, false),
+    /* 0x35 */ documented("AND", AddressingMode::ZeroPageX, 4, false),
+    /* 0x36 */ documented("ROL", AddressingMode::ZeroPageX, 6, false),
+    /* 0x37 */ documented("RMB3", AddressingMode::ZeroPage, 5, false),
+    /* 0x38 */ documented("SEC", AddressingMode::Implied, 2, false),
+    /* 0x39 */ documented("AND", AddressingMode::AbsoluteY, 4, true),
+    /* 0x3A */ documented("DEC", AddressingMode::Accumulator, 2, false),
+    /* 0x3B */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x3C */ documented("BIT", AddressingMode::AbsoluteX, 4, true),
+    /* 0x3D */ documented("AND", AddressingMode::AbsoluteX, 4, true),
+    /* 0x3E */ documented("ROL", AddressingMode::AbsoluteX, 6, true),
+    /* 0x3F */ documented("BBR3", AddressingMode::ZeroPageRelative, 5, true),
+    /* 0x40 */ documented("RTI", AddressingMode::Implied, 6, false),
+    /* 0x41 */ documented("EOR", AddressingMode::IndirectX, 6, false),
+    /* 0x42 */ undocumented("NOP", AddressingMode::Immediate, 2, false),
+    /* 0x43 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x44 */ undocumented("NOP", AddressingMode::ZeroPage, 3, false),
+    /* 0x45 */ documented("EOR", AddressingMode::ZeroPage, 3, false),
+    /* 0x46 */ documented("LSR", AddressingMode::ZeroPage, 5, false),
+    /* 0x47 */ documented("RMB4", AddressingMode::ZeroPage, 5, false),
+    /* 0x48 */ documented("PHA", AddressingMode::Implied, 3, false),
+    /* 0x49 */ documented("EOR", AddressingMode::Immediate, 2, false),
+    /* 0x4A */ documented("LSR", AddressingMode::Accumulator, 2, false),
+    /* 0x4B */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x4C */ documented("JMP", AddressingMode::Absolute, 3, false),
+    /* 0x4D */ documented("EOR", AddressingMode::Absolute, 4, false),
+    /* 0x4E */ documented("LSR", AddressingMode::Absolute, 6, false),
+    /* 0x4F */ documented("BBR4", AddressingMode::ZeroPageRelative, 5, true),
+    /* 0x50 */ documented("BVC", AddressingMode::Relative, 2, true),
+    /* 0x51 */ documented("EOR", AddressingMode::IndirectY, 5, true),
+    /* 0x52 */ documented("EOR", AddressingMode::ZeroPageIndirect, 5, false),
+    /* 0x53 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x54 */ undocumented("NOP", AddressingMode::ZeroPageX, 4, false),
+    /* 0x55 */ documented("EOR", AddressingMode::ZeroPageX, 4, false),
+    /* 0x56 */ documented("LSR", AddressingMode::ZeroPageX, 6, false),
+    /* 0x57 */ documented("RMB5", AddressingMode::ZeroPage, 5, false),
+    /* 0x58 */ documented("CLI", AddressingMode::Implied, 2, false),
+    /* 0x59 */ documented("EOR", AddressingMode::AbsoluteY, 4, true),
+    /* 0x5A */ documented("PHY", AddressingMode::Implied, 3, false),
+    /* 0x5B */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x5C */ undocumented("NOP", AddressingMode::Absolute, 8, false),
+    /* 0x5D */ documented("EOR", AddressingMode::AbsoluteX, 4, true),
+    /* 0x5E */ documented("LSR", AddressingMode::AbsoluteX, 6, true),
+    /* 0x5F */ documented("BBR5", AddressingMode::ZeroPageRelative, 5, true),
+    /* 0x60 */ documented("RTS", AddressingMode::Implied, 6, false),
+    /* 0x61 */ decimal("ADC", AddressingMode::IndirectX, 6, false),
+    /* 0x62 */ undocumented("NOP", AddressingMode::Immediate, 2, false),
+    /* 0x63 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x64 */ documented("STZ", AddressingMode::ZeroPage, 3, false),
+    /* 0x65 */ decimal("ADC", AddressingMode::ZeroPage, 3, false),
+    /* 0x66 */ documented("ROR", AddressingMode::ZeroPage, 5, false),
+    /* 0x67 */ documented("RMB6", AddressingMode::ZeroPage, 5, false),
+    /* 0x68 */ documented("PLA", AddressingMode::Implied, 4, false),
+    /* 0x69 */ decimal("ADC", AddressingMode::Immediate, 2, false),
+    /* 0x6A */ documented("ROR", AddressingMode::Accumulator, 2, false),
+    /* 0x6B */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x6C */ documented("JMP", AddressingMode::Indirect, 6, false),
+    /* 0x6D */ decimal("ADC", AddressingMode::Absolute, 4, false),
+    /* 0x6E */ documented("ROR", AddressingMode::Absolute, 6, false),
+    /* 0x6F */ documented("BBR6", AddressingMode::ZeroPageRelative, 5, true),
+    /* 0x70 */ documented("BVS", AddressingMode::Relative, 2, true),
+    /* 0x71 */ decimal("ADC", AddressingMode::IndirectY, 5, true),
+    /* 0x72 */ decimal("ADC", AddressingMode::ZeroPageIndirect, 5, false),
+    /* 0x73 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x74 */ documented("STZ", AddressingMode::ZeroPageX, 4, false),
+    /* 0x75 */ decimal("ADC", AddressingMode::ZeroPageX, 4, false),
+    /* 0x76 */ documented("ROR", AddressingMode::ZeroPageX, 6, false),
+    /* 0x77 */ documented("RMB7", AddressingMode::ZeroPage, 5, false),
+    /* 0x78 */ documented("SEI", AddressingMode::Implied, 2, false),
+    /* 0x79 */ decimal("ADC", AddressingMode::AbsoluteY, 4, true),
+    /* 0x7A */ documented("PLY", AddressingMode::Implied, 4, false),
+    /* 0x7B */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x7C */ documented("JMP", AddressingMode::AbsoluteIndexedIndirect, 6, false),
+    /* 0x7D */ decimal("ADC", AddressingMode::AbsoluteX, 4, true),
+    /* 0x7E */ documented("ROR", AddressingMode::AbsoluteX, 6, true),
+    /* 0x7F */ documented("BBR7", AddressingMode::ZeroPageRelative, 5, true),
+    /* 0x80 */ documented("BRA", AddressingMode::Relative, 3, true),
+    /* 0x81 */ documented("STA", AddressingMode::IndirectX, 6, false),
+    /* 0x82 */ undocumented("NOP", AddressingMode::Immediate, 2, false),
+    /* 0x83 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x84 */ documented("STY", AddressingMode::ZeroPage, 3, false),
+    /* 0x85 */ documented("STA", AddressingMode::ZeroPage, 3, false),
+    /* 0x86 */ documented("STX", AddressingMode::ZeroPage, 3, false),
+    /* 0x87 */ documented("SMB0", AddressingMode::ZeroPage, 5, false),
+    /* 0x88 */ documented("DEY", AddressingMode::Implied, 2, false),
+    /* 0x89 */ documented("BIT", AddressingMode::Immediate, 2, false),
+    /* 0x8A */ documented("TXA", AddressingMode::Implied, 2, false),
+    /* 0x8B */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x8C */ documented("STY", AddressingMode::Absolute, 4, false),
+    /* 0x8D */ documented("STA", AddressingMode::Absolute, 4, false),
+    /* 0x8E */ documented("STX", AddressingMode::Absolute, 4, false),
+    /* 0x8F */ documented("BBS0", AddressingMode::ZeroPageRelative, 5, true),
+    /* 0x90 */ documented("BCC", AddressingMode::Relative, 2, true),
+    /* 0x91 */ documented("STA", AddressingMode::IndirectY, 6, false),
+    /* 0x92 */ documented("STA", AddressingMode::ZeroPageIndirect, 5, false),
+    /* 0x93 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x94 */ documented("STY", AddressingMode::ZeroPageX, 4, false),
+    /* 0x95 */ documented("STA", AddressingMode::ZeroPageX, 4, false),
+    /* 0x96 */ documented("STX", AddressingMode::ZeroPageY, 4, false),
+    /* 0x97 */ documented("SMB1", AddressingMode::ZeroPage, 5, false),
+    /* 0x98 */ documented("TYA", AddressingMode::Implied, 2, false),
+    /* 0x99 */ documented("STA", AddressingMode::AbsoluteY, 5, false),
+    /* 0x9A */ documented("TXS", AddressingMode::Implied, 2, false),
+    /* 0x9B */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0x9C */ documented("STZ", AddressingMode::Absolute, 4, false),
+    /* 0x9D */ documented("STA", AddressingMode::AbsoluteX, 5, false),
+    /* 0x9E */ documented("STZ", AddressingMode::AbsoluteX, 5, false),
+    /* 0x9F */ documented("BBS1", AddressingMode::ZeroPageRelative, 5, true),
+    /* 0xA0 */ documented("LDY", AddressingMode::Immediate, 2, false),
+    /* 0xA1 */ documented("LDA", AddressingMode::IndirectX, 6, false),
+    /* 0xA2 */ documented("LDX", AddressingMode::Immediate, 2, false),
+    /* 0xA3 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0xA4 */ documented("LDY", AddressingMode::ZeroPage, 3, false),
+    /* 0xA5 */ documented("LDA", AddressingMode::ZeroPage, 3, false),
+    /* 0xA6 */ documented("LDX", AddressingMode::ZeroPage, 3, false),
+    /* 0xA7 */ documented("SMB2", AddressingMode::ZeroPage, 5, false),
+    /* 0xA8 */ documented("TAY", AddressingMode::Implied, 2, false),
+    /* 0xA9 */ documented("LDA", AddressingMode::Immediate, 2, false),
+    /* 0xAA */ documented("TAX", AddressingMode::Implied, 2, false),
+    /* 0xAB */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0xAC */ documented("LDY", AddressingMode::Absolute, 4, false),
+    /* 0xAD */ documented("LDA", AddressingMode::Absolute, 4, false),
+    /* 0xAE */ documented("LDX", AddressingMode::Absolute, 4, false),
+    /* 0xAF */ documented("BBS2", AddressingMode::ZeroPageRelative, 5, true),
+    /* 0xB0 */ documented("BCS", AddressingMode::Relative, 2, true),
+    /* 0xB1 */ documented("LDA", AddressingMode::IndirectY, 5, true),
+    /* 0xB2 */ documented("LDA", AddressingMode::ZeroPageIndirect, 5, false),
+    /* 0xB3 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0xB4 */ documented("LDY", AddressingMode::ZeroPageX, 4, false),
+    /* 0xB5 */ documented("LDA", AddressingMode::ZeroPageX, 4, false),
+    /* 0xB6 */ documented("LDX", AddressingMode::ZeroPageY, 4, false),
+    /* 0xB7 */ documented("SMB3", AddressingMode::ZeroPage, 5, false),
+    /* 0xB8 */ documented("CLV", AddressingMode::Implied, 2, false),
+    /* 0xB9 */ documented("LDA", AddressingMode::AbsoluteY, 4, true),
+    /* 0xBA */ documented("TSX", AddressingMode::Implied, 2, false),
+    /* 0xBB */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0xBC */ documented("LDY", AddressingMode::AbsoluteX, 4, true),
+    /* 0xBD */ documented("LDA", AddressingMode::AbsoluteX, 4, true),
+    /* 0xBE */ documented("LDX", AddressingMode::AbsoluteY, 4, true),
+    /* 0xBF */ documented("BBS3", AddressingMode::ZeroPageRelative, 5, true),
+    /* 0xC0 */ documented("CPY", AddressingMode::Immediate, 2, false),
+    /* 0xC1 */ documented("CMP", AddressingMode::IndirectX, 6, false),
+    /* 0xC2 */ undocumented("NOP", AddressingMode::Immediate, 2, false),
+    /* 0xC3 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0xC4 */ documented("CPY", AddressingMode::ZeroPage, 3, false),
+    /* 0xC5 */ documented("CMP", AddressingMode::ZeroPage, 3, false),
+    /* 0xC6 */ documented("DEC", AddressingMode::ZeroPage, 5, false),
+    /* 0xC7 */ documented("SMB4", AddressingMode::ZeroPage, 5, false),
+    /* 0xC8 */ documented("INY", AddressingMode::Implied, 2, false),
+    /* 0xC9 */ documented("CMP", AddressingMode::Immediate, 2, false),
+    /* 0xCA */ documented("DEX", AddressingMode::Implied, 2, false),
+    /* 0xCB */ documented("WAI", AddressingMode::Implied, 3, false),
+    /* 0xCC */ documented("CPY", AddressingMode::Absolute, 4, false),
+    /* 0xCD */ documented("CMP", AddressingMode::Absolute, 4, false),
+    /* 0xCE */ documented("DEC", AddressingMode::Absolute, 6, false),
+    /* 0xCF */ documented("BBS4", AddressingMode::ZeroPageRelative, 5, true),
+    /* 0xD0 */ documented("BNE", AddressingMode::Relative, 2, true),
+    /* 0xD1 */ documented("CMP", AddressingMode::IndirectY, 5, true),
+    /* 0xD2 */ documented("CMP", AddressingMode::ZeroPageIndirect, 5, false),
+    /* 0xD3 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0xD4 */ undocumented("NOP", AddressingMode::ZeroPageX, 4, false),
+    /* 0xD5 */ documented("CMP", AddressingMode::ZeroPageX, 4, false),
+    /* 0xD6 */ documented("DEC", AddressingMode::ZeroPageX, 6, false),
+    /* 0xD7 */ documented("SMB5", AddressingMode::ZeroPage, 5, false),
+    /* 0xD8 */ documented("CLD", AddressingMode::Implied, 2, false),
+    /* 0xD9 */ documented("CMP", AddressingMode::AbsoluteY, 4, true),
+    /* 0xDA */ documented("PHX", AddressingMode::Implied, 3, false),
+    /* 0xDB */ documented("STP", AddressingMode::Implied, 3, false),
+    /* 0xDC */ undocumented("NOP", AddressingMode::Absolute, 4, false),
+    /* 0xDD */ documented("CMP", AddressingMode::AbsoluteX, 4, true),
+    /* 0xDE */ documented("DEC", AddressingMode::AbsoluteX, 7, false),
+    /* 0xDF */ documented("BBS5", AddressingMode::ZeroPageRelative, 5, true),
+    /* 0xE0 */ documented("CPX", AddressingMode::Immediate, 2, false),
+    /* 0xE1 */ decimal("SBC", AddressingMode::IndirectX, 6, false),
+    /* 0xE2 */ undocumented("NOP", AddressingMode::Immediate, 2, false),
+    /* 0xE3 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0xE4 */ documented("CPX", AddressingMode::ZeroPage, 3, false),
+    /* 0xE5 */ decimal("SBC", AddressingMode::ZeroPage, 3, false),
+    /* 0xE6 */ documented("INC", AddressingMode::ZeroPage, 5, false),
+    /* 0xE7 */ documented("SMB6", AddressingMode::ZeroPage, 5, false),
+    /* 0xE8 */ documented("INX", AddressingMode::Implied, 2, false),
+    /* 0xE9 */ decimal("SBC", AddressingMode::Immediate, 2, false),
+    /* 0xEA */ documented("NOP", AddressingMode::Implied, 2, false),
+    /* 0xEB */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0xEC */ documented("CPX", AddressingMode::Absolute, 4, false),
+    /* 0xED */ decimal("SBC", AddressingMode::Absolute, 4, false),
+    /* 0xEE */ documented("INC", AddressingMode::Absolute, 6, false),
+    /* 0xEF */ documented("BBS6", AddressingMode::ZeroPageRelative, 5, true),
+    /* 0xF0 */ documented("BEQ", AddressingMode::Relative, 2, true),
+    /* 0xF1 */ decimal("SBC", AddressingMode::IndirectY, 5, true),
+    /* 0xF2 */ decimal("SBC", AddressingMode::ZeroPageIndirect, 5, false),
+    /* 0xF3 */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0xF4 */ undocumented("NOP", AddressingMode::ZeroPageX, 4, false),
+    /* 0xF5 */ decimal("SBC", AddressingMode::ZeroPageX, 4, false),
+    /* 0xF6 */ documented("INC", AddressingMode::ZeroPageX, 6, false),
+    /* 0xF7 */ documented("SMB7", AddressingMode::ZeroPage, 5, false),
+    /* 0xF8 */ documented("SED", AddressingMode::Implied, 2, false),
+    /* 0xF9 */ decimal("SBC", AddressingMode::AbsoluteY, 4, true),
+    /* 0xFA */ documented("PLX", AddressingMode::Implied, 4, false),
+    /* 0xFB */ undocumented("NOP", AddressingMode::Implied, 1, false),
+    /* 0xFC */ undocumented("NOP", AddressingMode::Absolute, 4, false),
+    /* 0xFD */ decimal("SBC", AddressingMode::AbsoluteX, 4, true),
+    /* 0xFE */ documented("INC", AddressingMode::AbsoluteX, 7, false),
+    /* 0xFF */ documented("BBS7", AddressingMode::ZeroPageRelative, 5, true),
+];
+
+// Looks up the NMOS table entry for an opcode byte
+pub fn opcode(instruction: u8) -> &'static Opcode {
+    &OPCODES[instruction as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+
+    use super::super::addressing::AddressingMode;
+    use super::super::{setup_variant, CoreMode, Variant};
+    use super::{Opcode, OPCODES, OPCODES_65C02};
+
+    // Opcodes that leave PC somewhere other than the next instruction
+    const CONTROL_FLOW: [&str; 6] = ["BRK", "JMP", "JSR", "RTS", "RTI", "BRA"];
+
+    // Opcodes that lock up the processor instead of completing
+    const HALTING: [&str; 2] = ["JAM", "STP"];
+
+    // Runs one opcode with the operand $0210, or $10 on the zero page where a pointer
+    // to $0210 is stored. Returns the cycles taken and PC afterwards
+    fn run(variant: Variant, instruction: u8, index: u8, mode: CoreMode) -> (u32, u16) {
+        let (mut cpu, mut memory) = setup_variant(variant, 0x0200, &[instruction, 0x10, 0x02]);
+        memory[0x0010] = 0x10;
+        memory[0x0011] = 0x02;
+        cpu.set_core_mode(mode);
+        cpu.regX = index;
+        cpu.regY = index;
+        let cycles: u32 = cpu.step(&mut memory).unwrap().cycles;
+        (cycles, cpu.regPC)
+    }
+
+    fn check_all_opcodes(variant: Variant, table: &[Opcode; 256], mode: CoreMode) {
+        for (instruction, opcode) in table.iter().enumerate() {
+            if HALTING.contains(&opcode.mnemonic) {
+                continue;
+            }
+            let instruction: u8 = instruction as u8;
+
+            // No page crossed
+            let (cycles, pc) = run(variant, instruction, 0x00, mode);
+            let branch: bool = matches!(opcode.mode, AddressingMode::Relative | AddressingMode::ZeroPageRelative);
+            // BRA is always taken, its base cycles already count that
+            let taken: bool = branch && opcode.mnemonic != "BRA" && pc != 0x0200 + opcode.bytes as u16;
+            assert_eq!(cycles, opcode.cycles as u32 + taken as u32, "{:02X} {}", instruction, opcode.mnemonic);
+            if !CONTROL_FLOW.contains(&opcode.mnemonic) && !taken {
+                assert_eq!(pc, 0x0200 + opcode.bytes as u16, "{:02X} {}", instruction, opcode.mnemonic);
+            }
+
+            // Indexing from $0210 with $FF crosses a page, only some opcodes pay for it
+            if matches!(opcode.mode, AddressingMode::AbsoluteX | AddressingMode::AbsoluteY | AddressingMode::IndirectY) {
+                let (cycles, _) = run(variant, instruction, 0xFF, mode);
+                assert_eq!(cycles, opcode.cycles as u32 + opcode.page_penalty as u32, "{:02X} {}", instruction, opcode.mnemonic);
+            }
+        }
+    }
+
+    #[test]
+    fn instruction_core_matches_the_table() {
+        check_all_opcodes(Variant::NMOS6502, &OPCODES, CoreMode::Instruction);
+    }
+
+    #[test]
+    fn cycle_core_matches_the_table() {
+        check_all_opcodes(Variant::NMOS6502, &OPCODES, CoreMode::Cycle);
+    }
+
+    #[test]
+    fn cmos_instruction_core_matches_the_table() {
+        check_all_opcodes(Variant::CMOS65C02, &OPCODES_65C02, CoreMode::Instruction);
+    }
+
+    #[test]
+    fn cmos_cycle_core_matches_the_table() {
+        check_all_opcodes(Variant::CMOS65C02, &OPCODES_65C02, CoreMode::Cycle);
+    }
+
+    #[test]
+    fn cmos_decimal_mode_costs_a_cycle() {
+        for mode in [CoreMode::Instruction, CoreMode::Cycle] {
+            for (instruction, opcode) in OPCODES_65C02.iter().enumerate() {
+                if !opcode.decimal_penalty {
+                    continue;
+                }
+                let (mut cpu, mut memory) = setup_variant(Variant::CMOS65C02, 0x0200, &[instruction as u8, 0x10, 0x02]);
+                memory[0x0010] = 0x10;
+                memory[0x0011] = 0x02;
+                cpu.set_core_mode(mode);
+                cpu.proc_status.set_decimal_mode();
+                assert_eq!(cpu.step(&mut memory).unwrap().cycles, opcode.cycles as u32 + 1, "{:02X} {}", instruction, opcode.mnemonic);
+            }
+        }
+    }
+
+    #[test]
+    fn lengths_follow_the_addressing_mode() {
+        for opcode in OPCODES.iter() {
+            assert_eq!(opcode.bytes, opcode.mode.bytes());
+        }
+        assert_eq!(OPCODES.iter().filter(|opcode| opcode.documented).count(), 151);
+    }
+}
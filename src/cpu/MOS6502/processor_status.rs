@@ -98,6 +98,23 @@ impl ProcessorStatus {
     pub fn negative(&self) -> bool {
         self.status & (1 << 7) != 0
     }
+
+    // Value pushed on the stack: bit 5 always reads as set, and B (bit 4)
+    // is only set when the push comes from BRK or PHP rather than IRQ/NMI
+    pub fn stack_value(&self, break_command: bool) -> u8 {
+        let pushed = self.status | (1 << 5);
+        if break_command {
+            pushed | (1 << 4)
+        } else {
+            pushed & !(1 << 4)
+        }
+    }
+
+    // B and bit 5 are not latched by the processor when the status is pulled
+    pub fn set_from_stack(&mut self, value: u8) {
+        let unlatched: u8 = (1 << 5) | (1 << 4);
+        self.status = (self.status & unlatched) | (value & !unlatched);
+    }
 }
 
 impl From<ProcessorStatus> for u8 {
@@ -0,0 +1,122 @@
+use super::{UnknownOpcodePolicy, Variant};
+
+// Behaviour that differs between members of the 6502 family. Each Variant
+// has a preset, a clone chip is described by starting from the closest one:
+// Quirks { jmp_indirect_bug : false, ..Variant::NMOS6502.quirks() }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quirks {
+    // ADC and SBC work in BCD when D is set. D itself is always stored and pushed
+    pub decimal_mode: bool,
+    // 65C02 instruction set: the new opcodes, undefined opcodes as NOPs of
+    // defined length, and decimal ADC/SBC with valid N and Z for one more cycle
+    pub cmos_instructions: bool,
+    // JMP ($xxFF) takes the high byte from $xx00. Without the bug the pointer
+    // carries into the next page, at the cost of one more cycle
+    pub jmp_indirect_bug: bool,
+    // Opcodes outside the documented NMOS set, unused with cmos_instructions
+    pub illegal_opcodes: UnknownOpcodePolicy,
+    // Read-modify-write instructions write the unmodified value back during
+    // the modify cycle, otherwise they read it a second time
+    pub rmw_double_write: bool,
+    // An NMI arriving while BRK or IRQ pushes the return address takes over
+    // their vector. Only the cycle core models it: the instruction core samples
+    // the lines between instructions, so there this has no effect
+    pub interrupt_hijacking: bool,
+    // Interrupts and reset clear D
+    pub clear_decimal_on_interrupt: bool,
+    // Constant ORed into A by the unstable XAA and LAX #imm, it varies between chips and temperature
+    pub unstable_magic: u8,
+    // SHA, SHX, SHY and TAS AND the stored value with the base high byte + 1.
+    // On some chips, or when RDY stalls the write cycle, that term drops out
+    pub store_high_and: bool,
+    // Address lines bonded out, every bus access is masked with it
+    pub address_mask: u16,
+    // IRQ and NMI pins bonded out
+    pub interrupt_pins: bool,
+    // 6510 I/O port mapped at $0000/$0001
+    pub io_port: bool,
+}
+
+impl Variant {
+    pub fn quirks(&self) -> Quirks {
+        let nmos: Quirks = Quirks {
+            decimal_mode : true,
+            cmos_instructions : false,
+            jmp_indirect_bug : true,
+            illegal_opcodes : UnknownOpcodePolicy::Undocumented,
+            rmw_double_write : true,
+            interrupt_hijacking : true,
+            clear_decimal_on_interrupt : false,
+            unstable_magic : 0xEE,
+            store_high_and : true,
+            address_mask : 0xFFFF,
+            interrupt_pins : true,
+            io_port : false,
+        };
+        match self {
+            Variant::NMOS6502 => nmos,
+            Variant::CMOS65C02 => Quirks {
+                cmos_instructions : true,
+                jmp_indirect_bug : false,
+                rmw_double_write : false,
+                interrupt_hijacking : false,
+                clear_decimal_on_interrupt : true,
+                ..nmos
+            },
+            Variant::RP2A03 | Variant::RP2A07 => Quirks { decimal_mode : false, ..nmos },
+            Variant::MOS6510 => Quirks { io_port : true, ..nmos },
+            Variant::MOS6507 => Quirks { address_mask : 0x1FFF, interrupt_pins : false, ..nmos },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+
+    use super::super::{setup, CoreMode, Variant, MOS6502};
+    use super::Quirks;
+
+    #[test]
+    fn with_variant_takes_the_preset() {
+        for variant in [Variant::NMOS6502, Variant::CMOS65C02, Variant::RP2A03, Variant::MOS6510, Variant::MOS6507] {
+            assert_eq!(MOS6502::with_variant(variant).quirks(), variant.quirks());
+        }
+    }
+
+    #[test]
+    fn clone_without_decimal_mode_or_jmp_bug() {
+        for mode in [CoreMode::Instruction, CoreMode::Cycle] {
+            // SED; CLC; LDA #$19; ADC #$28; JMP ($02FF)
+            let (mut cpu, mut memory) = setup(0x0200, &[0xF8, 0x18, 0xA9, 0x19, 0x69, 0x28, 0x6C, 0xFF, 0x02]);
+            cpu.set_quirks(Quirks { decimal_mode : false, jmp_indirect_bug : false, ..Variant::NMOS6502.quirks() });
+            cpu.set_core_mode(mode);
+            memory[0x02FF] = 0x34;
+            memory[0x0300] = 0x12;
+            cpu.run_instructions(4, &mut memory).unwrap();
+            assert_eq!(cpu.regA, 0x41);
+
+            // The pointer carries into the next page, for one more cycle
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 6);
+            assert_eq!(cpu.regPC, 0x1234);
+
+            // Still an NMOS instruction set otherwise
+            assert_eq!(cpu.variant(), Variant::NMOS6502);
+            assert!(!cpu.quirks().cmos_instructions);
+        }
+    }
+
+    #[test]
+    fn cmos_instructions_keeping_the_jmp_bug() {
+        for mode in [CoreMode::Instruction, CoreMode::Cycle] {
+            // JMP ($02FF)
+            let (mut cpu, mut memory) = setup(0x0200, &[0x6C, 0xFF, 0x02]);
+            cpu.set_quirks(Quirks { jmp_indirect_bug : true, ..Variant::CMOS65C02.quirks() });
+            cpu.set_core_mode(mode);
+            memory[0x02FF] = 0x34;
+            memory[0x0300] = 0x12;
+            assert_eq!(cpu.step(&mut memory).unwrap().cycles, 5);
+            assert_eq!(cpu.regPC, 0x6C34);
+        }
+    }
+}
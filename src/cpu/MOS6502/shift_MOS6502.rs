@@ -0,0 +1,225 @@
+use crate::cpu::bus::Bus; 
+
+use super::addressing::AddressingMode;
+use super::processor_status::ProcessorStatus;
+use super::MOS6502;
+
+fn on_shift_set_status(proc_status: &mut ProcessorStatus, value: u8, carry: bool) {
+    if carry {
+        proc_status.set_carry();
+    } else {
+        proc_status.clear_carry();
+    }
+    if value == 0 {
+        proc_status.set_zero();
+    } else {
+        proc_status.clear_zero();
+    }
+    if value & 0b1000_0000 != 0 {
+        proc_status.set_negative();
+    } else {
+        proc_status.clear_negative();
+    }
+}
+
+impl MOS6502 {
+    // Arithmetic shift left
+    pub(super) fn asl(&mut self, value: u8) -> u8 {
+        let result: u8 = value << 1;
+        on_shift_set_status(&mut self.proc_status, result, value & 0b1000_0000 != 0);
+        result
+    }
+
+    pub fn asl_acc(&mut self) -> u32 {
+        self.regA = self.asl(self.regA);
+        0
+    }
+
+    pub fn asl_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPage, memory, MOS6502::asl)
+    }
+
+    pub fn asl_zpx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPageX, memory, MOS6502::asl)
+    }
+
+    pub fn asl_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::Absolute, memory, MOS6502::asl)
+    }
+
+    pub fn asl_absx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteX, memory, MOS6502::asl)
+    }
+
+    // Logical shift right
+    pub(super) fn lsr(&mut self, value: u8) -> u8 {
+        let result: u8 = value >> 1;
+        on_shift_set_status(&mut self.proc_status, result, value & 0b0000_0001 != 0);
+        result
+    }
+
+    pub fn lsr_acc(&mut self) -> u32 {
+        self.regA = self.lsr(self.regA);
+        0
+    }
+
+    pub fn lsr_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPage, memory, MOS6502::lsr)
+    }
+
+    pub fn lsr_zpx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPageX, memory, MOS6502::lsr)
+    }
+
+    pub fn lsr_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::Absolute, memory, MOS6502::lsr)
+    }
+
+    pub fn lsr_absx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteX, memory, MOS6502::lsr)
+    }
+
+    // Rotate left through carry
+    pub(super) fn rol(&mut self, value: u8) -> u8 {
+        let carry: u8 = if self.proc_status.carry() { 1 } else { 0 };
+        let result: u8 = (value << 1) | carry;
+        on_shift_set_status(&mut self.proc_status, result, value & 0b1000_0000 != 0);
+        result
+    }
+
+    pub fn rol_acc(&mut self) -> u32 {
+        self.regA = self.rol(self.regA);
+        0
+    }
+
+    pub fn rol_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPage, memory, MOS6502::rol)
+    }
+
+    pub fn rol_zpx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPageX, memory, MOS6502::rol)
+    }
+
+    pub fn rol_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::Absolute, memory, MOS6502::rol)
+    }
+
+    pub fn rol_absx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteX, memory, MOS6502::rol)
+    }
+
+    // Rotate right through carry
+    pub(super) fn ror(&mut self, value: u8) -> u8 {
+        let carry: u8 = if self.proc_status.carry() { 1 } else { 0 };
+        let result: u8 = (value >> 1) | (carry << 7);
+        on_shift_set_status(&mut self.proc_status, result, value & 0b0000_0001 != 0);
+        result
+    }
+
+    pub fn ror_acc(&mut self) -> u32 {
+        self.regA = self.ror(self.regA);
+        0
+    }
+
+    pub fn ror_zp<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPage, memory, MOS6502::ror)
+    }
+
+    pub fn ror_zpx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::ZeroPageX, memory, MOS6502::ror)
+    }
+
+    pub fn ror_abs<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::Absolute, memory, MOS6502::ror)
+    }
+
+    pub fn ror_absx<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        self.modify_operand(AddressingMode::AbsoluteX, memory, MOS6502::ror)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+
+    use super::super::setup;
+
+    #[test]
+    fn accumulator_shifts() {
+        // ASL A; LSR A; ROL A; ROR A
+        let (mut cpu, mut memory) = setup(0x0200, &[0x0A, 0x4A, 0x2A, 0x6A]);
+        cpu.regA = 0x81;
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regA, 0x02);
+        assert!(cpu.proc_status.carry());
+
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regA, 0x01);
+        assert!(!cpu.proc_status.carry());
+
+        cpu.proc_status.set_carry();
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regA, 0x03);
+        assert!(!cpu.proc_status.carry());
+
+        cpu.proc_status.set_carry();
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regA, 0x81);
+        assert!(cpu.proc_status.carry());
+        assert!(cpu.proc_status.negative());
+    }
+
+    #[test]
+    fn shift_out_to_zero() {
+        // LSR A
+        let (mut cpu, mut memory) = setup(0x0200, &[0x4A]);
+        cpu.regA = 0x01;
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regA, 0x00);
+        assert!(cpu.proc_status.carry());
+        assert!(cpu.proc_status.zero());
+        assert!(!cpu.proc_status.negative());
+    }
+
+    #[test]
+    fn memory_modes_and_timings() {
+        // ASL $10; LSR $10,X; ROL $0300; ROR $0300,X
+        let (mut cpu, mut memory) = setup(0x0200, &[0x06, 0x10, 0x56, 0x10, 0x2E, 0x00, 0x03, 0x7E, 0x00, 0x03]);
+        cpu.regX = 0x01;
+        memory[0x0010] = 0x40;
+        memory[0x0011] = 0x02;
+        memory[0x0300] = 0x80;
+        memory[0x0301] = 0x01;
+        cpu.execute(5, &mut memory).unwrap();
+        assert_eq!(memory[0x0010], 0x80);
+        cpu.execute(6, &mut memory).unwrap();
+        assert_eq!(memory[0x0011], 0x01);
+        cpu.execute(6, &mut memory).unwrap();
+        assert_eq!(memory[0x0300], 0x00);
+        assert!(cpu.proc_status.carry());
+        cpu.execute(7, &mut memory).unwrap();
+        assert_eq!(memory[0x0301], 0x80);
+        assert!(cpu.proc_status.carry());
+        assert_eq!(cpu.regPC, 0x020A);
+    }
+
+    #[test]
+    fn zero_page_x_wraps() {
+        // ASL $FF,X
+        let (mut cpu, mut memory) = setup(0x0200, &[0x16, 0xFF]);
+        cpu.regX = 0x02;
+        memory[0x0001] = 0x21;
+        cpu.execute(6, &mut memory).unwrap();
+        assert_eq!(memory[0x0001], 0x42);
+        assert_eq!(memory[0x0101], 0x00);
+    }
+
+    #[test]
+    fn read_modify_write_writes_twice() {
+        // ROL $0300
+        let (mut cpu, mut memory) = setup(0x0200, &[0x2E, 0x00, 0x03]);
+        memory[0x0300] = 0x55;
+        cpu.execute(6, &mut memory).unwrap();
+        assert_eq!(memory.writes(), vec![(0x0300, 0x55), (0x0300, 0xAA)]);
+    }
+}
@@ -1,37 +1,133 @@
-use crate::cpu::memory::Memory; 
+use crate::cpu::bus::Bus; 
 
+use crate::cpu::cpu::CPU;
+
+use super::processor_status::ProcessorStatus;
 use super::MOS6502;
 
+pub(super) const STACK_PAGE: u16 = 0x0100;
+
+fn on_pull_set_status(proc_status: &mut ProcessorStatus, value: u8) {
+    if value == 0 {
+        proc_status.set_zero();
+    } else {
+        proc_status.clear_zero();
+    }
+    if value & 0b1000_0000 != 0 {
+        proc_status.set_negative();
+    } else {
+        proc_status.clear_negative();
+    }
+}
+
 impl MOS6502 {
+    // The stack lives in page $01 and grows downwards, SP points to the next free slot
+    pub(super) fn push<B: Bus>(&mut self, value: u8, memory : &mut B) {
+        self.write(STACK_PAGE | self.regSP as u16, value, memory);
+        self.regSP = self.regSP.wrapping_sub(1);
+    }
+
+    pub(super) fn pull<B: Bus>(&mut self, memory : &mut B) -> u8 {
+        self.regSP = self.regSP.wrapping_add(1);
+        self.read(STACK_PAGE | self.regSP as u16, memory)
+    }
+
     // Push operations
-    pub fn pha(&mut self, memory : &mut Memory) -> u32 {
-        memory[0x0100 | self.regSP as usize] = self.regA;
-        2
+    pub fn pha<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.push(self.regA, memory);
+        0
     }
 
-    pub fn php(&mut self, memory : &mut Memory) -> u32 {
-        memory[0x0100 | self.regSP as usize] = self.proc_status.into();
-        2
+    pub fn php<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        // PHP always pushes B and bit 5 set
+        self.push(self.proc_status.stack_value(true), memory);
+        0
     }
 
     // Pull operations
-    pub fn pla(&mut self, memory : &mut Memory) -> u32 {
-        self.regA = memory[0x0100 | self.regPC as usize];
-        if self.regA == 0 {
-            self.proc_status.set_zero();
-        } else {
-            self.proc_status.clear_zero();
-        }
-        if self.regA & 0b1000_0000 != 0 {
-            self.proc_status.set_negative();
-        } else {
-            self.proc_status.clear_negative();
-        }
-        3
-    }
-
-    pub fn plp(&mut self, memory : &mut Memory) -> u32 {
-        self.proc_status = memory[0x0100 | self.regSP as usize].into();
-        3
-    }
-}
\ No newline at end of file
+    pub fn pla<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        self.regA = self.pull(memory);
+        on_pull_set_status(&mut self.proc_status, self.regA);
+        0
+    }
+
+    pub fn plp<B: Bus>(&mut self, memory : &mut B) -> u32 {
+        let status: u8 = self.pull(memory);
+        self.proc_status.set_from_stack(status);
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+
+    use super::super::setup;
+
+    #[test]
+    fn pha_and_pla_move_sp() {
+        // PHA; LDA #$00; PLA
+        let (mut cpu, mut memory) = setup(0x0200, &[0x48, 0xA9, 0x00, 0x68]);
+        cpu.regA = 0x80;
+        cpu.execute(3, &mut memory).unwrap();
+        assert_eq!(cpu.regSP, 0xFE);
+        assert_eq!(memory[0x01FF], 0x80);
+        cpu.execute(2, &mut memory).unwrap();
+        assert!(cpu.proc_status.zero());
+        cpu.execute(4, &mut memory).unwrap();
+        assert_eq!(cpu.regSP, 0xFF);
+        assert_eq!(cpu.regA, 0x80);
+        assert!(!cpu.proc_status.zero());
+        assert!(cpu.proc_status.negative());
+        assert_eq!(cpu.regPC, 0x0204);
+    }
+
+    #[test]
+    fn php_pushes_b_and_bit_5() {
+        let (mut cpu, mut memory) = setup(0x0200, &[0x08]);
+        cpu.proc_status.set_carry();
+        cpu.proc_status.set_negative();
+        cpu.execute(3, &mut memory).unwrap();
+        assert_eq!(memory[0x01FF], 0xB1);
+        assert_eq!(cpu.regSP, 0xFE);
+        // Only the pushed copy carries B
+        assert!(!cpu.proc_status.break_command());
+    }
+
+    #[test]
+    fn plp_ignores_b_and_bit_5() {
+        let (mut cpu, mut memory) = setup(0x0200, &[0x28]);
+        cpu.regSP = 0xFE;
+        memory[0x01FF] = 0xFF;
+        cpu.execute(4, &mut memory).unwrap();
+        assert_eq!(u8::from(cpu.proc_status), 0xCF);
+        assert_eq!(cpu.regSP, 0xFF);
+    }
+
+    #[test]
+    fn stack_wraps_within_page_1() {
+        // PHA; PLA
+        let (mut cpu, mut memory) = setup(0x0200, &[0x48, 0x68]);
+        cpu.regSP = 0x00;
+        cpu.regA = 0x5A;
+        cpu.execute(3, &mut memory).unwrap();
+        assert_eq!(memory[0x0100], 0x5A);
+        assert_eq!(cpu.regSP, 0xFF);
+        cpu.regA = 0x00;
+        cpu.execute(4, &mut memory).unwrap();
+        assert_eq!(cpu.regA, 0x5A);
+        assert_eq!(cpu.regSP, 0x00);
+    }
+
+    #[test]
+    fn txs_leaves_flags_alone() {
+        // TXS; TSX
+        let (mut cpu, mut memory) = setup(0x0200, &[0x9A, 0xBA]);
+        cpu.regX = 0x00;
+        cpu.execute(2, &mut memory).unwrap();
+        assert_eq!(cpu.regSP, 0x00);
+        assert!(!cpu.proc_status.zero());
+        cpu.execute(2, &mut memory).unwrap();
+        assert!(cpu.proc_status.zero());
+    }
+}
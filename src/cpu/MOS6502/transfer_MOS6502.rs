@@ -19,39 +19,39 @@ impl MOS6502 {
     pub fn tax(&mut self) -> u32 {
         self.regX = self.regA;
         on_ts_set_status(&mut self.proc_status, self.regA);
-        1
+        0
     }
 
     pub fn tay(&mut self) -> u32 {
         self.regY = self.regA;
         on_ts_set_status(&mut self.proc_status, self.regA);
-        1
+        0
     }
 
     // Transfer from stack pointer
     pub fn tsx(&mut self) -> u32 {
         self.regX = self.regSP;
         on_ts_set_status(&mut self.proc_status, self.regSP);
-        1
+        0
     }
 
     // Transfer from X register
     pub fn txa(&mut self) -> u32 {
         self.regA = self.regX;
         on_ts_set_status(&mut self.proc_status, self.regX);
-        1
+        0
     }
 
+    // TXS is the only transfer that leaves the flags untouched
     pub fn txs(&mut self) -> u32 {
         self.regSP = self.regX;
-        on_ts_set_status(&mut self.proc_status, self.regX);
-        1
+        0
     }
 
     // Transfer from Y register
     pub fn tya(&mut self) -> u32 {
         self.regA = self.regY;
         on_ts_set_status(&mut self.proc_status, self.regY);        
-        1
+        0
     }
 }
\ No newline at end of file
@@ -0,0 +1,700 @@
+mod addressing_WDC65C816;
+mod transfer_WDC65C816;
+mod stack_WDC65C816;
+mod alu_WDC65C816;
+mod control_WDC65C816;
+
+use addressing_WDC65C816::Mode;
+
+use super::bus::Bus;
+use super::cpu::{Register, StepInfo, CPU};
+use super::error::ExecutionError;
+
+// Status register bits. M and X select 8-bit accumulator/memory and index registers,
+// in emulation mode both read as set and bit 4 is the 6502 break flag on the stack
+const CARRY: u8 = 0x01;
+const ZERO: u8 = 0x02;
+const IRQ_DISABLE: u8 = 0x04;
+const DECIMAL: u8 = 0x08;
+const INDEX_8: u8 = 0x10;
+const MEMORY_8: u8 = 0x20;
+const OVERFLOW: u8 = 0x40;
+const NEGATIVE: u8 = 0x80;
+
+// The 65C816 drives 24 address lines: 8 bits of bank and 16 bits of offset
+const ADDRESS_MASK: u32 = 0xFF_FFFF;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    ADC, AND, ASL, BCC, BCS, BEQ, BIT, BMI, BNE, BPL, BRA, BRK, BRL, BVC, BVS, CLC,
+    CLD, CLI, CLV, CMP, COP, CPX, CPY, DEC, DEX, DEY, EOR, INC, INX, INY, JML, JMP,
+    JSL, JSR, LDA, LDX, LDY, LSR, MVN, MVP, NOP, ORA, PEA, PEI, PER, PHA, PHB, PHD,
+    PHK, PHP, PHX, PHY, PLA, PLB, PLD, PLP, PLX, PLY, REP, ROL, ROR, RTI, RTL, RTS,
+    SBC, SEC, SED, SEI, SEP, STA, STP, STX, STY, STZ, TAX, TAY, TCD, TCS, TDC, TRB,
+    TSB, TSC, TSX, TXA, TXS, TXY, TYA, TYX, WAI, WDM, XBA, XCE,
+}
+
+// Every one of the 256 opcodes is defined on the 65C816
+const OPCODES: [(Op, Mode); 256] = {
+    use Op::*;
+    use Mode::*;
+    [
+        (BRK, Immediate), (ORA, DirectIndexedIndirect), (COP, Immediate), (ORA, StackRelative),
+        (TSB, Direct), (ORA, Direct), (ASL, Direct), (ORA, DirectIndirectLong),
+        (PHP, Implied), (ORA, Immediate), (ASL, Accumulator), (PHD, Implied),
+        (TSB, Absolute), (ORA, Absolute), (ASL, Absolute), (ORA, AbsoluteLong),
+        (BPL, Relative), (ORA, DirectIndirectIndexed), (ORA, DirectIndirect), (ORA, StackRelativeIndirectIndexed),
+        (TRB, Direct), (ORA, DirectX), (ASL, DirectX), (ORA, DirectIndirectLongIndexed),
+        (CLC, Implied), (ORA, AbsoluteY), (INC, Accumulator), (TCS, Implied),
+        (TRB, Absolute), (ORA, AbsoluteX), (ASL, AbsoluteX), (ORA, AbsoluteLongX),
+        (JSR, Absolute), (AND, DirectIndexedIndirect), (JSL, AbsoluteLong), (AND, StackRelative),
+        (BIT, Direct), (AND, Direct), (ROL, Direct), (AND, DirectIndirectLong),
+        (PLP, Implied), (AND, Immediate), (ROL, Accumulator), (PLD, Implied),
+        (BIT, Absolute), (AND, Absolute), (ROL, Absolute), (AND, AbsoluteLong),
+        (BMI, Relative), (AND, DirectIndirectIndexed), (AND, DirectIndirect), (AND, StackRelativeIndirectIndexed),
+        (BIT, DirectX), (AND, DirectX), (ROL, DirectX), (AND, DirectIndirectLongIndexed),
+        (SEC, Implied), (AND, AbsoluteY), (DEC, Accumulator), (TSC, Implied),
+        (BIT, AbsoluteX), (AND, AbsoluteX), (ROL, AbsoluteX), (AND, AbsoluteLongX),
+        (RTI, Implied), (EOR, DirectIndexedIndirect), (WDM, Immediate), (EOR, StackRelative),
+        (MVP, BlockMove), (EOR, Direct), (LSR, Direct), (EOR, DirectIndirectLong),
+        (PHA, Implied), (EOR, Immediate), (LSR, Accumulator), (PHK, Implied),
+        (JMP, Absolute), (EOR, Absolute), (LSR, Absolute), (EOR, AbsoluteLong),
+        (BVC, Relative), (EOR, DirectIndirectIndexed), (EOR, DirectIndirect), (EOR, StackRelativeIndirectIndexed),
+        (MVN, BlockMove), (EOR, DirectX), (LSR, DirectX), (EOR, DirectIndirectLongIndexed),
+        (CLI, Implied), (EOR, AbsoluteY), (PHY, Implied), (TCD, Implied),
+        (JML, AbsoluteLong), (EOR, AbsoluteX), (LSR, AbsoluteX), (EOR, AbsoluteLongX),
+        (RTS, Implied), (ADC, DirectIndexedIndirect), (PER, RelativeLong), (ADC, StackRelative),
+        (STZ, Direct), (ADC, Direct), (ROR, Direct), (ADC, DirectIndirectLong),
+        (PLA, Implied), (ADC, Immediate), (ROR, Accumulator), (RTL, Implied),
+        (JMP, AbsoluteIndirect), (ADC, Absolute), (ROR, Absolute), (ADC, AbsoluteLong),
+        (BVS, Relative), (ADC, DirectIndirectIndexed), (ADC, DirectIndirect), (ADC, StackRelativeIndirectIndexed),
+        (STZ, DirectX), (ADC, DirectX), (ROR, DirectX), (ADC, DirectIndirectLongIndexed),
+        (SEI, Implied), (ADC, AbsoluteY), (PLY, Implied), (TDC, Implied),
+        (JMP, AbsoluteIndexedIndirect), (ADC, AbsoluteX), (ROR, AbsoluteX), (ADC, AbsoluteLongX),
+        (BRA, Relative), (STA, DirectIndexedIndirect), (BRL, RelativeLong), (STA, StackRelative),
+        (STY, Direct), (STA, Direct), (STX, Direct), (STA, DirectIndirectLong),
+        (DEY, Implied), (BIT, Immediate), (TXA, Implied), (PHB, Implied),
+        (STY, Absolute), (STA, Absolute), (STX, Absolute), (STA, AbsoluteLong),
+        (BCC, Relative), (STA, DirectIndirectIndexed), (STA, DirectIndirect), (STA, StackRelativeIndirectIndexed),
+        (STY, DirectX), (STA, DirectX), (STX, DirectY), (STA, DirectIndirectLongIndexed),
+        (TYA, Implied), (STA, AbsoluteY), (TXS, Implied), (TXY, Implied),
+        (STZ, Absolute), (STA, AbsoluteX), (STZ, AbsoluteX), (STA, AbsoluteLongX),
+        (LDY, Immediate), (LDA, DirectIndexedIndirect), (LDX, Immediate), (LDA, StackRelative),
+        (LDY, Direct), (LDA, Direct), (LDX, Direct), (LDA, DirectIndirectLong),
+        (TAY, Implied), (LDA, Immediate), (TAX, Implied), (PLB, Implied),
+        (LDY, Absolute), (LDA, Absolute), (LDX, Absolute), (LDA, AbsoluteLong),
+        (BCS, Relative), (LDA, DirectIndirectIndexed), (LDA, DirectIndirect), (LDA, StackRelativeIndirectIndexed),
+        (LDY, DirectX), (LDA, DirectX), (LDX, DirectY), (LDA, DirectIndirectLongIndexed),
+        (CLV, Implied), (LDA, AbsoluteY), (TSX, Implied), (TYX, Implied),
+        (LDY, AbsoluteX), (LDA, AbsoluteX), (LDX, AbsoluteY), (LDA, AbsoluteLongX),
+        (CPY, Immediate), (CMP, DirectIndexedIndirect), (REP, Immediate), (CMP, StackRelative),
+        (CPY, Direct), (CMP, Direct), (DEC, Direct), (CMP, DirectIndirectLong),
+        (INY, Implied), (CMP, Immediate), (DEX, Implied), (WAI, Implied),
+        (CPY, Absolute), (CMP, Absolute), (DEC, Absolute), (CMP, AbsoluteLong),
+        (BNE, Relative), (CMP, DirectIndirectIndexed), (CMP, DirectIndirect), (CMP, StackRelativeIndirectIndexed),
+        (PEI, DirectIndirect), (CMP, DirectX), (DEC, DirectX), (CMP, DirectIndirectLongIndexed),
+        (CLD, Implied), (CMP, AbsoluteY), (PHX, Implied), (STP, Implied),
+        (JML, AbsoluteIndirectLong), (CMP, AbsoluteX), (DEC, AbsoluteX), (CMP, AbsoluteLongX),
+        (CPX, Immediate), (SBC, DirectIndexedIndirect), (SEP, Immediate), (SBC, StackRelative),
+        (CPX, Direct), (SBC, Direct), (INC, Direct), (SBC, DirectIndirectLong),
+        (INX, Implied), (SBC, Immediate), (NOP, Implied), (XBA, Implied),
+        (CPX, Absolute), (SBC, Absolute), (INC, Absolute), (SBC, AbsoluteLong),
+        (BEQ, Relative), (SBC, DirectIndirectIndexed), (SBC, DirectIndirect), (SBC, StackRelativeIndirectIndexed),
+        (PEA, Absolute), (SBC, DirectX), (INC, DirectX), (SBC, DirectIndirectLongIndexed),
+        (SED, Implied), (SBC, AbsoluteY), (PLX, Implied), (XCE, Implied),
+        (JSR, AbsoluteIndexedIndirect), (SBC, AbsoluteX), (INC, AbsoluteX), (SBC, AbsoluteLongX),
+    ]
+};
+
+pub struct WDC65C816 {
+    regPC: u16,
+    regSP: u16,
+    // 16-bit accumulator C, A is its low byte and B its high byte
+    regA: u16,
+    regX: u16,
+    regY: u16,
+    // Direct page base, replaces the fixed zero page of the 6502
+    regD: u16,
+    regDB: u8,
+    regPB: u8,
+    regP: u8,
+    emulation: bool,
+
+    irq_line: bool,
+    nmi_line: bool,
+    nmi_pending: bool,
+    stopped: bool,
+    waiting: bool,
+
+    total_cycles: u64,
+    cycle_debt: u32,
+    // Bus and internal cycles of the instruction being executed
+    step_cycles: u32,
+}
+
+impl Default for WDC65C816 {
+    fn default() -> Self {
+        WDC65C816::new()
+    }
+}
+
+impl WDC65C816 {
+    // Power-on state: emulation mode with 8-bit registers, banks and direct
+    // page at 0 and interrupts disabled. PC is loaded by reset()
+    pub fn new() -> Self {
+        WDC65C816 {
+            regPC : 0x0000,
+            regSP : 0x0100,
+            regA : 0,
+            regX : 0,
+            regY : 0,
+            regD : 0,
+            regDB : 0,
+            regPB : 0,
+            regP : IRQ_DISABLE | MEMORY_8 | INDEX_8,
+            emulation : true,
+            irq_line : false,
+            nmi_line : false,
+            nmi_pending : false,
+            stopped : false,
+            waiting : false,
+            total_cycles : 0,
+            cycle_debt : 0,
+            step_cycles : 0,
+        }
+    }
+
+    // Register access, PC is on the CPU trait
+    pub fn sp(&self) -> u16 {
+        self.regSP
+    }
+
+    // Full 16-bit accumulator, whatever the M flag
+    pub fn a(&self) -> u16 {
+        self.regA
+    }
+
+    pub fn x(&self) -> u16 {
+        self.regX
+    }
+
+    pub fn y(&self) -> u16 {
+        self.regY
+    }
+
+    pub fn direct_page(&self) -> u16 {
+        self.regD
+    }
+
+    pub fn data_bank(&self) -> u8 {
+        self.regDB
+    }
+
+    pub fn program_bank(&self) -> u8 {
+        self.regPB
+    }
+
+    pub fn status(&self) -> u8 {
+        self.regP
+    }
+
+    pub fn emulation(&self) -> bool {
+        self.emulation
+    }
+
+    // 24-bit address of the next opcode
+    pub fn program_address(&self) -> u32 {
+        ((self.regPB as u32) << 16) | self.regPC as u32
+    }
+
+    // Accumulator and memory accesses are 16 bits wide when M is clear
+    fn memory_wide(&self) -> bool {
+        self.regP & MEMORY_8 == 0
+    }
+
+    // Index registers are 16 bits wide when X is clear
+    fn index_wide(&self) -> bool {
+        self.regP & INDEX_8 == 0
+    }
+
+    fn set_flag(&mut self, flag: u8, value: bool) {
+        if value {
+            self.regP |= flag;
+        } else {
+            self.regP &= !flag;
+        }
+    }
+
+    fn set_nz(&mut self, value: u16, wide: bool) {
+        let value: u16 = if wide { value } else { value & 0xFF };
+        let sign: u16 = if wide { 0x8000 } else { 0x80 };
+        self.set_flag(ZERO, value == 0);
+        self.set_flag(NEGATIVE, value & sign != 0);
+    }
+
+    // Loads P, emulation mode keeps M and X set. Setting X clears the index high bytes
+    fn set_status(&mut self, value: u8) {
+        self.regP = if self.emulation { value | MEMORY_8 | INDEX_8 } else { value };
+        if self.regP & INDEX_8 != 0 {
+            self.regX &= 0xFF;
+            self.regY &= 0xFF;
+        }
+    }
+
+    // Entering emulation mode forces 8-bit registers and moves the stack back to page $01
+    fn set_emulation(&mut self, emulation: bool) {
+        self.emulation = emulation;
+        if emulation {
+            self.set_status(self.regP);
+            self.set_sp(self.regSP);
+        }
+    }
+
+    fn set_sp(&mut self, value: u16) {
+        self.regSP = if self.emulation { 0x0100 | (value & 0xFF) } else { value };
+    }
+
+    // Accumulator at the current M width, B is left alone by 8-bit operations
+    fn accumulator(&self) -> u16 {
+        if self.memory_wide() { self.regA } else { self.regA & 0xFF }
+    }
+
+    fn set_accumulator(&mut self, value: u16) {
+        self.regA = if self.memory_wide() { value } else { (self.regA & 0xFF00) | (value & 0xFF) };
+    }
+
+    // Index registers have no hidden high byte, it reads as zero in 8-bit mode
+    fn index(&self, value: u16) -> u16 {
+        if self.index_wide() { value } else { value & 0xFF }
+    }
+
+    // Every bus access costs one cycle
+    fn read_byte<B: Bus>(&mut self, address: u32, memory: &mut B) -> u8 {
+        self.step_cycles += 1;
+        memory.read(address & ADDRESS_MASK)
+    }
+
+    fn write_byte<B: Bus>(&mut self, address: u32, value: u8, memory: &mut B) {
+        self.step_cycles += 1;
+        memory.write(address & ADDRESS_MASK, value);
+    }
+
+    // Internal operation cycle, nothing useful on the bus
+    fn idle(&mut self) {
+        self.step_cycles += 1;
+    }
+
+    fn execute_instruction<B: Bus>(&mut self, op: Op, mode: Mode, memory: &mut B) {
+        match op {
+            Op::LDA => self.lda(mode, memory),
+            Op::LDX => self.ldx(mode, memory),
+            Op::LDY => self.ldy(mode, memory),
+            Op::STA => self.sta(mode, memory),
+            Op::STX => self.stx(mode, memory),
+            Op::STY => self.sty(mode, memory),
+            Op::STZ => self.stz(mode, memory),
+            Op::TAX => self.tax(),
+            Op::TAY => self.tay(),
+            Op::TXA => self.txa(),
+            Op::TYA => self.tya(),
+            Op::TXY => self.txy(),
+            Op::TYX => self.tyx(),
+            Op::TSX => self.tsx(),
+            Op::TXS => self.txs(),
+            Op::TCS => self.tcs(),
+            Op::TSC => self.tsc(),
+            Op::TCD => self.tcd(),
+            Op::TDC => self.tdc(),
+            Op::XBA => self.xba(),
+            Op::MVN => self.block_move(memory, 1),
+            Op::MVP => self.block_move(memory, 0xFFFF),
+            Op::PHA => self.pha(memory),
+            Op::PHX => self.phx(memory),
+            Op::PHY => self.phy(memory),
+            Op::PHB => self.phb(memory),
+            Op::PHD => self.phd(memory),
+            Op::PHK => self.phk(memory),
+            Op::PHP => self.php(memory),
+            Op::PLA => self.pla(memory),
+            Op::PLX => self.plx(memory),
+            Op::PLY => self.ply(memory),
+            Op::PLB => self.plb(memory),
+            Op::PLD => self.pld(memory),
+            Op::PLP => self.plp(memory),
+            Op::PEA => self.pea(memory),
+            Op::PEI => self.pei(memory),
+            Op::PER => self.per(memory),
+            Op::ORA => self.ora(mode, memory),
+            Op::AND => self.and(mode, memory),
+            Op::EOR => self.eor(mode, memory),
+            Op::ADC => self.adc(mode, memory),
+            Op::SBC => self.sbc(mode, memory),
+            Op::CMP => self.cmp(mode, memory),
+            Op::CPX => self.cpx(mode, memory),
+            Op::CPY => self.cpy(mode, memory),
+            Op::BIT => self.bit(mode, memory),
+            Op::ASL => self.modify_operand(mode, memory, WDC65C816::asl),
+            Op::LSR => self.modify_operand(mode, memory, WDC65C816::lsr),
+            Op::ROL => self.modify_operand(mode, memory, WDC65C816::rol),
+            Op::ROR => self.modify_operand(mode, memory, WDC65C816::ror),
+            Op::INC => self.modify_operand(mode, memory, WDC65C816::inc),
+            Op::DEC => self.modify_operand(mode, memory, WDC65C816::dec),
+            Op::TSB => self.modify_operand(mode, memory, WDC65C816::tsb),
+            Op::TRB => self.modify_operand(mode, memory, WDC65C816::trb),
+            Op::INX => self.inx(),
+            Op::INY => self.iny(),
+            Op::DEX => self.dex(),
+            Op::DEY => self.dey(),
+            Op::BPL => self.branch(memory, self.regP & NEGATIVE == 0),
+            Op::BMI => self.branch(memory, self.regP & NEGATIVE != 0),
+            Op::BVC => self.branch(memory, self.regP & OVERFLOW == 0),
+            Op::BVS => self.branch(memory, self.regP & OVERFLOW != 0),
+            Op::BCC => self.branch(memory, self.regP & CARRY == 0),
+            Op::BCS => self.branch(memory, self.regP & CARRY != 0),
+            Op::BNE => self.branch(memory, self.regP & ZERO == 0),
+            Op::BEQ => self.branch(memory, self.regP & ZERO != 0),
+            Op::BRA => self.branch(memory, true),
+            Op::BRL => self.brl(memory),
+            Op::JMP => self.jmp(mode, memory),
+            Op::JML => self.jml(mode, memory),
+            Op::JSR => self.jsr(mode, memory),
+            Op::JSL => self.jsl(memory),
+            Op::RTS => self.rts(memory),
+            Op::RTL => self.rtl(memory),
+            Op::RTI => self.rti(memory),
+            Op::BRK => self.brk(memory),
+            Op::COP => self.cop(memory),
+            Op::CLC => self.change_flag(CARRY, false),
+            Op::SEC => self.change_flag(CARRY, true),
+            Op::CLI => self.change_flag(IRQ_DISABLE, false),
+            Op::SEI => self.change_flag(IRQ_DISABLE, true),
+            Op::CLD => self.change_flag(DECIMAL, false),
+            Op::SED => self.change_flag(DECIMAL, true),
+            Op::CLV => self.change_flag(OVERFLOW, false),
+            Op::REP => self.rep(memory),
+            Op::SEP => self.sep(memory),
+            Op::XCE => self.xce(),
+            Op::WAI => self.wai(),
+            Op::STP => self.stp(),
+            Op::WDM => self.wdm(memory),
+            Op::NOP => self.idle(),
+        }
+    }
+}
+
+impl CPU for WDC65C816 {
+    // Opcode and operand bytes come from the program bank, PC wraps within it
+    fn fetch<B: Bus>(&mut self, memory : &mut B) -> u8 {
+        let res = self.read_byte(self.program_address(), memory);
+        self.regPC = self.regPC.wrapping_add(1);
+        res
+    }
+
+    // 16-bit addresses are in bank 0, where the vectors, stack and direct page live
+    fn read<B: Bus>(&mut self, address: u16, memory : &mut B) -> u8 {
+        memory.read(address as u32)
+    }
+
+    fn write<B: Bus>(&mut self, address: u16, value: u8, memory : &mut B) {
+        memory.write(address as u32, value);
+    }
+
+    fn step<B: Bus>(&mut self, memory : &mut B) -> Result<StepInfo, ExecutionError> {
+        if self.stopped {
+            return Err(ExecutionError::Stopped { pc: self.program_address() });
+        }
+        let address : u32 = self.program_address();
+        if self.waiting && !self.wake_up() {
+            self.total_cycles += 1;
+            return Ok(StepInfo { address, opcode : None, cycles : 1 });
+        }
+
+        self.step_cycles = 0;
+        let mut opcode : Option<u8> = None;
+        if !self.poll_interrupts(memory) {
+            let instruction : u8 = self.fetch(memory);
+            let (op, mode) = OPCODES[instruction as usize];
+            self.execute_instruction(op, mode, memory);
+            opcode = Some(instruction);
+        }
+        self.total_cycles += self.step_cycles as u64;
+        if let Some(address) = memory.fault() {
+            return Err(ExecutionError::BusFault { address });
+        }
+
+        if self.stopped {
+            Err(ExecutionError::Stopped { pc: self.program_address() })
+        } else {
+            Ok(StepInfo { address, opcode, cycles : self.step_cycles })
+        }
+    }
+
+    // Runs for at least the given budget, the cycles spent past it are
+    // carried over and deducted from the next call
+    fn execute<B: Bus>(&mut self, cycles : u32, memory : &mut B) -> Result<(), ExecutionError> {
+        let mut remaining : i64 = cycles as i64 - self.cycle_debt as i64;
+        self.cycle_debt = 0;
+        while remaining > 0 {
+            remaining -= self.step(memory)?.cycles as i64;
+        }
+        self.cycle_debt = (-remaining) as u32;
+        Ok(())
+    }
+
+    // Reset always returns to emulation mode, PC is loaded from $00FFFC/$00FFFD
+    fn reset<B: Bus>(&mut self, memory : &mut B) {
+        self.reset_sequence(memory);
+        self.total_cycles += 7;
+    }
+
+    fn halted(&self) -> bool {
+        self.stopped
+    }
+
+    fn waiting(&self) -> bool {
+        self.waiting
+    }
+
+    fn total_cycles(&self) -> u64 {
+        self.total_cycles
+    }
+
+    fn irq(&mut self, active: bool) {
+        self.irq_line = active;
+    }
+
+    fn nmi(&mut self, active: bool) {
+        if active && !self.nmi_line {
+            self.nmi_pending = true;
+        }
+        self.nmi_line = active;
+    }
+
+    fn pc(&self) -> u16 {
+        self.regPC
+    }
+
+    fn set_pc(&mut self, pc: u16) {
+        self.regPC = pc;
+    }
+
+    // C is shown whole, in 8-bit mode its high byte is the hidden B accumulator
+    fn registers(&self) -> Vec<Register> {
+        vec![
+            Register { name : "PC", value : self.regPC as u32, bits : 16 },
+            Register { name : "PB", value : self.regPB as u32, bits : 8 },
+            Register { name : "DB", value : self.regDB as u32, bits : 8 },
+            Register { name : "D", value : self.regD as u32, bits : 16 },
+            Register { name : "SP", value : self.regSP as u32, bits : 16 },
+            Register { name : "C", value : self.regA as u32, bits : 16 },
+            Register { name : "X", value : self.regX as u32, bits : 16 },
+            Register { name : "Y", value : self.regY as u32, bits : 16 },
+            Register { name : "P", value : self.regP as u32, bits : 8 },
+            Register { name : "E", value : self.emulation as u32, bits : 1 },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WDC65C816;
+    use crate::cpu::cpu::CPU;
+    use crate::cpu::error::ExecutionError;
+    use crate::cpu::bus::RecordingBus;
+    use crate::cpu::memory::Memory;
+
+    // 16 MiB of memory with the program at $8000 and the processor just out of reset
+    fn setup(program: &[u8]) -> (WDC65C816, RecordingBus) {
+        let mut memory: RecordingBus = RecordingBus::new(Memory::with_size(0x100_0000));
+        for (offset, byte) in program.iter().enumerate() {
+            memory[0x8000 + offset] = *byte;
+        }
+        memory[0xFFFC] = 0x00;
+        memory[0xFFFD] = 0x80;
+        let mut cpu: WDC65C816 = WDC65C816::new();
+        cpu.reset(&mut memory);
+        (cpu, memory)
+    }
+
+    fn run(cpu: &mut WDC65C816, memory: &mut RecordingBus, instructions: u32) {
+        for _ in 0..instructions {
+            cpu.step(memory).unwrap();
+        }
+    }
+
+    #[test]
+    fn stop_reports_program_bank() {
+        // JML $123456, where STP waits
+        let (mut cpu, mut memory) = setup(&[0x5C, 0x56, 0x34, 0x12]);
+        memory[0x12_3456] = 0xDB;
+        cpu.step(&mut memory).unwrap();
+        assert_eq!(cpu.step(&mut memory), Err(ExecutionError::Stopped { pc : 0x12_3457 }));
+        assert_eq!(cpu.step(&mut memory), Err(ExecutionError::Stopped { pc : 0x12_3457 }));
+    }
+
+    #[test]
+    fn xce_switches_between_emulation_and_native_mode() {
+        // CLC, XCE, REP #$30, SEC, XCE
+        let (mut cpu, mut memory) = setup(&[0x18, 0xFB, 0xC2, 0x30, 0x38, 0xFB]);
+        run(&mut cpu, &mut memory, 2);
+        assert!(!cpu.emulation());
+        // The old emulation bit lands in carry
+        assert_eq!(cpu.status() & 0x01, 0x01);
+
+        run(&mut cpu, &mut memory, 1);
+        assert_eq!(cpu.status() & 0x30, 0x00);
+        cpu.regSP = 0x1234;
+
+        run(&mut cpu, &mut memory, 2);
+        assert!(cpu.emulation());
+        assert_eq!(cpu.status() & 0x30, 0x30);
+        assert_eq!(cpu.sp(), 0x0134);
+    }
+
+    #[test]
+    fn setting_x_clears_the_index_high_bytes() {
+        // CLC, XCE, REP #$10, LDX #$1234, LDY #$5678, SEP #$10
+        let (mut cpu, mut memory) = setup(&[0x18, 0xFB, 0xC2, 0x10, 0xA2, 0x34, 0x12, 0xA0, 0x78, 0x56, 0xE2, 0x10]);
+        run(&mut cpu, &mut memory, 5);
+        assert_eq!(cpu.x(), 0x1234);
+        assert_eq!(cpu.y(), 0x5678);
+
+        run(&mut cpu, &mut memory, 1);
+        assert_eq!(cpu.x(), 0x0034);
+        assert_eq!(cpu.y(), 0x0078);
+    }
+
+    #[test]
+    fn rep_and_sep_switch_the_accumulator_width() {
+        // CLC, XCE, REP #$20, LDA #$1234, SEP #$20, LDA #$FF
+        let (mut cpu, mut memory) = setup(&[0x18, 0xFB, 0xC2, 0x20, 0xA9, 0x34, 0x12, 0xE2, 0x20, 0xA9, 0xFF]);
+        run(&mut cpu, &mut memory, 4);
+        assert_eq!(cpu.a(), 0x1234);
+
+        // B is left alone by 8-bit loads
+        run(&mut cpu, &mut memory, 2);
+        assert_eq!(cpu.a(), 0x12FF);
+    }
+
+    #[test]
+    fn mvn_moves_a_block_upwards() {
+        // CLC, XCE, REP #$30, MVN $02,$01
+        let (mut cpu, mut memory) = setup(&[0x18, 0xFB, 0xC2, 0x30, 0x54, 0x02, 0x01]);
+        memory[0x01_1000] = 0x11;
+        memory[0x01_1001] = 0x22;
+        memory[0x01_1002] = 0x33;
+        run(&mut cpu, &mut memory, 3);
+        cpu.regA = 0x0002;
+        cpu.regX = 0x1000;
+        cpu.regY = 0x2000;
+
+        // One byte per pass, the opcode runs again until C wraps
+        run(&mut cpu, &mut memory, 1);
+        assert_eq!(cpu.pc(), 0x8004);
+        run(&mut cpu, &mut memory, 2);
+        assert_eq!(cpu.pc(), 0x8007);
+        assert_eq!((memory[0x02_2000], memory[0x02_2001], memory[0x02_2002]), (0x11, 0x22, 0x33));
+        assert_eq!((cpu.a(), cpu.x(), cpu.y()), (0xFFFF, 0x1003, 0x2003));
+        assert_eq!(cpu.data_bank(), 0x02);
+    }
+
+    #[test]
+    fn mvp_moves_a_block_downwards() {
+        // CLC, XCE, REP #$30, MVP $02,$01
+        let (mut cpu, mut memory) = setup(&[0x18, 0xFB, 0xC2, 0x30, 0x44, 0x02, 0x01]);
+        memory[0x01_1000] = 0x11;
+        memory[0x01_1001] = 0x22;
+        run(&mut cpu, &mut memory, 3);
+        cpu.regA = 0x0001;
+        cpu.regX = 0x1001;
+        cpu.regY = 0x2001;
+
+        run(&mut cpu, &mut memory, 2);
+        assert_eq!(cpu.pc(), 0x8007);
+        assert_eq!((memory[0x02_2000], memory[0x02_2001]), (0x11, 0x22));
+        assert_eq!((cpu.a(), cpu.x(), cpu.y()), (0xFFFF, 0x0FFF, 0x1FFF));
+    }
+
+    #[test]
+    fn direct_page_indexing_wraps_in_emulation_mode() {
+        // LDX #$02, LDA $FF,X
+        let (mut cpu, mut memory) = setup(&[0xA2, 0x02, 0xB5, 0xFF]);
+        memory[0x0001] = 0x42;
+        memory[0x0101] = 0x99;
+        run(&mut cpu, &mut memory, 2);
+        assert_eq!(cpu.a() & 0xFF, 0x42);
+
+        // Native mode runs on into the next page
+        let (mut cpu, mut memory) = setup(&[0x18, 0xFB, 0xA2, 0x02, 0xB5, 0xFF]);
+        memory[0x0001] = 0x42;
+        memory[0x0101] = 0x99;
+        run(&mut cpu, &mut memory, 4);
+        assert_eq!(cpu.a() & 0xFF, 0x99);
+    }
+
+    #[test]
+    fn stack_relative_reads_above_the_stack_pointer() {
+        // PEA $1234, LDA $01,S, LDY #$01, LDA ($01,S),Y
+        let (mut cpu, mut memory) = setup(&[0xF4, 0x34, 0x12, 0xA3, 0x01, 0xA0, 0x01, 0xB3, 0x01]);
+        memory[0x1235] = 0x77;
+        run(&mut cpu, &mut memory, 2);
+        assert_eq!(cpu.a() & 0xFF, 0x34);
+
+        run(&mut cpu, &mut memory, 2);
+        assert_eq!(cpu.a() & 0xFF, 0x77);
+    }
+
+    #[test]
+    fn long_modes_reach_any_bank() {
+        // LDA $123456, LDA [$10], LDX #$06, LDA $123450,X
+        let (mut cpu, mut memory) = setup(&[0xAF, 0x56, 0x34, 0x12, 0xA7, 0x10, 0xA2, 0x06, 0xBF, 0x50, 0x34, 0x12]);
+        memory[0x12_3456] = 0x5A;
+        memory[0x0010] = 0x00;
+        memory[0x0011] = 0x10;
+        memory[0x0012] = 0x7E;
+        memory[0x7E_1000] = 0xA5;
+        run(&mut cpu, &mut memory, 1);
+        assert_eq!(cpu.a() & 0xFF, 0x5A);
+
+        run(&mut cpu, &mut memory, 1);
+        assert_eq!(cpu.a() & 0xFF, 0xA5);
+
+        memory[0x12_3456] = 0x3C;
+        run(&mut cpu, &mut memory, 2);
+        assert_eq!(cpu.a() & 0xFF, 0x3C);
+    }
+
+    #[test]
+    fn sixteen_bit_decimal_arithmetic() {
+        // CLC, XCE, REP #$20, SED, CLC, LDA #$1999, ADC #$0001, SEC, SBC #$0002
+        let (mut cpu, mut memory) = setup(&[0x18, 0xFB, 0xC2, 0x20, 0xF8, 0x18, 0xA9, 0x99, 0x19, 0x69, 0x01, 0x00, 0x38, 0xE9, 0x02, 0x00]);
+        run(&mut cpu, &mut memory, 7);
+        assert_eq!(cpu.a(), 0x2000);
+        assert_eq!(cpu.status() & 0x01, 0x00);
+
+        run(&mut cpu, &mut memory, 2);
+        assert_eq!(cpu.a(), 0x1998);
+        assert_eq!(cpu.status() & 0x01, 0x01);
+
+        // 9999 + 1 carries out of all four digits
+        cpu.regA = 0x9999;
+        cpu.regPC = 0x8009;
+        cpu.regP &= !0x01;
+        run(&mut cpu, &mut memory, 1);
+        assert_eq!(cpu.a(), 0x0000);
+        assert_eq!(cpu.status() & 0x03, 0x03);
+    }
+
+    #[test]
+    fn wide_read_modify_write_writes_the_high_byte_first() {
+        // CLC, XCE, REP #$20, INC $2000
+        let (mut cpu, mut memory) = setup(&[0x18, 0xFB, 0xC2, 0x20, 0xEE, 0x00, 0x20]);
+        memory[0x2000] = 0xFF;
+        memory[0x2001] = 0x12;
+        run(&mut cpu, &mut memory, 3);
+        memory.accesses.clear();
+
+        assert_eq!(cpu.step(&mut memory).unwrap().cycles, 8);
+        assert_eq!(memory.writes(), vec![(0x2001, 0x13), (0x2000, 0x00)]);
+    }
+}
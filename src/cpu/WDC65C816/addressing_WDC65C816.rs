@@ -0,0 +1,277 @@
+use crate::cpu::bus::Bus;
+
+use crate::cpu::cpu::CPU;
+
+use super::{WDC65C816, ADDRESS_MASK};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Mode {
+    Implied,
+    Accumulator,
+    // One or two bytes depending on the register width
+    Immediate,
+    // d, offset from the direct page register in bank 0
+    Direct,
+    DirectX,
+    DirectY,
+    // (d), 16-bit pointer in the direct page to data in the data bank
+    DirectIndirect,
+    // [d], 24-bit pointer in the direct page
+    DirectIndirectLong,
+    // (d,X)
+    DirectIndexedIndirect,
+    // (d),Y
+    DirectIndirectIndexed,
+    // [d],Y
+    DirectIndirectLongIndexed,
+    // d,S, offset from the stack pointer in bank 0
+    StackRelative,
+    // (d,S),Y
+    StackRelativeIndirectIndexed,
+    // a, in the data bank, or the program bank for JMP and JSR
+    Absolute,
+    AbsoluteX,
+    AbsoluteY,
+    // al, full 24-bit address
+    AbsoluteLong,
+    AbsoluteLongX,
+    // (a), JMP only, the pointer is in bank 0
+    AbsoluteIndirect,
+    // (a,X), JMP and JSR only, the pointer is in the program bank
+    AbsoluteIndexedIndirect,
+    // [a], JML only
+    AbsoluteIndirectLong,
+    Relative,
+    RelativeLong,
+    // Destination and source bank bytes of MVN and MVP
+    BlockMove,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Access {
+    Read,
+    Write,
+    Modify,
+}
+
+// Effective address of a data operand. Immediate, direct page and stack operands
+// wrap within their bank, the others run on through the 24-bit address space
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Operand {
+    address: u32,
+    bank_wrap: bool,
+}
+
+impl Operand {
+    fn in_bank(address: u32) -> Self {
+        Operand { address, bank_wrap : true }
+    }
+
+    fn linear(address: u32) -> Self {
+        Operand { address : address & ADDRESS_MASK, bank_wrap : false }
+    }
+
+    // Address of the high byte of a 16-bit operand
+    fn next(&self) -> u32 {
+        if self.bank_wrap {
+            (self.address & 0xFF_0000) | (self.address.wrapping_add(1) & 0xFFFF)
+        } else {
+            self.address.wrapping_add(1) & ADDRESS_MASK
+        }
+    }
+}
+
+impl WDC65C816 {
+    pub(super) fn fetch_word<B: Bus>(&mut self, memory: &mut B) -> u16 {
+        let low_byte: u8 = self.fetch(memory);
+        let high_byte: u8 = self.fetch(memory);
+        ((high_byte as u16) << 8) | (low_byte as u16)
+    }
+
+    pub(super) fn fetch_long<B: Bus>(&mut self, memory: &mut B) -> u32 {
+        let address: u16 = self.fetch_word(memory);
+        let bank: u8 = self.fetch(memory);
+        ((bank as u32) << 16) | address as u32
+    }
+
+    // Reads a 16-bit pointer from bank 0, wrapping within it
+    pub(super) fn read_vector<B: Bus>(&mut self, address: u16, memory: &mut B) -> u16 {
+        let low_byte: u8 = self.read_byte(address as u32, memory);
+        let high_byte: u8 = self.read_byte(address.wrapping_add(1) as u32, memory);
+        ((high_byte as u16) << 8) | (low_byte as u16)
+    }
+
+    // Direct page offsets take an extra cycle unless the direct page is page aligned
+    pub(super) fn fetch_direct<B: Bus>(&mut self, memory: &mut B) -> u16 {
+        let offset: u16 = self.fetch(memory) as u16;
+        if self.regD & 0xFF != 0 {
+            self.idle();
+        }
+        offset
+    }
+
+    // In emulation mode an aligned direct page behaves like the 6502 zero page,
+    // indexing wraps within the page
+    pub(super) fn direct_address(&self, offset: u16, index: u16) -> u32 {
+        if self.emulation && self.regD & 0xFF == 0 {
+            (self.regD | (offset.wrapping_add(index) & 0xFF)) as u32
+        } else {
+            self.regD.wrapping_add(offset).wrapping_add(index) as u32
+        }
+    }
+
+    pub(super) fn read_direct_pointer<B: Bus>(&mut self, offset: u16, index: u16, memory: &mut B) -> u16 {
+        let low_byte: u8 = self.read_byte(self.direct_address(offset, index), memory);
+        let high_byte: u8 = self.read_byte(self.direct_address(offset, index.wrapping_add(1)), memory);
+        ((high_byte as u16) << 8) | (low_byte as u16)
+    }
+
+    fn read_direct_long<B: Bus>(&mut self, offset: u16, memory: &mut B) -> u32 {
+        let address: u16 = self.read_direct_pointer(offset, 0, memory);
+        let bank: u8 = self.read_byte(self.direct_address(offset, 2), memory);
+        ((bank as u32) << 16) | address as u32
+    }
+
+    fn data_address(&self, offset: u16, index: u16) -> u32 {
+        (((self.regDB as u32) << 16) + offset as u32 + index as u32) & ADDRESS_MASK
+    }
+
+    // Indexing carries into the next bank. The fixup cycle is taken when the
+    // index crosses a page, with 16-bit index registers, and always by writes
+    fn indexed(&mut self, base: u16, index: u16, access: Access) -> Operand {
+        let page_crossed: bool = (base as u32 + index as u32) >> 8 != (base as u32) >> 8;
+        if access != Access::Read || self.index_wide() || page_crossed {
+            self.idle();
+        }
+        Operand::linear(self.data_address(base, index))
+    }
+
+    pub(super) fn resolve<B: Bus>(&mut self, mode: Mode, wide: bool, access: Access, memory: &mut B) -> Operand {
+        match mode {
+            Mode::Immediate => {
+                let address: u32 = self.program_address();
+                self.regPC = self.regPC.wrapping_add(if wide { 2 } else { 1 });
+                Operand::in_bank(address)
+            }
+            Mode::Direct => {
+                let offset: u16 = self.fetch_direct(memory);
+                Operand::in_bank(self.direct_address(offset, 0))
+            }
+            Mode::DirectX => {
+                let offset: u16 = self.fetch_direct(memory);
+                self.idle();
+                Operand::in_bank(self.direct_address(offset, self.regX))
+            }
+            Mode::DirectY => {
+                let offset: u16 = self.fetch_direct(memory);
+                self.idle();
+                Operand::in_bank(self.direct_address(offset, self.regY))
+            }
+            Mode::DirectIndirect => {
+                let offset: u16 = self.fetch_direct(memory);
+                let pointer: u16 = self.read_direct_pointer(offset, 0, memory);
+                Operand::linear(self.data_address(pointer, 0))
+            }
+            Mode::DirectIndirectLong => {
+                let offset: u16 = self.fetch_direct(memory);
+                Operand::linear(self.read_direct_long(offset, memory))
+            }
+            Mode::DirectIndexedIndirect => {
+                let offset: u16 = self.fetch_direct(memory);
+                self.idle();
+                let pointer: u16 = self.read_direct_pointer(offset, self.regX, memory);
+                Operand::linear(self.data_address(pointer, 0))
+            }
+            Mode::DirectIndirectIndexed => {
+                let offset: u16 = self.fetch_direct(memory);
+                let pointer: u16 = self.read_direct_pointer(offset, 0, memory);
+                self.indexed(pointer, self.regY, access)
+            }
+            Mode::DirectIndirectLongIndexed => {
+                let offset: u16 = self.fetch_direct(memory);
+                let pointer: u32 = self.read_direct_long(offset, memory);
+                Operand::linear(pointer + self.regY as u32)
+            }
+            Mode::StackRelative => {
+                let offset: u16 = self.fetch(memory) as u16;
+                self.idle();
+                Operand::in_bank(self.regSP.wrapping_add(offset) as u32)
+            }
+            Mode::StackRelativeIndirectIndexed => {
+                let offset: u16 = self.fetch(memory) as u16;
+                self.idle();
+                let pointer: u16 = self.read_vector(self.regSP.wrapping_add(offset), memory);
+                self.idle();
+                Operand::linear(self.data_address(pointer, self.regY))
+            }
+            Mode::Absolute => {
+                let address: u16 = self.fetch_word(memory);
+                Operand::linear(self.data_address(address, 0))
+            }
+            Mode::AbsoluteX => {
+                let address: u16 = self.fetch_word(memory);
+                self.indexed(address, self.regX, access)
+            }
+            Mode::AbsoluteY => {
+                let address: u16 = self.fetch_word(memory);
+                self.indexed(address, self.regY, access)
+            }
+            Mode::AbsoluteLong => {
+                let address: u32 = self.fetch_long(memory);
+                Operand::linear(address)
+            }
+            Mode::AbsoluteLongX => {
+                let address: u32 = self.fetch_long(memory);
+                Operand::linear(address + self.regX as u32)
+            }
+            _ => unreachable!("{:?} has no data operand", mode),
+        }
+    }
+
+    pub(super) fn read_data<B: Bus>(&mut self, operand: Operand, wide: bool, memory: &mut B) -> u16 {
+        let low_byte: u8 = self.read_byte(operand.address, memory);
+        if !wide {
+            return low_byte as u16;
+        }
+        let high_byte: u8 = self.read_byte(operand.next(), memory);
+        ((high_byte as u16) << 8) | (low_byte as u16)
+    }
+
+    pub(super) fn write_data<B: Bus>(&mut self, operand: Operand, value: u16, wide: bool, memory: &mut B) {
+        self.write_byte(operand.address, value as u8, memory);
+        if wide {
+            self.write_byte(operand.next(), (value >> 8) as u8, memory);
+        }
+    }
+
+    pub(super) fn read_operand<B: Bus>(&mut self, mode: Mode, wide: bool, memory: &mut B) -> u16 {
+        let operand: Operand = self.resolve(mode, wide, Access::Read, memory);
+        self.read_data(operand, wide, memory)
+    }
+
+    pub(super) fn write_operand<B: Bus>(&mut self, mode: Mode, value: u16, wide: bool, memory: &mut B) {
+        let operand: Operand = self.resolve(mode, wide, Access::Write, memory);
+        self.write_data(operand, value, wide, memory);
+    }
+
+    // Read-modify-write at the accumulator width, one internal cycle between
+    // the read and the write. Accumulator mode takes that cycle alone.
+    // A 16-bit result is written high byte first
+    pub(super) fn modify_operand<B: Bus>(&mut self, mode: Mode, memory: &mut B, operation: fn(&mut WDC65C816, u16) -> u16) {
+        let wide: bool = self.memory_wide();
+        if mode == Mode::Accumulator {
+            self.idle();
+            let result: u16 = operation(self, self.accumulator());
+            self.set_accumulator(result);
+            return;
+        }
+        let operand: Operand = self.resolve(mode, wide, Access::Modify, memory);
+        let value: u16 = self.read_data(operand, wide, memory);
+        self.idle();
+        let result: u16 = operation(self, value);
+        if wide {
+            self.write_byte(operand.next(), (result >> 8) as u8, memory);
+        }
+        self.write_byte(operand.address, result as u8, memory);
+    }
+}
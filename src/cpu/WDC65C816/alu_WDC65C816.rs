@@ -0,0 +1,203 @@
+use crate::cpu::bus::Bus;
+
+use super::addressing_WDC65C816::Mode;
+use super::{WDC65C816, CARRY, DECIMAL, NEGATIVE, OVERFLOW, ZERO};
+
+impl WDC65C816 {
+    // Mask and sign bit of the accumulator at the current M width
+    fn memory_bits(&self) -> (u16, u16) {
+        if self.memory_wide() { (0xFFFF, 0x8000) } else { (0x00FF, 0x0080) }
+    }
+
+    // Logical operations
+    pub(super) fn ora<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        let value: u16 = self.read_operand(mode, self.memory_wide(), memory);
+        self.set_accumulator(self.regA | value);
+        self.set_nz(self.regA, self.memory_wide());
+    }
+
+    pub(super) fn and<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        let value: u16 = self.read_operand(mode, self.memory_wide(), memory);
+        self.set_accumulator(self.regA & value);
+        self.set_nz(self.regA, self.memory_wide());
+    }
+
+    pub(super) fn eor<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        let value: u16 = self.read_operand(mode, self.memory_wide(), memory);
+        self.set_accumulator(self.regA ^ value);
+        self.set_nz(self.regA, self.memory_wide());
+    }
+
+    // BIT immediate only changes Z, the memory forms copy the top two bits into N and V
+    pub(super) fn bit<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        let value: u16 = self.read_operand(mode, self.memory_wide(), memory);
+        let (_, sign) = self.memory_bits();
+        if mode != Mode::Immediate {
+            self.set_flag(NEGATIVE, value & sign != 0);
+            self.set_flag(OVERFLOW, value & (sign >> 1) != 0);
+        }
+        self.set_flag(ZERO, self.accumulator() & value == 0);
+    }
+
+    // Add with carry
+    pub(super) fn adc<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        let value: u16 = self.read_operand(mode, self.memory_wide(), memory);
+        self.add(value, false);
+    }
+
+    // Subtract with carry, an addition of the complemented operand
+    pub(super) fn sbc<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        let value: u16 = self.read_operand(mode, self.memory_wide(), memory);
+        self.add(!value, true);
+    }
+
+    // Binary or BCD addition at the current M width. BCD runs digit by digit
+    // with the carry rippling up, V is taken before the top digit is adjusted
+    // and N and Z come from the decimal result
+    fn add(&mut self, value: u16, subtract: bool) {
+        let (mask, sign) = self.memory_bits();
+        let a: u32 = self.accumulator() as u32;
+        let value: u32 = (value & mask) as u32;
+        let sign: u32 = sign as u32;
+        let mut carry: u32 = (self.regP & CARRY) as u32;
+        let mut result: u32;
+        let overflow: bool;
+
+        if self.regP & DECIMAL == 0 {
+            result = a + value + carry;
+            carry = (result > mask as u32) as u32;
+            overflow = !(a ^ value) & (a ^ result) & sign != 0;
+        } else {
+            let digits: u32 = if self.memory_wide() { 4 } else { 2 };
+            let mut unadjusted: u32 = 0;
+            result = 0;
+            for digit in 0..digits {
+                let shift: u32 = digit * 4;
+                let sum: u32 = ((a >> shift) & 0xF) + ((value >> shift) & 0xF) + carry;
+                unadjusted = result | (sum << shift);
+                let (adjusted, digit_carry) = if subtract {
+                    if sum > 0xF { (sum & 0xF, 1) } else { (sum.wrapping_sub(0x06) & 0xF, 0) }
+                } else if sum > 0x09 {
+                    ((sum + 0x06) & 0xF, 1)
+                } else {
+                    (sum, 0)
+                };
+                result |= adjusted << shift;
+                carry = digit_carry;
+            }
+            overflow = !(a ^ value) & (a ^ unadjusted) & sign != 0;
+        }
+
+        self.set_flag(CARRY, carry != 0);
+        self.set_flag(OVERFLOW, overflow);
+        self.set_accumulator(result as u16);
+        self.set_nz(self.regA, self.memory_wide());
+    }
+
+    // Compare
+    fn compare(&mut self, register: u16, value: u16, wide: bool) {
+        let mask: u16 = if wide { 0xFFFF } else { 0x00FF };
+        let register: u16 = register & mask;
+        self.set_flag(CARRY, register >= value);
+        self.set_nz(register.wrapping_sub(value), wide);
+    }
+
+    pub(super) fn cmp<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        let value: u16 = self.read_operand(mode, self.memory_wide(), memory);
+        self.compare(self.regA, value, self.memory_wide());
+    }
+
+    pub(super) fn cpx<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        let value: u16 = self.read_operand(mode, self.index_wide(), memory);
+        self.compare(self.regX, value, self.index_wide());
+    }
+
+    pub(super) fn cpy<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        let value: u16 = self.read_operand(mode, self.index_wide(), memory);
+        self.compare(self.regY, value, self.index_wide());
+    }
+
+    // Read-modify-write operations at the accumulator width, applied by modify_operand
+    pub(super) fn asl(&mut self, value: u16) -> u16 {
+        let (mask, sign) = self.memory_bits();
+        self.set_flag(CARRY, value & sign != 0);
+        let result: u16 = (value << 1) & mask;
+        self.set_nz(result, self.memory_wide());
+        result
+    }
+
+    pub(super) fn lsr(&mut self, value: u16) -> u16 {
+        self.set_flag(CARRY, value & 0x01 != 0);
+        let result: u16 = value >> 1;
+        self.set_nz(result, self.memory_wide());
+        result
+    }
+
+    pub(super) fn rol(&mut self, value: u16) -> u16 {
+        let (mask, sign) = self.memory_bits();
+        let carry: u16 = (self.regP & CARRY) as u16;
+        self.set_flag(CARRY, value & sign != 0);
+        let result: u16 = ((value << 1) | carry) & mask;
+        self.set_nz(result, self.memory_wide());
+        result
+    }
+
+    pub(super) fn ror(&mut self, value: u16) -> u16 {
+        let (_, sign) = self.memory_bits();
+        let carry: bool = self.regP & CARRY != 0;
+        self.set_flag(CARRY, value & 0x01 != 0);
+        let result: u16 = if carry { (value >> 1) | sign } else { value >> 1 };
+        self.set_nz(result, self.memory_wide());
+        result
+    }
+
+    pub(super) fn inc(&mut self, value: u16) -> u16 {
+        let (mask, _) = self.memory_bits();
+        let result: u16 = value.wrapping_add(1) & mask;
+        self.set_nz(result, self.memory_wide());
+        result
+    }
+
+    pub(super) fn dec(&mut self, value: u16) -> u16 {
+        let (mask, _) = self.memory_bits();
+        let result: u16 = value.wrapping_sub(1) & mask;
+        self.set_nz(result, self.memory_wide());
+        result
+    }
+
+    // Test and set/reset bits, Z reports the bits the accumulator had in common with memory
+    pub(super) fn tsb(&mut self, value: u16) -> u16 {
+        self.set_flag(ZERO, self.accumulator() & value == 0);
+        value | self.accumulator()
+    }
+
+    pub(super) fn trb(&mut self, value: u16) -> u16 {
+        self.set_flag(ZERO, self.accumulator() & value == 0);
+        value & !self.accumulator()
+    }
+
+    // Index increment and decrement
+    pub(super) fn inx(&mut self) {
+        self.idle();
+        self.regX = self.index(self.regX.wrapping_add(1));
+        self.set_nz(self.regX, self.index_wide());
+    }
+
+    pub(super) fn iny(&mut self) {
+        self.idle();
+        self.regY = self.index(self.regY.wrapping_add(1));
+        self.set_nz(self.regY, self.index_wide());
+    }
+
+    pub(super) fn dex(&mut self) {
+        self.idle();
+        self.regX = self.index(self.regX.wrapping_sub(1));
+        self.set_nz(self.regX, self.index_wide());
+    }
+
+    pub(super) fn dey(&mut self) {
+        self.idle();
+        self.regY = self.index(self.regY.wrapping_sub(1));
+        self.set_nz(self.regY, self.index_wide());
+    }
+}
@@ -0,0 +1,251 @@
+use crate::cpu::bus::Bus;
+
+use crate::cpu::cpu::CPU;
+
+use super::addressing_WDC65C816::Mode;
+use super::{WDC65C816, CARRY, DECIMAL, INDEX_8, IRQ_DISABLE, MEMORY_8};
+
+// Vectors are in bank 0, native mode has its own set below the 6502 ones
+const NATIVE_COP_VECTOR: u16 = 0xFFE4;
+const NATIVE_BRK_VECTOR: u16 = 0xFFE6;
+const NATIVE_NMI_VECTOR: u16 = 0xFFEA;
+const NATIVE_IRQ_VECTOR: u16 = 0xFFEE;
+const EMULATION_COP_VECTOR: u16 = 0xFFF4;
+const EMULATION_NMI_VECTOR: u16 = 0xFFFA;
+const RESET_VECTOR: u16 = 0xFFFC;
+const EMULATION_IRQ_VECTOR: u16 = 0xFFFE;
+
+impl WDC65C816 {
+    // Native mode also pushes the program bank. In emulation mode BRK and IRQ
+    // share a vector and only the pushed break flag tells them apart
+    fn interrupt<B: Bus>(&mut self, memory: &mut B, vector: u16, break_command: bool) {
+        if !self.emulation {
+            self.push(self.regPB, memory);
+        }
+        self.push_word(self.regPC, memory);
+        let status: u8 = if self.emulation && !break_command { self.regP & !INDEX_8 } else { self.regP };
+        self.push(status, memory);
+        self.regP = (self.regP | IRQ_DISABLE) & !DECIMAL;
+        self.regPB = 0;
+        self.regPC = self.read_vector(vector, memory);
+    }
+
+    // Reset goes back to emulation mode with 8-bit registers, direct page and
+    // banks at 0. SP is decremented three times within page $01 without writes
+    pub(super) fn reset_sequence<B: Bus>(&mut self, memory: &mut B) {
+        self.emulation = true;
+        self.regD = 0;
+        self.regDB = 0;
+        self.regPB = 0;
+        self.set_sp(self.regSP.wrapping_sub(3));
+        self.set_status((self.regP | IRQ_DISABLE | MEMORY_8 | INDEX_8) & !DECIMAL);
+        self.nmi_pending = false;
+        self.stopped = false;
+        self.waiting = false;
+
+        let low_byte: u8 = self.read(RESET_VECTOR, memory);
+        let high_byte: u8 = self.read(RESET_VECTOR.wrapping_add(1), memory);
+        self.regPC = ((high_byte as u16) << 8) | (low_byte as u16);
+    }
+
+    // Services a pending interrupt before the next opcode fetch, NMI takes priority over IRQ.
+    // The opcode is read and discarded, then one internal cycle before the pushes
+    pub(super) fn poll_interrupts<B: Bus>(&mut self, memory: &mut B) -> bool {
+        let vector: u16 = if self.nmi_pending {
+            self.nmi_pending = false;
+            if self.emulation { EMULATION_NMI_VECTOR } else { NATIVE_NMI_VECTOR }
+        } else if self.irq_line && self.regP & IRQ_DISABLE == 0 {
+            if self.emulation { EMULATION_IRQ_VECTOR } else { NATIVE_IRQ_VECTOR }
+        } else {
+            return false;
+        };
+        self.read_byte(self.program_address(), memory);
+        self.idle();
+        self.interrupt(memory, vector, false);
+        true
+    }
+
+    // WAI resumes on any interrupt line, even a masked IRQ, which then just continues after the WAI
+    pub(super) fn wake_up(&mut self) -> bool {
+        if self.nmi_pending || self.irq_line {
+            self.waiting = false;
+        }
+        !self.waiting
+    }
+
+    // Break and coprocessor, the signature byte after the opcode is skipped by the return address
+    pub(super) fn brk<B: Bus>(&mut self, memory: &mut B) {
+        self.fetch(memory);
+        let vector: u16 = if self.emulation { EMULATION_IRQ_VECTOR } else { NATIVE_BRK_VECTOR };
+        self.interrupt(memory, vector, true);
+    }
+
+    pub(super) fn cop<B: Bus>(&mut self, memory: &mut B) {
+        self.fetch(memory);
+        let vector: u16 = if self.emulation { EMULATION_COP_VECTOR } else { NATIVE_COP_VECTOR };
+        self.interrupt(memory, vector, true);
+    }
+
+    // Return from interrupt, native mode also pulls the program bank
+    pub(super) fn rti<B: Bus>(&mut self, memory: &mut B) {
+        self.idle();
+        self.idle();
+        let status: u8 = self.pull(memory);
+        self.set_status(status);
+        self.regPC = self.pull_word(memory);
+        if !self.emulation {
+            self.regPB = self.pull(memory);
+        }
+    }
+
+    // Branches stay in the program bank. A taken branch costs one more cycle,
+    // and in emulation mode another one if the target is on a different page
+    pub(super) fn branch<B: Bus>(&mut self, memory: &mut B, condition: bool) {
+        let offset: i8 = self.fetch(memory) as i8;
+        if !condition {
+            return;
+        }
+        self.idle();
+        let base_address: u16 = self.regPC;
+        self.regPC = base_address.wrapping_add(offset as u16);
+        if self.emulation && (base_address & 0xFF00) != (self.regPC & 0xFF00) {
+            self.idle();
+        }
+    }
+
+    pub(super) fn brl<B: Bus>(&mut self, memory: &mut B) {
+        let offset: u16 = self.fetch_word(memory);
+        self.idle();
+        self.regPC = self.regPC.wrapping_add(offset);
+    }
+
+    // Jumps within the program bank
+    pub(super) fn jmp<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        let address: u16 = self.fetch_word(memory);
+        self.regPC = match mode {
+            Mode::Absolute => address,
+            // Unlike the NMOS 6502, a pointer at $xxFF is read across the page boundary
+            Mode::AbsoluteIndirect => self.read_vector(address, memory),
+            Mode::AbsoluteIndexedIndirect => {
+                self.idle();
+                self.read_program_pointer(address.wrapping_add(self.regX), memory)
+            }
+            _ => unreachable!("JMP has no {:?} form", mode),
+        };
+    }
+
+    // Long jumps load the program bank as well
+    pub(super) fn jml<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        let target: u32 = match mode {
+            Mode::AbsoluteLong => self.fetch_long(memory),
+            Mode::AbsoluteIndirectLong => {
+                let address: u16 = self.fetch_word(memory);
+                let pointer: u16 = self.read_vector(address, memory);
+                let bank: u8 = self.read_byte(address.wrapping_add(2) as u32, memory);
+                ((bank as u32) << 16) | pointer as u32
+            }
+            _ => unreachable!("JML has no {:?} form", mode),
+        };
+        self.regPB = (target >> 16) as u8;
+        self.regPC = target as u16;
+    }
+
+    // JSR pushes the address of its last byte, RTS adds one back
+    pub(super) fn jsr<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        match mode {
+            Mode::Absolute => {
+                let address: u16 = self.fetch_word(memory);
+                self.idle();
+                self.push_word(self.regPC.wrapping_sub(1), memory);
+                self.regPC = address;
+            }
+            // The return address is pushed between the two operand bytes
+            Mode::AbsoluteIndexedIndirect => {
+                let low_byte: u8 = self.fetch(memory);
+                self.push_word(self.regPC, memory);
+                let high_byte: u8 = self.fetch(memory);
+                self.idle();
+                let address: u16 = ((high_byte as u16) << 8) | (low_byte as u16);
+                self.regPC = self.read_program_pointer(address.wrapping_add(self.regX), memory);
+            }
+            _ => unreachable!("JSR has no {:?} form", mode),
+        }
+    }
+
+    pub(super) fn jsl<B: Bus>(&mut self, memory: &mut B) {
+        let address: u16 = self.fetch_word(memory);
+        self.push(self.regPB, memory);
+        self.idle();
+        let bank: u8 = self.fetch(memory);
+        self.push_word(self.regPC.wrapping_sub(1), memory);
+        self.regPB = bank;
+        self.regPC = address;
+    }
+
+    pub(super) fn rts<B: Bus>(&mut self, memory: &mut B) {
+        self.idle();
+        self.idle();
+        self.regPC = self.pull_word(memory).wrapping_add(1);
+        self.idle();
+    }
+
+    pub(super) fn rtl<B: Bus>(&mut self, memory: &mut B) {
+        self.idle();
+        self.idle();
+        self.regPC = self.pull_word(memory).wrapping_add(1);
+        self.regPB = self.pull(memory);
+    }
+
+    fn read_program_pointer<B: Bus>(&mut self, address: u16, memory: &mut B) -> u16 {
+        let bank: u32 = (self.regPB as u32) << 16;
+        let low_byte: u8 = self.read_byte(bank | address as u32, memory);
+        let high_byte: u8 = self.read_byte(bank | address.wrapping_add(1) as u32, memory);
+        ((high_byte as u16) << 8) | (low_byte as u16)
+    }
+
+    // Single flag set and clear instructions
+    pub(super) fn change_flag(&mut self, flag: u8, value: bool) {
+        self.idle();
+        self.set_flag(flag, value);
+    }
+
+    // Reset and set the status bits given by the operand, this is how M and X are switched
+    pub(super) fn rep<B: Bus>(&mut self, memory: &mut B) {
+        let mask: u8 = self.fetch(memory);
+        self.idle();
+        self.set_status(self.regP & !mask);
+    }
+
+    pub(super) fn sep<B: Bus>(&mut self, memory: &mut B) {
+        let mask: u8 = self.fetch(memory);
+        self.idle();
+        self.set_status(self.regP | mask);
+    }
+
+    // Exchange carry and emulation, the only way in and out of native mode
+    pub(super) fn xce(&mut self) {
+        self.idle();
+        let carry: bool = self.regP & CARRY != 0;
+        self.set_flag(CARRY, self.emulation);
+        self.set_emulation(carry);
+    }
+
+    // Wait for an interrupt with the clock running
+    pub(super) fn wai(&mut self) {
+        self.idle();
+        self.idle();
+        self.waiting = true;
+    }
+
+    // Stop the clock until reset
+    pub(super) fn stp(&mut self) {
+        self.idle();
+        self.idle();
+        self.stopped = true;
+    }
+
+    // Reserved for future expansion, a two byte NOP
+    pub(super) fn wdm<B: Bus>(&mut self, memory: &mut B) {
+        self.fetch(memory);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::cpu::bus::Bus;
+
+use super::WDC65C816;
+
+impl WDC65C816 {
+    // The stack is in bank 0 and grows downwards, SP points to the next free slot.
+    // Emulation mode keeps it in page $01
+    pub(super) fn push<B: Bus>(&mut self, value: u8, memory: &mut B) {
+        self.write_byte(self.regSP as u32, value, memory);
+        self.set_sp(self.regSP.wrapping_sub(1));
+    }
+
+    pub(super) fn pull<B: Bus>(&mut self, memory: &mut B) -> u8 {
+        self.set_sp(self.regSP.wrapping_add(1));
+        self.read_byte(self.regSP as u32, memory)
+    }
+
+    pub(super) fn push_word<B: Bus>(&mut self, value: u16, memory: &mut B) {
+        self.push((value >> 8) as u8, memory);
+        self.push(value as u8, memory);
+    }
+
+    pub(super) fn pull_word<B: Bus>(&mut self, memory: &mut B) -> u16 {
+        let low_byte: u8 = self.pull(memory);
+        let high_byte: u8 = self.pull(memory);
+        ((high_byte as u16) << 8) | (low_byte as u16)
+    }
+
+    fn push_sized<B: Bus>(&mut self, value: u16, wide: bool, memory: &mut B) {
+        self.idle();
+        if wide {
+            self.push_word(value, memory);
+        } else {
+            self.push(value as u8, memory);
+        }
+    }
+
+    fn pull_sized<B: Bus>(&mut self, wide: bool, memory: &mut B) -> u16 {
+        self.idle();
+        self.idle();
+        let value: u16 = if wide { self.pull_word(memory) } else { self.pull(memory) as u16 };
+        self.set_nz(value, wide);
+        value
+    }
+
+    // Push operations
+    pub(super) fn pha<B: Bus>(&mut self, memory: &mut B) {
+        self.push_sized(self.regA, self.memory_wide(), memory);
+    }
+
+    pub(super) fn phx<B: Bus>(&mut self, memory: &mut B) {
+        self.push_sized(self.regX, self.index_wide(), memory);
+    }
+
+    pub(super) fn phy<B: Bus>(&mut self, memory: &mut B) {
+        self.push_sized(self.regY, self.index_wide(), memory);
+    }
+
+    pub(super) fn phb<B: Bus>(&mut self, memory: &mut B) {
+        self.push_sized(self.regDB as u16, false, memory);
+    }
+
+    pub(super) fn phd<B: Bus>(&mut self, memory: &mut B) {
+        self.push_sized(self.regD, true, memory);
+    }
+
+    pub(super) fn phk<B: Bus>(&mut self, memory: &mut B) {
+        self.push_sized(self.regPB as u16, false, memory);
+    }
+
+    // In emulation mode the set X bit is pushed as the break flag
+    pub(super) fn php<B: Bus>(&mut self, memory: &mut B) {
+        self.push_sized(self.regP as u16, false, memory);
+    }
+
+    // Pull operations
+    pub(super) fn pla<B: Bus>(&mut self, memory: &mut B) {
+        let value: u16 = self.pull_sized(self.memory_wide(), memory);
+        self.set_accumulator(value);
+    }
+
+    pub(super) fn plx<B: Bus>(&mut self, memory: &mut B) {
+        self.regX = self.pull_sized(self.index_wide(), memory);
+    }
+
+    pub(super) fn ply<B: Bus>(&mut self, memory: &mut B) {
+        self.regY = self.pull_sized(self.index_wide(), memory);
+    }
+
+    pub(super) fn plb<B: Bus>(&mut self, memory: &mut B) {
+        self.regDB = self.pull_sized(false, memory) as u8;
+    }
+
+    pub(super) fn pld<B: Bus>(&mut self, memory: &mut B) {
+        self.regD = self.pull_sized(true, memory);
+    }
+
+    pub(super) fn plp<B: Bus>(&mut self, memory: &mut B) {
+        self.idle();
+        self.idle();
+        let status: u8 = self.pull(memory);
+        self.set_status(status);
+    }
+
+    // Push effective address: absolute, indirect through the direct page, and PC relative
+    pub(super) fn pea<B: Bus>(&mut self, memory: &mut B) {
+        let value: u16 = self.fetch_word(memory);
+        self.push_word(value, memory);
+    }
+
+    pub(super) fn pei<B: Bus>(&mut self, memory: &mut B) {
+        let offset: u16 = self.fetch_direct(memory);
+        let value: u16 = self.read_direct_pointer(offset, 0, memory);
+        self.push_word(value, memory);
+    }
+
+    pub(super) fn per<B: Bus>(&mut self, memory: &mut B) {
+        let offset: u16 = self.fetch_word(memory);
+        self.idle();
+        self.push_word(self.regPC.wrapping_add(offset), memory);
+    }
+}
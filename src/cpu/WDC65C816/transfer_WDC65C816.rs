@@ -0,0 +1,143 @@
+use crate::cpu::bus::Bus;
+
+use crate::cpu::cpu::CPU;
+
+use super::addressing_WDC65C816::Mode;
+use super::WDC65C816;
+
+impl WDC65C816 {
+    // Loads, the accumulator follows M and the index registers follow X
+    pub(super) fn lda<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        let value: u16 = self.read_operand(mode, self.memory_wide(), memory);
+        self.set_accumulator(value);
+        self.set_nz(value, self.memory_wide());
+    }
+
+    pub(super) fn ldx<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        self.regX = self.read_operand(mode, self.index_wide(), memory);
+        self.set_nz(self.regX, self.index_wide());
+    }
+
+    pub(super) fn ldy<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        self.regY = self.read_operand(mode, self.index_wide(), memory);
+        self.set_nz(self.regY, self.index_wide());
+    }
+
+    // Stores
+    pub(super) fn sta<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        self.write_operand(mode, self.regA, self.memory_wide(), memory);
+    }
+
+    pub(super) fn stx<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        self.write_operand(mode, self.regX, self.index_wide(), memory);
+    }
+
+    pub(super) fn sty<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        self.write_operand(mode, self.regY, self.index_wide(), memory);
+    }
+
+    pub(super) fn stz<B: Bus>(&mut self, mode: Mode, memory: &mut B) {
+        self.write_operand(mode, 0, self.memory_wide(), memory);
+    }
+
+    // Register transfers take the width of the destination register
+    pub(super) fn tax(&mut self) {
+        self.idle();
+        self.regX = self.index(self.regA);
+        self.set_nz(self.regX, self.index_wide());
+    }
+
+    pub(super) fn tay(&mut self) {
+        self.idle();
+        self.regY = self.index(self.regA);
+        self.set_nz(self.regY, self.index_wide());
+    }
+
+    pub(super) fn txa(&mut self) {
+        self.idle();
+        self.set_accumulator(self.regX);
+        self.set_nz(self.regA, self.memory_wide());
+    }
+
+    pub(super) fn tya(&mut self) {
+        self.idle();
+        self.set_accumulator(self.regY);
+        self.set_nz(self.regA, self.memory_wide());
+    }
+
+    pub(super) fn txy(&mut self) {
+        self.idle();
+        self.regY = self.regX;
+        self.set_nz(self.regY, self.index_wide());
+    }
+
+    pub(super) fn tyx(&mut self) {
+        self.idle();
+        self.regX = self.regY;
+        self.set_nz(self.regX, self.index_wide());
+    }
+
+    pub(super) fn tsx(&mut self) {
+        self.idle();
+        self.regX = self.index(self.regSP);
+        self.set_nz(self.regX, self.index_wide());
+    }
+
+    pub(super) fn txs(&mut self) {
+        self.idle();
+        self.set_sp(self.regX);
+    }
+
+    // The transfers to and from C, S and D are always 16 bits wide
+    pub(super) fn tcs(&mut self) {
+        self.idle();
+        self.set_sp(self.regA);
+    }
+
+    pub(super) fn tsc(&mut self) {
+        self.idle();
+        self.regA = self.regSP;
+        self.set_nz(self.regA, true);
+    }
+
+    pub(super) fn tcd(&mut self) {
+        self.idle();
+        self.regD = self.regA;
+        self.set_nz(self.regD, true);
+    }
+
+    pub(super) fn tdc(&mut self) {
+        self.idle();
+        self.regA = self.regD;
+        self.set_nz(self.regA, true);
+    }
+
+    // Exchange B and A, the flags follow the new A
+    pub(super) fn xba(&mut self) {
+        self.idle();
+        self.idle();
+        self.regA = self.regA.rotate_left(8);
+        self.set_nz(self.regA, false);
+    }
+
+    // MVN/MVP move one byte from source bank:X to destination bank:Y per
+    // pass and run again until C wraps to $FFFF, so interrupts can be taken
+    // between bytes. step is added to X and Y: 1 for MVN, -1 for MVP
+    pub(super) fn block_move<B: Bus>(&mut self, memory: &mut B, step: u16) {
+        let destination_bank: u8 = self.fetch(memory);
+        let source_bank: u8 = self.fetch(memory);
+        self.regDB = destination_bank;
+
+        let value: u8 = self.read_byte(((source_bank as u32) << 16) | self.regX as u32, memory);
+        self.write_byte(((destination_bank as u32) << 16) | self.regY as u32, value, memory);
+        self.idle();
+        self.idle();
+
+        self.regX = self.index(self.regX.wrapping_add(step));
+        self.regY = self.index(self.regY.wrapping_add(step));
+        self.regA = self.regA.wrapping_sub(1);
+        if self.regA != 0xFFFF {
+            self.regPC = self.regPC.wrapping_sub(3);
+        }
+    }
+}
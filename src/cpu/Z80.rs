@@ -0,0 +1,593 @@
+mod alu_Z80;
+mod block_Z80;
+mod control_Z80;
+mod decode_Z80;
+
+use super::bus::Bus;
+use super::cpu::{Register, StepInfo, CPU};
+use super::error::ExecutionError;
+#[cfg(test)]
+use super::memory::Memory;
+
+// Flag bits of F. X and Y are undocumented, they copy bits 3 and 5 of
+// whatever internal value the instruction last put on the ALU bus
+const CARRY: u8 = 0x01;
+const SUBTRACT: u8 = 0x02;
+// Parity after logical operations, overflow after arithmetic
+const PARITY: u8 = 0x04;
+const X_FLAG: u8 = 0x08;
+const HALF_CARRY: u8 = 0x10;
+const Y_FLAG: u8 = 0x20;
+const ZERO: u8 = 0x40;
+const SIGN: u8 = 0x80;
+const XY: u8 = X_FLAG | Y_FLAG;
+
+// What stands in for HL: a DD prefix selects IX, FD selects IY, for the
+// whole register as well as for its H and L halves and the (HL) operand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Index {
+    HL,
+    IX,
+    IY,
+}
+
+pub struct Z80 {
+    regA: u8,
+    regF: u8,
+    regB: u8,
+    regC: u8,
+    regD: u8,
+    regE: u8,
+    regH: u8,
+    regL: u8,
+    // Alternate register set, swapped in by EX AF,AF' and EXX
+    regAF_: u16,
+    regBC_: u16,
+    regDE_: u16,
+    regHL_: u16,
+    regIX: u16,
+    regIY: u16,
+    regSP: u16,
+    regPC: u16,
+    // Interrupt vector base for mode 2 and memory refresh counter. R counts
+    // opcode fetches in its low 7 bits, bit 7 only changes through LD R,A
+    regI: u8,
+    regR: u8,
+    // Internal address latch, also known as WZ. Only visible through the
+    // X and Y flags of BIT n,(HL)
+    memptr: u16,
+    // F as written by the current and by the previous instruction, 0 if it left
+    // the flags alone. SCF and CCF take X and Y from it
+    q: u8,
+    last_q: u8,
+
+    iff1: bool,
+    iff2: bool,
+    interrupt_mode: u8,
+    halted: bool,
+    int_line: bool,
+    nmi_line: bool,
+    nmi_pending: bool,
+    // Byte the interrupting device puts on the data bus during the acknowledge cycle
+    interrupt_data: u8,
+    // EI holds off maskable interrupts until the instruction after it has run
+    interrupt_blocked: bool,
+    // LD A,I and LD A,R just copied IFF2 into P/V, an interrupt taken right after clears it
+    ld_a_ir: bool,
+    // Prefix of the instruction being executed
+    index: Index,
+
+    total_cycles: u64,
+    cycle_debt: u32,
+    // T-states of the instruction being executed
+    step_cycles: u32,
+}
+
+impl Default for Z80 {
+    fn default() -> Self {
+        Z80::new()
+    }
+}
+
+impl Z80 {
+    // Power-on state as left by reset, AF and SP come up as $FFFF on most chips
+    pub fn new() -> Self {
+        Z80 {
+            regA : 0xFF,
+            regF : 0xFF,
+            regB : 0,
+            regC : 0,
+            regD : 0,
+            regE : 0,
+            regH : 0,
+            regL : 0,
+            regAF_ : 0xFFFF,
+            regBC_ : 0,
+            regDE_ : 0,
+            regHL_ : 0,
+            regIX : 0,
+            regIY : 0,
+            regSP : 0xFFFF,
+            regPC : 0x0000,
+            regI : 0,
+            regR : 0,
+            memptr : 0,
+            q : 0,
+            last_q : 0,
+            iff1 : false,
+            iff2 : false,
+            interrupt_mode : 0,
+            halted : false,
+            int_line : false,
+            nmi_line : false,
+            nmi_pending : false,
+            interrupt_data : 0xFF,
+            interrupt_blocked : false,
+            ld_a_ir : false,
+            index : Index::HL,
+            total_cycles : 0,
+            cycle_debt : 0,
+            step_cycles : 0,
+        }
+    }
+
+    // Register access, PC is on the CPU trait
+    pub fn a(&self) -> u8 {
+        self.regA
+    }
+
+    pub fn f(&self) -> u8 {
+        self.regF
+    }
+
+    pub fn af(&self) -> u16 {
+        ((self.regA as u16) << 8) | self.regF as u16
+    }
+
+    pub fn bc(&self) -> u16 {
+        ((self.regB as u16) << 8) | self.regC as u16
+    }
+
+    pub fn de(&self) -> u16 {
+        ((self.regD as u16) << 8) | self.regE as u16
+    }
+
+    pub fn hl(&self) -> u16 {
+        ((self.regH as u16) << 8) | self.regL as u16
+    }
+
+    pub fn ix(&self) -> u16 {
+        self.regIX
+    }
+
+    pub fn iy(&self) -> u16 {
+        self.regIY
+    }
+
+    pub fn sp(&self) -> u16 {
+        self.regSP
+    }
+
+    pub fn set_sp(&mut self, sp: u16) {
+        self.regSP = sp;
+    }
+
+    pub fn i(&self) -> u8 {
+        self.regI
+    }
+
+    pub fn r(&self) -> u8 {
+        self.regR
+    }
+
+    pub fn memptr(&self) -> u16 {
+        self.memptr
+    }
+
+    pub fn interrupt_mode(&self) -> u8 {
+        self.interrupt_mode
+    }
+
+    // Data bus contents during an interrupt acknowledge: the opcode executed in
+    // mode 0 (normally an RST) or the low byte of the vector table entry in mode 2
+    pub fn set_interrupt_data(&mut self, data: u8) {
+        self.interrupt_data = data;
+    }
+
+    fn set_af(&mut self, value: u16) {
+        self.regA = (value >> 8) as u8;
+        self.regF = value as u8;
+    }
+
+    fn set_bc(&mut self, value: u16) {
+        self.regB = (value >> 8) as u8;
+        self.regC = value as u8;
+    }
+
+    fn set_de(&mut self, value: u16) {
+        self.regD = (value >> 8) as u8;
+        self.regE = value as u8;
+    }
+
+    fn set_hl(&mut self, value: u16) {
+        self.regH = (value >> 8) as u8;
+        self.regL = value as u8;
+    }
+
+    // Every instruction that computes flags goes through here, see q
+    fn set_flags(&mut self, value: u8) {
+        self.regF = value;
+        self.q = value;
+    }
+
+    // HL, IX or IY depending on the prefix
+    fn index_register(&self) -> u16 {
+        match self.index {
+            Index::HL => self.hl(),
+            Index::IX => self.regIX,
+            Index::IY => self.regIY,
+        }
+    }
+
+    fn set_index_register(&mut self, value: u16) {
+        match self.index {
+            Index::HL => self.set_hl(value),
+            Index::IX => self.regIX = value,
+            Index::IY => self.regIY = value,
+        }
+    }
+
+    // 8-bit register from its 3-bit encoding B C D E H L - A. Under a prefix
+    // H and L are the halves of IX or IY
+    fn register(&self, code: u8) -> u8 {
+        match code {
+            0 => self.regB,
+            1 => self.regC,
+            2 => self.regD,
+            3 => self.regE,
+            4 => (self.index_register() >> 8) as u8,
+            5 => self.index_register() as u8,
+            _ => self.regA,
+        }
+    }
+
+    fn set_register(&mut self, code: u8, value: u8) {
+        match code {
+            0 => self.regB = value,
+            1 => self.regC = value,
+            2 => self.regD = value,
+            3 => self.regE = value,
+            4 => self.set_index_register((self.index_register() & 0x00FF) | ((value as u16) << 8)),
+            5 => self.set_index_register((self.index_register() & 0xFF00) | value as u16),
+            _ => self.regA = value,
+        }
+    }
+
+    // Instructions that also have an (HL) operand always use the real H and L
+    fn unindexed_register(&self, code: u8) -> u8 {
+        match code {
+            4 => self.regH,
+            5 => self.regL,
+            _ => self.register(code),
+        }
+    }
+
+    fn set_unindexed_register(&mut self, code: u8, value: u8) {
+        match code {
+            4 => self.regH = value,
+            5 => self.regL = value,
+            _ => self.set_register(code, value),
+        }
+    }
+
+    // Register pair from its 2-bit encoding, with SP or AF as the last one
+    fn register_pair(&self, code: u8, last_is_af: bool) -> u16 {
+        match code {
+            0 => self.bc(),
+            1 => self.de(),
+            2 => self.index_register(),
+            _ => if last_is_af { self.af() } else { self.regSP },
+        }
+    }
+
+    fn set_register_pair(&mut self, code: u8, value: u16, last_is_af: bool) {
+        match code {
+            0 => self.set_bc(value),
+            1 => self.set_de(value),
+            2 => self.set_index_register(value),
+            _ => if last_is_af { self.set_af(value) } else { self.regSP = value },
+        }
+    }
+
+    // M1 cycle: opcode read and memory refresh, 4 T-states
+    fn fetch_opcode<B: Bus>(&mut self, memory: &mut B) -> u8 {
+        self.refresh();
+        self.step_cycles += 4;
+        let opcode: u8 = memory.read(self.regPC as u32);
+        self.regPC = self.regPC.wrapping_add(1);
+        opcode
+    }
+
+    fn refresh(&mut self) {
+        self.regR = (self.regR & 0x80) | (self.regR.wrapping_add(1) & 0x7F);
+    }
+
+    fn fetch_word<B: Bus>(&mut self, memory: &mut B) -> u16 {
+        let low_byte: u8 = self.fetch(memory);
+        let high_byte: u8 = self.fetch(memory);
+        ((high_byte as u16) << 8) | low_byte as u16
+    }
+
+    // Memory cycles take 3 T-states, I/O cycles 4 with the automatic wait state
+    fn read_byte<B: Bus>(&mut self, address: u16, memory: &mut B) -> u8 {
+        self.step_cycles += 3;
+        memory.read(address as u32)
+    }
+
+    fn write_byte<B: Bus>(&mut self, address: u16, value: u8, memory: &mut B) {
+        self.step_cycles += 3;
+        memory.write(address as u32, value);
+    }
+
+    fn read_word<B: Bus>(&mut self, address: u16, memory: &mut B) -> u16 {
+        let low_byte: u8 = self.read_byte(address, memory);
+        let high_byte: u8 = self.read_byte(address.wrapping_add(1), memory);
+        ((high_byte as u16) << 8) | low_byte as u16
+    }
+
+    fn write_word<B: Bus>(&mut self, address: u16, value: u16, memory: &mut B) {
+        self.write_byte(address, value as u8, memory);
+        self.write_byte(address.wrapping_add(1), (value >> 8) as u8, memory);
+    }
+
+    fn input<B: Bus>(&mut self, port: u16, memory: &mut B) -> u8 {
+        self.step_cycles += 4;
+        memory.input(port)
+    }
+
+    fn output<B: Bus>(&mut self, port: u16, value: u8, memory: &mut B) {
+        self.step_cycles += 4;
+        memory.output(port, value);
+    }
+
+    // T-states spent inside the processor with no bus activity
+    fn idle(&mut self, cycles: u32) {
+        self.step_cycles += cycles;
+    }
+
+    fn push<B: Bus>(&mut self, value: u16, memory: &mut B) {
+        self.regSP = self.regSP.wrapping_sub(1);
+        self.write_byte(self.regSP, (value >> 8) as u8, memory);
+        self.regSP = self.regSP.wrapping_sub(1);
+        self.write_byte(self.regSP, value as u8, memory);
+    }
+
+    fn pop<B: Bus>(&mut self, memory: &mut B) -> u16 {
+        let value: u16 = self.read_word(self.regSP, memory);
+        self.regSP = self.regSP.wrapping_add(2);
+        value
+    }
+}
+
+impl CPU for Z80 {
+    // Operand bytes after the opcode, a plain memory read
+    fn fetch<B: Bus>(&mut self, memory : &mut B) -> u8 {
+        let res = self.read_byte(self.regPC, memory);
+        self.regPC = self.regPC.wrapping_add(1);
+        res
+    }
+
+    fn read<B: Bus>(&mut self, address: u16, memory : &mut B) -> u8 {
+        memory.read(address as u32)
+    }
+
+    fn write<B: Bus>(&mut self, address: u16, value: u8, memory : &mut B) {
+        memory.write(address as u32, value);
+    }
+
+    // A HALT in progress executes NOPs without moving PC until an interrupt arrives
+    fn step<B: Bus>(&mut self, memory : &mut B) -> Result<StepInfo, ExecutionError> {
+        let address : u32 = self.regPC as u32;
+        self.step_cycles = 0;
+        let mut opcode : Option<u8> = None;
+        if !self.poll_interrupts(memory) {
+            if self.halted {
+                self.refresh();
+                self.idle(4);
+            } else {
+                let instruction : u8 = self.fetch_opcode(memory);
+                self.last_q = self.q;
+                self.q = 0;
+                self.execute_instruction(instruction, memory);
+                opcode = Some(instruction);
+            }
+        }
+        self.total_cycles += self.step_cycles as u64;
+        if let Some(address) = memory.fault() {
+            return Err(ExecutionError::BusFault { address });
+        }
+        Ok(StepInfo { address, opcode, cycles : self.step_cycles })
+    }
+
+    // Runs for at least the given budget, the cycles spent past it are
+    // carried over and deducted from the next call
+    fn execute<B: Bus>(&mut self, cycles : u32, memory : &mut B) -> Result<(), ExecutionError> {
+        let mut remaining : i64 = cycles as i64 - self.cycle_debt as i64;
+        self.cycle_debt = 0;
+        while remaining > 0 {
+            remaining -= self.step(memory)?.cycles as i64;
+        }
+        self.cycle_debt = (-remaining) as u32;
+        Ok(())
+    }
+
+    // Execution starts at $0000 in interrupt mode 0 with interrupts disabled
+    fn reset<B: Bus>(&mut self, _memory : &mut B) {
+        self.reset_sequence();
+        self.total_cycles += 3;
+    }
+
+    // HALT waits for an interrupt and an NMI always ends it, the Z80 never needs a reset to run again
+    fn halted(&self) -> bool {
+        false
+    }
+
+    // State of the HALT pin: a HALT instruction is waiting for an interrupt
+    fn waiting(&self) -> bool {
+        self.halted
+    }
+
+    fn total_cycles(&self) -> u64 {
+        self.total_cycles
+    }
+
+    // INT, level-triggered
+    fn irq(&mut self, active: bool) {
+        self.int_line = active;
+    }
+
+    fn nmi(&mut self, active: bool) {
+        if active && !self.nmi_line {
+            self.nmi_pending = true;
+        }
+        self.nmi_line = active;
+    }
+
+    fn pc(&self) -> u16 {
+        self.regPC
+    }
+
+    fn set_pc(&mut self, pc: u16) {
+        self.regPC = pc;
+        self.halted = false;
+    }
+
+    fn registers(&self) -> Vec<Register> {
+        vec![
+            Register { name : "PC", value : self.regPC as u32, bits : 16 },
+            Register { name : "SP", value : self.regSP as u32, bits : 16 },
+            Register { name : "AF", value : self.af() as u32, bits : 16 },
+            Register { name : "BC", value : self.bc() as u32, bits : 16 },
+            Register { name : "DE", value : self.de() as u32, bits : 16 },
+            Register { name : "HL", value : self.hl() as u32, bits : 16 },
+            Register { name : "IX", value : self.regIX as u32, bits : 16 },
+            Register { name : "IY", value : self.regIY as u32, bits : 16 },
+            Register { name : "AF'", value : self.regAF_ as u32, bits : 16 },
+            Register { name : "BC'", value : self.regBC_ as u32, bits : 16 },
+            Register { name : "DE'", value : self.regDE_ as u32, bits : 16 },
+            Register { name : "HL'", value : self.regHL_ as u32, bits : 16 },
+            Register { name : "I", value : self.regI as u32, bits : 8 },
+            Register { name : "R", value : self.regR as u32, bits : 8 },
+            Register { name : "IM", value : self.interrupt_mode as u32, bits : 2 },
+            Register { name : "IFF1", value : self.iff1 as u32, bits : 1 },
+            Register { name : "IFF2", value : self.iff2 as u32, bits : 1 },
+        ]
+    }
+}
+
+// Loads `program` at $0000 with the processor just out of reset and the stack at $FF00
+#[cfg(test)]
+fn setup(program: &[u8]) -> (Z80, Memory) {
+    let mut memory: Memory = Memory::new();
+    for (offset, byte) in program.iter().enumerate() {
+        memory[offset] = *byte;
+    }
+    let mut cpu: Z80 = Z80::new();
+    cpu.reset(&mut memory);
+    cpu.regSP = 0xFF00;
+    (cpu, memory)
+}
+
+// Runs whole instructions and returns the T-states of the last one
+#[cfg(test)]
+fn run<B: Bus>(cpu: &mut Z80, memory: &mut B, instructions: u32) -> u32 {
+    let mut cycles: u32 = 0;
+    for _ in 0..instructions {
+        cycles = cpu.step(memory).unwrap().cycles;
+    }
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::bus::Bus;
+    use crate::cpu::cpu::CPU;
+    use crate::cpu::memory::Memory;
+
+    use super::{run, setup};
+
+    // Memory plus a port space where every port reads back its low address byte
+    struct PortBus {
+        memory: Memory,
+        outputs: Vec<(u16, u8)>,
+    }
+
+    impl Bus for PortBus {
+        fn read(&mut self, address: u32) -> u8 {
+            self.memory.read(address)
+        }
+
+        fn write(&mut self, address: u32, value: u8) {
+            self.memory.write(address, value);
+        }
+
+        fn peek(&self, address: u32) -> u8 {
+            self.memory.peek(address)
+        }
+
+        fn input(&mut self, port: u16) -> u8 {
+            port as u8
+        }
+
+        fn output(&mut self, port: u16, value: u8) {
+            self.outputs.push((port, value));
+        }
+    }
+
+    #[test]
+    fn in_and_out_use_the_port_space() {
+        // LD A,$12 ; OUT ($34),A ; LD BC,$5678 ; OUT (C),B ; IN A,(C) ; IN A,($9A)
+        let (mut cpu, memory) = setup(&[0x3E, 0x12, 0xD3, 0x34, 0x01, 0x78, 0x56, 0xED, 0x41, 0xED, 0x78, 0xDB, 0x9A]);
+        let mut bus: PortBus = PortBus { memory, outputs : Vec::new() };
+        run(&mut cpu, &mut bus, 1);
+        assert_eq!(run(&mut cpu, &mut bus, 1), 11);
+        run(&mut cpu, &mut bus, 1);
+        assert_eq!(run(&mut cpu, &mut bus, 1), 12);
+        // OUT (n),A puts A on the high half of the port address
+        assert_eq!(bus.outputs, vec![(0x1234, 0x12), (0x5678, 0x56)]);
+        assert_eq!(bus.memory[0x0034], 0x00);
+
+        assert_eq!(run(&mut cpu, &mut bus, 1), 12);
+        assert_eq!(cpu.a(), 0x78);
+        assert_eq!(cpu.memptr(), 0x5679);
+        run(&mut cpu, &mut bus, 1);
+        assert_eq!(cpu.a(), 0x9A);
+    }
+
+    #[test]
+    fn unanswered_input_floats_high() {
+        // IN A,($00)
+        let (mut cpu, mut memory) = setup(&[0xDB, 0x00]);
+        run(&mut cpu, &mut memory, 1);
+        assert_eq!(cpu.a(), 0xFF);
+    }
+
+    #[test]
+    fn halt_waits_for_an_interrupt() {
+        // IM 1 ; EI ; HALT
+        let (mut cpu, mut memory) = setup(&[0xED, 0x56, 0xFB, 0x76]);
+        run(&mut cpu, &mut memory, 3);
+        assert!(cpu.waiting());
+        assert!(!cpu.halted());
+        assert_eq!(run(&mut cpu, &mut memory, 2), 4);
+        assert_eq!(cpu.pc(), 0x0004);
+
+        cpu.irq(true);
+        run(&mut cpu, &mut memory, 1);
+        assert!(!cpu.waiting());
+        assert_eq!(cpu.pc(), 0x0038);
+        // The return address is past the HALT
+        assert_eq!(memory[0xFEFE], 0x04);
+        assert_eq!(memory[0xFEFF], 0x00);
+    }
+}
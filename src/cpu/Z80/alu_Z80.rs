@@ -0,0 +1,312 @@
+use crate::cpu::bus::Bus;
+
+use super::{Z80, CARRY, HALF_CARRY, PARITY, SIGN, SUBTRACT, XY, X_FLAG, Y_FLAG, ZERO};
+
+// Sign, zero and the undocumented X and Y bits of a result
+pub(super) fn sign_zero_xy(value: u8) -> u8 {
+    let zero: u8 = if value == 0 { ZERO } else { 0 };
+    (value & (SIGN | XY)) | zero
+}
+
+// Set when the number of one bits is even
+pub(super) fn parity(value: u8) -> u8 {
+    if value.count_ones().is_multiple_of(2) { PARITY } else { 0 }
+}
+
+impl Z80 {
+    // 8-bit arithmetic on A. Half carry and overflow come from the carries
+    // into bits 4 and 7, recovered from the operands and the result
+    pub(super) fn add(&mut self, value: u8, carry: bool) {
+        let a: u8 = self.regA;
+        let sum: u16 = a as u16 + value as u16 + carry as u16;
+        let result: u8 = sum as u8;
+        let overflow: u8 = if (a ^ result) & (value ^ result) & 0x80 != 0 { PARITY } else { 0 };
+        let carry_out: u8 = if sum > 0xFF { CARRY } else { 0 };
+        self.regA = result;
+        self.set_flags(sign_zero_xy(result) | ((a ^ value ^ result) & HALF_CARRY) | overflow | carry_out);
+    }
+
+    fn subtract(&mut self, value: u8, carry: bool) -> u8 {
+        let a: u8 = self.regA;
+        let difference: i16 = a as i16 - value as i16 - carry as i16;
+        let result: u8 = difference as u8;
+        let overflow: u8 = if (a ^ value) & (a ^ result) & 0x80 != 0 { PARITY } else { 0 };
+        let borrow: u8 = if difference < 0 { CARRY } else { 0 };
+        self.set_flags(sign_zero_xy(result) | ((a ^ value ^ result) & HALF_CARRY) | overflow | SUBTRACT | borrow);
+        result
+    }
+
+    pub(super) fn sub(&mut self, value: u8, carry: bool) {
+        self.regA = self.subtract(value, carry);
+    }
+
+    // CP takes X and Y from the operand rather than from the difference
+    pub(super) fn cp(&mut self, value: u8) {
+        self.subtract(value, false);
+        self.set_flags((self.regF & !XY) | (value & XY));
+    }
+
+    pub(super) fn and(&mut self, value: u8) {
+        self.regA &= value;
+        self.set_flags(sign_zero_xy(self.regA) | parity(self.regA) | HALF_CARRY);
+    }
+
+    pub(super) fn xor(&mut self, value: u8) {
+        self.regA ^= value;
+        self.set_flags(sign_zero_xy(self.regA) | parity(self.regA));
+    }
+
+    pub(super) fn or(&mut self, value: u8) {
+        self.regA |= value;
+        self.set_flags(sign_zero_xy(self.regA) | parity(self.regA));
+    }
+
+    // ADD ADC SUB SBC AND XOR OR CP by their 3-bit encoding
+    pub(super) fn alu(&mut self, operation: u8, value: u8) {
+        let carry: bool = self.regF & CARRY != 0;
+        match operation {
+            0 => self.add(value, false),
+            1 => self.add(value, carry),
+            2 => self.sub(value, false),
+            3 => self.sub(value, carry),
+            4 => self.and(value),
+            5 => self.xor(value),
+            6 => self.or(value),
+            _ => self.cp(value),
+        }
+    }
+
+    // INC and DEC leave the carry alone
+    pub(super) fn inc(&mut self, value: u8) -> u8 {
+        let result: u8 = value.wrapping_add(1);
+        let half_carry: u8 = if value & 0x0F == 0x0F { HALF_CARRY } else { 0 };
+        let overflow: u8 = if value == 0x7F { PARITY } else { 0 };
+        self.set_flags(sign_zero_xy(result) | half_carry | overflow | (self.regF & CARRY));
+        result
+    }
+
+    pub(super) fn dec(&mut self, value: u8) -> u8 {
+        let result: u8 = value.wrapping_sub(1);
+        let half_carry: u8 = if value & 0x0F == 0x00 { HALF_CARRY } else { 0 };
+        let overflow: u8 = if value == 0x80 { PARITY } else { 0 };
+        self.set_flags(sign_zero_xy(result) | half_carry | overflow | SUBTRACT | (self.regF & CARRY));
+        result
+    }
+
+    // 16-bit arithmetic. The flags are those of the high byte operation, done
+    // last by the 8-bit ALU, and MEMPTR is left pointing past the first operand
+    pub(super) fn add16(&mut self, left: u16, right: u16) -> u16 {
+        let sum: u32 = left as u32 + right as u32;
+        let result: u16 = sum as u16;
+        let half_carry: u8 = (((left ^ right ^ result) >> 8) as u8) & HALF_CARRY;
+        let carry: u8 = if sum > 0xFFFF { CARRY } else { 0 };
+        let kept: u8 = self.regF & (SIGN | ZERO | PARITY);
+        self.set_flags(kept | (((result >> 8) as u8) & XY) | half_carry | carry);
+        self.memptr = left.wrapping_add(1);
+        self.idle(7);
+        result
+    }
+
+    pub(super) fn adc16(&mut self, left: u16, right: u16) -> u16 {
+        let sum: u32 = left as u32 + right as u32 + (self.regF & CARRY) as u32;
+        let result: u16 = sum as u16;
+        let half_carry: u8 = (((left ^ right ^ result) >> 8) as u8) & HALF_CARRY;
+        let overflow: u8 = if (left ^ result) & (right ^ result) & 0x8000 != 0 { PARITY } else { 0 };
+        let carry: u8 = if sum > 0xFFFF { CARRY } else { 0 };
+        let zero: u8 = if result == 0 { ZERO } else { 0 };
+        self.set_flags((((result >> 8) as u8) & (SIGN | XY)) | zero | half_carry | overflow | carry);
+        self.memptr = left.wrapping_add(1);
+        self.idle(7);
+        result
+    }
+
+    pub(super) fn sbc16(&mut self, left: u16, right: u16) -> u16 {
+        let difference: i32 = left as i32 - right as i32 - (self.regF & CARRY) as i32;
+        let result: u16 = difference as u16;
+        let half_carry: u8 = (((left ^ right ^ result) >> 8) as u8) & HALF_CARRY;
+        let overflow: u8 = if (left ^ right) & (left ^ result) & 0x8000 != 0 { PARITY } else { 0 };
+        let borrow: u8 = if difference < 0 { CARRY } else { 0 };
+        let zero: u8 = if result == 0 { ZERO } else { 0 };
+        self.set_flags((((result >> 8) as u8) & (SIGN | XY)) | zero | half_carry | overflow | SUBTRACT | borrow);
+        self.memptr = left.wrapping_add(1);
+        self.idle(7);
+        result
+    }
+
+    // Accumulator rotates: S, Z and P/V are kept, unlike their CB counterparts
+    pub(super) fn rotate_accumulator(&mut self, operation: u8) {
+        let a: u8 = self.regA;
+        let carry: u8 = self.regF & CARRY;
+        let (result, carry_out) = match operation {
+            0 => (a.rotate_left(1), a >> 7),
+            1 => (a.rotate_right(1), a & 0x01),
+            2 => ((a << 1) | carry, a >> 7),
+            _ => ((a >> 1) | (carry << 7), a & 0x01),
+        };
+        self.regA = result;
+        self.set_flags((self.regF & (SIGN | ZERO | PARITY)) | (result & XY) | carry_out);
+    }
+
+    // RLC RRC RL RR SLA SRA SLL SRL by their 3-bit encoding. SLL is the
+    // undocumented shift that moves a 1 into bit 0
+    pub(super) fn rotate(&mut self, operation: u8, value: u8) -> u8 {
+        let carry: u8 = self.regF & CARRY;
+        let (result, carry_out) = match operation {
+            0 => (value.rotate_left(1), value >> 7),
+            1 => (value.rotate_right(1), value & 0x01),
+            2 => ((value << 1) | carry, value >> 7),
+            3 => ((value >> 1) | (carry << 7), value & 0x01),
+            4 => (value << 1, value >> 7),
+            5 => ((value >> 1) | (value & 0x80), value & 0x01),
+            6 => ((value << 1) | 0x01, value >> 7),
+            _ => (value >> 1, value & 0x01),
+        };
+        self.set_flags(sign_zero_xy(result) | parity(result) | carry_out);
+        result
+    }
+
+    // X and Y come from the tested value for registers, from MEMPTR for memory operands
+    pub(super) fn bit(&mut self, bit: u8, value: u8, xy_source: u8) {
+        let tested: u8 = value & (1 << bit);
+        let zero: u8 = if tested == 0 { ZERO | PARITY } else { 0 };
+        self.set_flags((tested & SIGN) | zero | (xy_source & XY) | HALF_CARRY | (self.regF & CARRY));
+    }
+
+    // Decimal adjust after an addition or a subtraction, N tells which
+    pub(super) fn daa(&mut self) {
+        let a: u8 = self.regA;
+        let subtract: bool = self.regF & SUBTRACT != 0;
+        let mut correction: u8 = 0;
+        let mut carry: u8 = self.regF & CARRY;
+        if self.regF & HALF_CARRY != 0 || a & 0x0F > 9 {
+            correction |= 0x06;
+        }
+        if carry != 0 || a > 0x99 {
+            correction |= 0x60;
+            carry = CARRY;
+        }
+        let half_carry: bool = if subtract {
+            self.regF & HALF_CARRY != 0 && a & 0x0F < 6
+        } else {
+            a & 0x0F > 9
+        };
+        self.regA = if subtract { a.wrapping_sub(correction) } else { a.wrapping_add(correction) };
+        let half_carry: u8 = if half_carry { HALF_CARRY } else { 0 };
+        self.set_flags(sign_zero_xy(self.regA) | parity(self.regA) | half_carry | (self.regF & SUBTRACT) | carry);
+    }
+
+    pub(super) fn cpl(&mut self) {
+        self.regA = !self.regA;
+        self.set_flags((self.regF & (SIGN | ZERO | PARITY | CARRY)) | (self.regA & XY) | HALF_CARRY | SUBTRACT);
+    }
+
+    pub(super) fn neg(&mut self) {
+        let value: u8 = self.regA;
+        self.regA = 0;
+        self.sub(value, false);
+    }
+
+    // SCF and CCF take X and Y from A ORed with F, unless the previous
+    // instruction wrote the flags, in which case from A alone
+    fn carry_flag_xy(&self) -> u8 {
+        ((self.last_q ^ self.regF) | self.regA) & XY
+    }
+
+    pub(super) fn scf(&mut self) {
+        let xy: u8 = self.carry_flag_xy();
+        self.set_flags((self.regF & (SIGN | ZERO | PARITY)) | xy | CARRY);
+    }
+
+    pub(super) fn ccf(&mut self) {
+        let xy: u8 = self.carry_flag_xy();
+        let half_carry: u8 = if self.regF & CARRY != 0 { HALF_CARRY } else { 0 };
+        let carry: u8 = (self.regF & CARRY) ^ CARRY;
+        self.set_flags((self.regF & (SIGN | ZERO | PARITY)) | xy | half_carry | carry);
+    }
+
+    // Flags of LD A,I and LD A,R, which copy IFF2 into P/V
+    pub(super) fn load_interrupt_register(&mut self, value: u8) {
+        self.regA = value;
+        let enabled: u8 = if self.iff2 { PARITY } else { 0 };
+        self.set_flags(sign_zero_xy(value) | enabled | (self.regF & CARRY));
+        self.ld_a_ir = true;
+    }
+
+    // Flags of IN r,(C)
+    pub(super) fn input_flags(&mut self, value: u8) {
+        self.set_flags(sign_zero_xy(value) | parity(value) | (self.regF & CARRY));
+    }
+
+    // RLD and RRD rotate a BCD digit pair through the low digit of A
+    pub(super) fn rotate_digits<B: Bus>(&mut self, left: bool, memory: &mut B) {
+        let address: u16 = self.hl();
+        let value: u8 = self.read_byte(address, memory);
+        self.idle(4);
+        let a: u8 = self.regA;
+        let (result, digit) = if left {
+            ((value << 4) | (a & 0x0F), value >> 4)
+        } else {
+            ((a << 4) | (value >> 4), value & 0x0F)
+        };
+        self.write_byte(address, result, memory);
+        self.regA = (a & 0xF0) | digit;
+        self.set_flags(sign_zero_xy(self.regA) | parity(self.regA) | (self.regF & CARRY));
+        self.memptr = address.wrapping_add(1);
+    }
+}
+
+// Undocumented flags of the block instructions, which copy bits 3 and 1 of
+// an internal sum into X and Y
+pub(super) fn block_xy(value: u8) -> u8 {
+    (value & X_FLAG) | ((value << 4) & Y_FLAG)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{run, setup, XY};
+
+    #[test]
+    fn x_and_y_copy_bits_3_and_5_of_the_result() {
+        // LD A,$28 ; OR A ; LD A,$D7 ; OR A
+        let (mut cpu, mut memory) = setup(&[0x3E, 0x28, 0xB7, 0x3E, 0xD7, 0xB7]);
+        run(&mut cpu, &mut memory, 2);
+        assert_eq!(cpu.f() & XY, XY);
+        run(&mut cpu, &mut memory, 2);
+        assert_eq!(cpu.f() & XY, 0x00);
+    }
+
+    #[test]
+    fn cp_takes_x_and_y_from_the_operand() {
+        // LD A,$00 ; CP $28
+        let (mut cpu, mut memory) = setup(&[0x3E, 0x00, 0xFE, 0x28]);
+        run(&mut cpu, &mut memory, 2);
+        assert_eq!(cpu.f() & XY, XY);
+    }
+
+    #[test]
+    fn scf_x_and_y_depend_on_the_previous_instruction() {
+        // LD A,$00 ; CP $28 ; SCF: F was just written, X and Y come from A
+        let (mut cpu, mut memory) = setup(&[0x3E, 0x00, 0xFE, 0x28, 0x37]);
+        run(&mut cpu, &mut memory, 3);
+        assert_eq!(cpu.f() & XY, 0x00);
+
+        // LD A,$00 ; CP $28 ; NOP ; SCF: from A ORed with F
+        let (mut cpu, mut memory) = setup(&[0x3E, 0x00, 0xFE, 0x28, 0x00, 0x37]);
+        run(&mut cpu, &mut memory, 4);
+        assert_eq!(cpu.f() & XY, XY);
+    }
+
+    #[test]
+    fn bit_on_memory_takes_x_and_y_from_memptr() {
+        // LD A,($2800) ; LD HL,$4000 ; BIT 0,(HL) ; BIT 0,B
+        let (mut cpu, mut memory) = setup(&[0x3A, 0x00, 0x28, 0x21, 0x00, 0x40, 0xCB, 0x46, 0xCB, 0x40]);
+        run(&mut cpu, &mut memory, 2);
+        assert_eq!(cpu.memptr(), 0x2801);
+        assert_eq!(run(&mut cpu, &mut memory, 1), 12);
+        assert_eq!(cpu.f() & XY, XY);
+        assert_eq!(cpu.f() & 0x40, 0x40);
+        // A register operand supplies them itself
+        run(&mut cpu, &mut memory, 1);
+        assert_eq!(cpu.f() & XY, 0x00);
+    }
+}
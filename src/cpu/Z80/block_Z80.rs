@@ -0,0 +1,117 @@
+use crate::cpu::bus::Bus;
+
+use super::alu_Z80::{block_xy, parity, sign_zero_xy};
+use super::{Z80, CARRY, HALF_CARRY, PARITY, SIGN, SUBTRACT, XY, ZERO};
+
+impl Z80 {
+    // Repeating block instructions run one iteration per step and move PC
+    // back onto themselves, so interrupts are taken between iterations. While
+    // repeating, X and Y come from the high byte of PC
+    fn repeat_block(&mut self) {
+        self.idle(5);
+        self.regPC = self.regPC.wrapping_sub(2);
+        self.regF = (self.regF & !XY) | (((self.regPC >> 8) as u8) & XY);
+        self.q = self.regF;
+    }
+
+    // LDI LDD LDIR LDDR: copy (HL) to (DE), count BC down
+    pub(super) fn block_load<B: Bus>(&mut self, decrement: bool, repeat: bool, memory: &mut B) {
+        let step: u16 = if decrement { 0xFFFF } else { 1 };
+        let value: u8 = self.read_byte(self.hl(), memory);
+        self.write_byte(self.de(), value, memory);
+        self.idle(2);
+        self.set_hl(self.hl().wrapping_add(step));
+        self.set_de(self.de().wrapping_add(step));
+        self.set_bc(self.bc().wrapping_sub(1));
+
+        let counting: u8 = if self.bc() != 0 { PARITY } else { 0 };
+        let sum: u8 = value.wrapping_add(self.regA);
+        self.set_flags((self.regF & (SIGN | ZERO | CARRY)) | counting | block_xy(sum));
+        if repeat && self.bc() != 0 {
+            self.repeat_block();
+            self.memptr = self.regPC.wrapping_add(1);
+        }
+    }
+
+    // CPI CPD CPIR CPDR: compare A with (HL), the repeating forms stop on a match
+    pub(super) fn block_compare<B: Bus>(&mut self, decrement: bool, repeat: bool, memory: &mut B) {
+        let step: u16 = if decrement { 0xFFFF } else { 1 };
+        let value: u8 = self.read_byte(self.hl(), memory);
+        self.idle(5);
+        self.set_hl(self.hl().wrapping_add(step));
+        self.set_bc(self.bc().wrapping_sub(1));
+        self.memptr = self.memptr.wrapping_add(step);
+
+        let result: u8 = self.regA.wrapping_sub(value);
+        let half_carry: u8 = (self.regA ^ value ^ result) & HALF_CARRY;
+        let counting: u8 = if self.bc() != 0 { PARITY } else { 0 };
+        let xy: u8 = block_xy(result.wrapping_sub((half_carry != 0) as u8));
+        let flags: u8 = (sign_zero_xy(result) & (SIGN | ZERO)) | half_carry | counting | SUBTRACT | (self.regF & CARRY);
+        self.set_flags(flags | xy);
+        if repeat && self.bc() != 0 && result != 0 {
+            self.repeat_block();
+            self.memptr = self.regPC.wrapping_add(1);
+        }
+    }
+
+    // INI IND INIR INDR: read port (C) into (HL), count B down
+    pub(super) fn block_input<B: Bus>(&mut self, decrement: bool, repeat: bool, memory: &mut B) {
+        let step: u16 = if decrement { 0xFFFF } else { 1 };
+        self.idle(1);
+        let value: u8 = self.input(self.bc(), memory);
+        self.write_byte(self.hl(), value, memory);
+        self.memptr = self.bc().wrapping_add(step);
+        self.regB = self.regB.wrapping_sub(1);
+        self.set_hl(self.hl().wrapping_add(step));
+        let sum: u16 = value as u16 + (self.regC.wrapping_add(step as u8)) as u16;
+        self.block_io_flags(value, sum, repeat);
+    }
+
+    // OUTI OUTD OTIR OTDR: write (HL) to port (C). B is counted down before
+    // it goes out on the upper half of the address bus
+    pub(super) fn block_output<B: Bus>(&mut self, decrement: bool, repeat: bool, memory: &mut B) {
+        let step: u16 = if decrement { 0xFFFF } else { 1 };
+        self.idle(1);
+        let value: u8 = self.read_byte(self.hl(), memory);
+        self.regB = self.regB.wrapping_sub(1);
+        self.memptr = self.bc().wrapping_add(step);
+        self.output(self.bc(), value, memory);
+        self.set_hl(self.hl().wrapping_add(step));
+        let sum: u16 = value as u16 + self.regL as u16;
+        self.block_io_flags(value, sum, repeat);
+    }
+
+    // S, Z, X and Y follow B. N is bit 7 of the byte transferred, H and C the carry
+    // out of an internal sum, and P/V the parity of that sum's low 3 bits with B.
+    // When the instruction repeats, P/V and H are modified once more by the
+    // adjustment of B that the interrupted iteration would have done
+    fn block_io_flags(&mut self, value: u8, sum: u16, repeat: bool) {
+        let b: u8 = self.regB;
+        let carry: u8 = if sum > 0xFF { HALF_CARRY | CARRY } else { 0 };
+        let negative: u8 = if value & 0x80 != 0 { SUBTRACT } else { 0 };
+        let mut flags: u8 = sign_zero_xy(b) | negative | carry | parity((sum as u8 & 0x07) ^ b);
+        if repeat && b != 0 {
+            self.regF = flags;
+            self.repeat_block();
+            flags = self.regF;
+            if flags & CARRY != 0 {
+                flags &= !HALF_CARRY;
+                let adjusted: u8 = if value & 0x80 != 0 {
+                    if b & 0x0F == 0x00 {
+                        flags |= HALF_CARRY;
+                    }
+                    b.wrapping_sub(1)
+                } else {
+                    if b & 0x0F == 0x0F {
+                        flags |= HALF_CARRY;
+                    }
+                    b.wrapping_add(1)
+                };
+                flags ^= parity(adjusted & 0x07) ^ PARITY;
+            } else {
+                flags ^= parity(b & 0x07) ^ PARITY;
+            }
+        }
+        self.set_flags(flags);
+    }
+}
@@ -0,0 +1,232 @@
+use crate::cpu::bus::Bus;
+
+use crate::cpu::cpu::CPU;
+
+use super::{Z80, CARRY, PARITY, SIGN, ZERO};
+
+const NMI_VECTOR: u16 = 0x0066;
+// Mode 1 restart, also what mode 0 executes when the data bus floats at $FF
+const IM1_VECTOR: u16 = 0x0038;
+
+impl Z80 {
+    // Reset clears PC, I, R and the interrupt state, the other registers keep their contents
+    pub(super) fn reset_sequence(&mut self) {
+        self.regPC = 0x0000;
+        self.regI = 0;
+        self.regR = 0;
+        self.iff1 = false;
+        self.iff2 = false;
+        self.interrupt_mode = 0;
+        self.halted = false;
+        self.nmi_pending = false;
+        self.interrupt_blocked = false;
+        self.ld_a_ir = false;
+    }
+
+    // Services a pending interrupt before the next opcode fetch, NMI takes
+    // priority over INT. Acceptance ends a HALT, PC already points past it
+    pub(super) fn poll_interrupts<B: Bus>(&mut self, memory: &mut B) -> bool {
+        let blocked: bool = self.interrupt_blocked;
+        let ld_a_ir: bool = self.ld_a_ir;
+        self.interrupt_blocked = false;
+        self.ld_a_ir = false;
+        if self.nmi_pending {
+            // The opcode fetch is discarded, IFF2 remembers whether INT was enabled for RETN
+            self.nmi_pending = false;
+            self.halted = false;
+            self.iff1 = false;
+            self.refresh();
+            self.idle(5);
+            self.call(NMI_VECTOR, memory);
+            return true;
+        }
+        if !self.int_line || !self.iff1 || blocked {
+            return false;
+        }
+
+        self.halted = false;
+        self.iff1 = false;
+        self.iff2 = false;
+        if ld_a_ir {
+            self.regF &= !PARITY;
+        }
+        // Acknowledge is an M1 cycle with two extra wait states
+        self.refresh();
+        self.idle(6);
+        match self.interrupt_mode {
+            // The data bus supplies an opcode, in practice a one-byte RST
+            0 => {
+                let data: u8 = self.interrupt_data;
+                self.execute_instruction(data, memory);
+            }
+            1 => {
+                self.idle(1);
+                self.call(IM1_VECTOR, memory);
+            }
+            _ => {
+                self.idle(1);
+                self.push(self.regPC, memory);
+                let entry: u16 = ((self.regI as u16) << 8) | self.interrupt_data as u16;
+                self.regPC = self.read_word(entry, memory);
+                self.memptr = self.regPC;
+            }
+        }
+        true
+    }
+
+    // Condition from its 3-bit encoding: NZ Z NC C PO PE P M
+    pub(super) fn condition(&self, code: u8) -> bool {
+        let flag: u8 = match code >> 1 {
+            0 => ZERO,
+            1 => CARRY,
+            2 => PARITY,
+            _ => SIGN,
+        };
+        (self.regF & flag != 0) == (code & 1 != 0)
+    }
+
+    // Pushes the return address and jumps, as CALL, RST and the interrupts do
+    fn call<B: Bus>(&mut self, target: u16, memory: &mut B) {
+        self.push(self.regPC, memory);
+        self.regPC = target;
+        self.memptr = target;
+    }
+
+    // JP and CALL load MEMPTR with the target whether or not the condition holds
+    pub(super) fn jp<B: Bus>(&mut self, condition: bool, memory: &mut B) {
+        let target: u16 = self.fetch_word(memory);
+        self.memptr = target;
+        if condition {
+            self.regPC = target;
+        }
+    }
+
+    pub(super) fn call_nn<B: Bus>(&mut self, condition: bool, memory: &mut B) {
+        let target: u16 = self.fetch_word(memory);
+        self.memptr = target;
+        if condition {
+            self.idle(1);
+            self.call(target, memory);
+        }
+    }
+
+    pub(super) fn ret<B: Bus>(&mut self, memory: &mut B) {
+        self.regPC = self.pop(memory);
+        self.memptr = self.regPC;
+    }
+
+    pub(super) fn ret_cc<B: Bus>(&mut self, condition: bool, memory: &mut B) {
+        self.idle(1);
+        if condition {
+            self.ret(memory);
+        }
+    }
+
+    // RETN and RETI both restore IFF1 from IFF2, RETI is only told apart by
+    // the peripherals that watch the bus for its opcode
+    pub(super) fn retn<B: Bus>(&mut self, memory: &mut B) {
+        self.iff1 = self.iff2;
+        self.ret(memory);
+    }
+
+    pub(super) fn rst<B: Bus>(&mut self, target: u16, memory: &mut B) {
+        self.idle(1);
+        self.call(target, memory);
+    }
+
+    // JR and DJNZ: the displacement is read either way, the jump adds five T-states
+    pub(super) fn jr<B: Bus>(&mut self, condition: bool, memory: &mut B) {
+        let offset: i8 = self.fetch(memory) as i8;
+        if condition {
+            self.idle(5);
+            self.regPC = self.regPC.wrapping_add(offset as u16);
+            self.memptr = self.regPC;
+        }
+    }
+
+    pub(super) fn djnz<B: Bus>(&mut self, memory: &mut B) {
+        self.idle(1);
+        self.regB = self.regB.wrapping_sub(1);
+        self.jr(self.regB != 0, memory);
+    }
+
+    pub(super) fn halt(&mut self) {
+        self.halted = true;
+    }
+
+    pub(super) fn di(&mut self) {
+        self.iff1 = false;
+        self.iff2 = false;
+    }
+
+    pub(super) fn ei(&mut self) {
+        self.iff1 = true;
+        self.iff2 = true;
+        self.interrupt_blocked = true;
+    }
+
+    // IM from the ED opcode: the undocumented encodings between the modes select 0
+    pub(super) fn im(&mut self, code: u8) {
+        self.interrupt_mode = match code & 0x03 {
+            2 => 1,
+            3 => 2,
+            _ => 0,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+
+    use super::super::{run, setup};
+
+    #[test]
+    fn mode_0_executes_the_opcode_on_the_data_bus() {
+        // EI ; NOP, with RST 08 on the bus
+        let (mut cpu, mut memory) = setup(&[0xFB, 0x00, 0x00]);
+        cpu.set_interrupt_data(0xCF);
+        cpu.irq(true);
+        // EI holds the interrupt off for one more instruction
+        run(&mut cpu, &mut memory, 2);
+        assert_eq!(cpu.pc(), 0x0002);
+        assert_eq!(run(&mut cpu, &mut memory, 1), 13);
+        assert_eq!(cpu.pc(), 0x0008);
+        assert_eq!(cpu.interrupt_mode(), 0);
+    }
+
+    #[test]
+    fn mode_1_restarts_at_0038() {
+        // IM 1 ; EI ; NOP
+        let (mut cpu, mut memory) = setup(&[0xED, 0x56, 0xFB, 0x00, 0x00]);
+        cpu.irq(true);
+        run(&mut cpu, &mut memory, 3);
+        assert_eq!(run(&mut cpu, &mut memory, 1), 13);
+        assert_eq!(cpu.pc(), 0x0038);
+        assert_eq!(cpu.sp(), 0xFEFE);
+        assert_eq!(memory[0xFEFE], 0x04);
+    }
+
+    #[test]
+    fn mode_2_jumps_through_the_vector_table() {
+        // LD A,$40 ; LD I,A ; IM 2 ; EI ; NOP
+        let (mut cpu, mut memory) = setup(&[0x3E, 0x40, 0xED, 0x47, 0xED, 0x5E, 0xFB, 0x00, 0x00]);
+        memory[0x4010] = 0x00;
+        memory[0x4011] = 0x30;
+        cpu.set_interrupt_data(0x10);
+        cpu.irq(true);
+        run(&mut cpu, &mut memory, 5);
+        assert_eq!(run(&mut cpu, &mut memory, 1), 19);
+        assert_eq!(cpu.pc(), 0x3000);
+        assert_eq!(cpu.memptr(), 0x3000);
+    }
+
+    #[test]
+    fn interrupts_stay_off_after_di() {
+        // IM 1 ; DI ; NOP
+        let (mut cpu, mut memory) = setup(&[0xED, 0x56, 0xF3, 0x00]);
+        cpu.irq(true);
+        run(&mut cpu, &mut memory, 3);
+        assert_eq!(cpu.pc(), 0x0004);
+    }
+}
@@ -0,0 +1,515 @@
+use crate::cpu::bus::Bus;
+
+use crate::cpu::cpu::CPU;
+
+use super::{Index, Z80};
+
+// Opcodes are decoded from their octal fields: x (bits 7-6), y (bits 5-3) and z (bits 2-0),
+// with y further split into p (bits 5-4) and q (bit 3)
+fn fields(opcode: u8) -> (u8, u8, u8, u8, u8) {
+    let y: u8 = (opcode >> 3) & 0x07;
+    (opcode >> 6, y, opcode & 0x07, y >> 1, y & 0x01)
+}
+
+impl Z80 {
+    pub(super) fn execute_instruction<B: Bus>(&mut self, opcode: u8, memory: &mut B) {
+        match opcode {
+            0xCB => {
+                let opcode: u8 = self.fetch_opcode(memory);
+                self.execute_bit_instruction(opcode, memory);
+            }
+            0xED => {
+                let opcode: u8 = self.fetch_opcode(memory);
+                self.execute_extended_instruction(opcode, memory);
+            }
+            0xDD | 0xFD => self.execute_indexed_instruction(opcode, memory),
+            _ => self.execute_main_instruction(opcode, memory),
+        }
+    }
+
+    // Any number of DD and FD prefixes may come before an opcode, the last one
+    // counts. On opcodes that do not involve HL a prefix only costs its M1 cycle
+    fn execute_indexed_instruction<B: Bus>(&mut self, prefix: u8, memory: &mut B) {
+        let mut prefix: u8 = prefix;
+        let mut opcode: u8 = self.fetch_opcode(memory);
+        while opcode == 0xDD || opcode == 0xFD {
+            prefix = opcode;
+            opcode = self.fetch_opcode(memory);
+        }
+        self.index = if prefix == 0xDD { Index::IX } else { Index::IY };
+        match opcode {
+            0xCB => self.execute_indexed_bit_instruction(memory),
+            0xED => {
+                self.index = Index::HL;
+                let opcode: u8 = self.fetch_opcode(memory);
+                self.execute_extended_instruction(opcode, memory);
+            }
+            _ => self.execute_main_instruction(opcode, memory),
+        }
+        self.index = Index::HL;
+    }
+
+    // IX or IY plus the signed displacement byte after the opcode
+    fn displaced_address<B: Bus>(&mut self, memory: &mut B) -> u16 {
+        let offset: i8 = self.fetch(memory) as i8;
+        let address: u16 = self.index_register().wrapping_add(offset as u16);
+        self.memptr = address;
+        address
+    }
+
+    // Address of the (HL) operand. Adding a displacement takes 5 T-states
+    fn operand_address<B: Bus>(&mut self, memory: &mut B) -> u16 {
+        if self.index == Index::HL {
+            return self.hl();
+        }
+        let address: u16 = self.displaced_address(memory);
+        self.idle(5);
+        address
+    }
+
+    // Register or (HL) operand by its 3-bit encoding
+    fn read_operand<B: Bus>(&mut self, code: u8, memory: &mut B) -> u8 {
+        if code == 6 {
+            let address: u16 = self.operand_address(memory);
+            self.read_byte(address, memory)
+        } else {
+            self.register(code)
+        }
+    }
+
+    fn modify_operand<B: Bus>(&mut self, code: u8, memory: &mut B, operation: fn(&mut Z80, u8) -> u8) {
+        if code == 6 {
+            let address: u16 = self.operand_address(memory);
+            let value: u8 = self.read_byte(address, memory);
+            self.idle(1);
+            let result: u8 = operation(self, value);
+            self.write_byte(address, result, memory);
+        } else {
+            let result: u8 = operation(self, self.register(code));
+            self.set_register(code, result);
+        }
+    }
+
+    fn execute_main_instruction<B: Bus>(&mut self, opcode: u8, memory: &mut B) {
+        let (x, y, z, p, q) = fields(opcode);
+        match (x, z) {
+            (0, 0) => match y {
+                0 => {}
+                1 => {
+                    let af: u16 = self.af();
+                    self.set_af(self.regAF_);
+                    self.regAF_ = af;
+                }
+                2 => self.djnz(memory),
+                3 => self.jr(true, memory),
+                _ => self.jr(self.condition(y - 4), memory),
+            },
+            (0, 1) => {
+                if q == 0 {
+                    let value: u16 = self.fetch_word(memory);
+                    self.set_register_pair(p, value, false);
+                } else {
+                    let result: u16 = self.add16(self.index_register(), self.register_pair(p, false));
+                    self.set_index_register(result);
+                }
+            }
+            (0, 2) => self.load_indirect(p, q == 1, memory),
+            (0, 3) => {
+                self.idle(2);
+                let step: u16 = if q == 0 { 1 } else { 0xFFFF };
+                self.set_register_pair(p, self.register_pair(p, false).wrapping_add(step), false);
+            }
+            (0, 4) => self.modify_operand(y, memory, Z80::inc),
+            (0, 5) => self.modify_operand(y, memory, Z80::dec),
+            (0, 6) => {
+                if y == 6 {
+                    // The immediate byte comes after the displacement, the address add overlaps it
+                    let address: u16 = if self.index == Index::HL { self.hl() } else { self.displaced_address(memory) };
+                    let value: u8 = self.fetch(memory);
+                    if self.index != Index::HL {
+                        self.idle(2);
+                    }
+                    self.write_byte(address, value, memory);
+                } else {
+                    let value: u8 = self.fetch(memory);
+                    self.set_register(y, value);
+                }
+            }
+            (0, _) => match y {
+                0..=3 => self.rotate_accumulator(y),
+                4 => self.daa(),
+                5 => self.cpl(),
+                6 => self.scf(),
+                _ => self.ccf(),
+            },
+            (1, _) => {
+                if y == 6 && z == 6 {
+                    self.halt();
+                } else if z == 6 {
+                    let address: u16 = self.operand_address(memory);
+                    let value: u8 = self.read_byte(address, memory);
+                    self.set_unindexed_register(y, value);
+                } else if y == 6 {
+                    let address: u16 = self.operand_address(memory);
+                    self.write_byte(address, self.unindexed_register(z), memory);
+                } else {
+                    self.set_register(y, self.register(z));
+                }
+            }
+            (2, _) => {
+                let value: u8 = self.read_operand(z, memory);
+                self.alu(y, value);
+            }
+            (_, 0) => self.ret_cc(self.condition(y), memory),
+            (_, 1) => match (q, p) {
+                (0, _) => {
+                    let value: u16 = self.pop(memory);
+                    self.set_register_pair(p, value, true);
+                }
+                (_, 0) => self.ret(memory),
+                (_, 1) => self.exx(),
+                (_, 2) => self.regPC = self.index_register(),
+                _ => {
+                    self.idle(2);
+                    self.regSP = self.index_register();
+                }
+            },
+            (_, 2) => self.jp(self.condition(y), memory),
+            (_, 3) => match y {
+                0 => self.jp(true, memory),
+                2 => {
+                    let port: u8 = self.fetch(memory);
+                    self.output(((self.regA as u16) << 8) | port as u16, self.regA, memory);
+                    self.memptr = ((self.regA as u16) << 8) | port.wrapping_add(1) as u16;
+                }
+                3 => {
+                    let port: u16 = ((self.regA as u16) << 8) | self.fetch(memory) as u16;
+                    self.regA = self.input(port, memory);
+                    self.memptr = port.wrapping_add(1);
+                }
+                4 => self.exchange_stack_top(memory),
+                5 => {
+                    let de: u16 = self.de();
+                    self.set_de(self.hl());
+                    self.set_hl(de);
+                }
+                6 => self.di(),
+                _ => self.ei(),
+            },
+            (_, 4) => self.call_nn(self.condition(y), memory),
+            (_, 5) => {
+                if q == 0 {
+                    self.idle(1);
+                    self.push(self.register_pair(p, true), memory);
+                } else {
+                    self.call_nn(true, memory);
+                }
+            }
+            (_, 6) => {
+                let value: u8 = self.fetch(memory);
+                self.alu(y, value);
+            }
+            _ => self.rst((y as u16) * 8, memory),
+        }
+    }
+
+    // LD between A or HL and memory. Stores of A leave A in the high byte of
+    // MEMPTR, everything else leaves it pointing past the address used
+    fn load_indirect<B: Bus>(&mut self, p: u8, load: bool, memory: &mut B) {
+        let address: u16 = match p {
+            0 => self.bc(),
+            1 => self.de(),
+            _ => self.fetch_word(memory),
+        };
+        let next: u16 = address.wrapping_add(1);
+        if p == 2 {
+            if load {
+                let value: u16 = self.read_word(address, memory);
+                self.set_index_register(value);
+            } else {
+                self.write_word(address, self.index_register(), memory);
+            }
+            self.memptr = next;
+        } else if load {
+            self.regA = self.read_byte(address, memory);
+            self.memptr = next;
+        } else {
+            self.write_byte(address, self.regA, memory);
+            self.memptr = ((self.regA as u16) << 8) | (next & 0x00FF);
+        }
+    }
+
+    fn exx(&mut self) {
+        let (bc, de, hl) = (self.bc(), self.de(), self.hl());
+        self.set_bc(self.regBC_);
+        self.set_de(self.regDE_);
+        self.set_hl(self.regHL_);
+        self.regBC_ = bc;
+        self.regDE_ = de;
+        self.regHL_ = hl;
+    }
+
+    // EX (SP),HL writes the high byte first
+    fn exchange_stack_top<B: Bus>(&mut self, memory: &mut B) {
+        let value: u16 = self.read_word(self.regSP, memory);
+        self.idle(1);
+        let register: u16 = self.index_register();
+        self.write_byte(self.regSP.wrapping_add(1), (register >> 8) as u8, memory);
+        self.write_byte(self.regSP, register as u8, memory);
+        self.idle(2);
+        self.set_index_register(value);
+        self.memptr = value;
+    }
+
+    // CB prefix: rotates and shifts, BIT, RES and SET
+    fn execute_bit_instruction<B: Bus>(&mut self, opcode: u8, memory: &mut B) {
+        let (x, y, z, _, _) = fields(opcode);
+        if x == 1 {
+            if z == 6 {
+                let value: u8 = self.read_byte(self.hl(), memory);
+                self.idle(1);
+                self.bit(y, value, (self.memptr >> 8) as u8);
+            } else {
+                self.bit(y, self.register(z), self.register(z));
+            }
+            return;
+        }
+        let operation: fn(&mut Z80, u8) -> u8 = match x {
+            0 => ROTATE[y as usize],
+            2 => RESET_BIT[y as usize],
+            _ => SET_BIT[y as usize],
+        };
+        self.modify_operand(z, memory, operation);
+    }
+
+    // DD CB and FD CB: the displacement comes before the opcode, and every
+    // operation works on (IX+d). Except for BIT the result is also copied to the
+    // register the low bits of the opcode name
+    fn execute_indexed_bit_instruction<B: Bus>(&mut self, memory: &mut B) {
+        let address: u16 = self.displaced_address(memory);
+        let opcode: u8 = self.fetch(memory);
+        self.idle(2);
+        let (x, y, z, _, _) = fields(opcode);
+        let value: u8 = self.read_byte(address, memory);
+        self.idle(1);
+        let result: u8 = match x {
+            0 => self.rotate(y, value),
+            1 => {
+                self.bit(y, value, (address >> 8) as u8);
+                return;
+            }
+            2 => value & !(1 << y),
+            _ => value | (1 << y),
+        };
+        self.write_byte(address, result, memory);
+        if z != 6 {
+            self.set_unindexed_register(z, result);
+        }
+    }
+
+    // ED prefix. Opcodes outside the defined ones do nothing for 8 T-states
+    fn execute_extended_instruction<B: Bus>(&mut self, opcode: u8, memory: &mut B) {
+        let (x, y, z, p, q) = fields(opcode);
+        match (x, z) {
+            (1, 0) => {
+                // IN (C) with y = 6 only sets the flags
+                let value: u8 = self.input(self.bc(), memory);
+                self.memptr = self.bc().wrapping_add(1);
+                self.input_flags(value);
+                if y != 6 {
+                    self.set_register(y, value);
+                }
+            }
+            (1, 1) => {
+                // OUT (C),0 on NMOS parts, CMOS ones output $FF
+                let value: u8 = if y == 6 { 0 } else { self.register(y) };
+                self.output(self.bc(), value, memory);
+                self.memptr = self.bc().wrapping_add(1);
+            }
+            (1, 2) => {
+                let result: u16 = if q == 0 {
+                    self.sbc16(self.hl(), self.register_pair(p, false))
+                } else {
+                    self.adc16(self.hl(), self.register_pair(p, false))
+                };
+                self.set_hl(result);
+            }
+            (1, 3) => {
+                let address: u16 = self.fetch_word(memory);
+                if q == 0 {
+                    self.write_word(address, self.register_pair(p, false), memory);
+                } else {
+                    let value: u16 = self.read_word(address, memory);
+                    self.set_register_pair(p, value, false);
+                }
+                self.memptr = address.wrapping_add(1);
+            }
+            (1, 4) => self.neg(),
+            (1, 5) => self.retn(memory),
+            (1, 6) => self.im(y),
+            (1, 7) => match y {
+                0 => {
+                    self.idle(1);
+                    self.regI = self.regA;
+                }
+                1 => {
+                    self.idle(1);
+                    self.regR = self.regA;
+                }
+                2 => {
+                    self.idle(1);
+                    self.load_interrupt_register(self.regI);
+                }
+                3 => {
+                    self.idle(1);
+                    self.load_interrupt_register(self.regR);
+                }
+                4 => self.rotate_digits(false, memory),
+                5 => self.rotate_digits(true, memory),
+                _ => {}
+            },
+            (2, 0..=3) if y >= 4 => {
+                let decrement: bool = y & 1 != 0;
+                let repeat: bool = y >= 6;
+                match z {
+                    0 => self.block_load(decrement, repeat, memory),
+                    1 => self.block_compare(decrement, repeat, memory),
+                    2 => self.block_input(decrement, repeat, memory),
+                    _ => self.block_output(decrement, repeat, memory),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+// CB operations on a value, by the y field of the opcode
+const ROTATE: [fn(&mut Z80, u8) -> u8; 8] = [
+    Z80::rotate_by::<0>,
+    Z80::rotate_by::<1>,
+    Z80::rotate_by::<2>,
+    Z80::rotate_by::<3>,
+    Z80::rotate_by::<4>,
+    Z80::rotate_by::<5>,
+    Z80::rotate_by::<6>,
+    Z80::rotate_by::<7>,
+];
+
+const RESET_BIT: [fn(&mut Z80, u8) -> u8; 8] = [
+    Z80::res::<0>,
+    Z80::res::<1>,
+    Z80::res::<2>,
+    Z80::res::<3>,
+    Z80::res::<4>,
+    Z80::res::<5>,
+    Z80::res::<6>,
+    Z80::res::<7>,
+];
+
+const SET_BIT: [fn(&mut Z80, u8) -> u8; 8] = [
+    Z80::set::<0>,
+    Z80::set::<1>,
+    Z80::set::<2>,
+    Z80::set::<3>,
+    Z80::set::<4>,
+    Z80::set::<5>,
+    Z80::set::<6>,
+    Z80::set::<7>,
+];
+
+impl Z80 {
+    fn rotate_by<const OPERATION: u8>(&mut self, value: u8) -> u8 {
+        self.rotate(OPERATION, value)
+    }
+
+    fn res<const BIT: u8>(&mut self, value: u8) -> u8 {
+        value & !(1 << BIT)
+    }
+
+    fn set<const BIT: u8>(&mut self, value: u8) -> u8 {
+        value | (1 << BIT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::cpu::CPU;
+
+    use super::super::{run, setup};
+
+    #[test]
+    fn prefixes_select_hl_ix_or_iy() {
+        // LD HL,$1111 ; LD IX,$2222 ; LD IY,$3333
+        let (mut cpu, mut memory) = setup(&[0x21, 0x11, 0x11, 0xDD, 0x21, 0x22, 0x22, 0xFD, 0x21, 0x33, 0x33]);
+        assert_eq!(run(&mut cpu, &mut memory, 1), 10);
+        assert_eq!(run(&mut cpu, &mut memory, 1), 14);
+        assert_eq!(run(&mut cpu, &mut memory, 1), 14);
+        assert_eq!((cpu.hl(), cpu.ix(), cpu.iy()), (0x1111, 0x2222, 0x3333));
+    }
+
+    #[test]
+    fn last_of_several_prefixes_counts() {
+        // DD FD LD IY,$5678, the ignored prefix costs its M1 cycle
+        let (mut cpu, mut memory) = setup(&[0xDD, 0xFD, 0x21, 0x78, 0x56]);
+        assert_eq!(run(&mut cpu, &mut memory, 1), 18);
+        assert_eq!((cpu.ix(), cpu.iy()), (0x0000, 0x5678));
+        assert_eq!(cpu.pc(), 0x0005);
+    }
+
+    #[test]
+    fn index_register_halves() {
+        // LD IXH,$12 ; LD IXL,$34 ; LD IYH,$56 ; LD IYL,IYH ; LD A,IXH ; LD H,(IX+1)
+        let (mut cpu, mut memory) = setup(&[
+            0xDD, 0x26, 0x12, 0xDD, 0x2E, 0x34, 0xFD, 0x26, 0x56, 0xFD, 0x6C, 0xDD, 0x7C, 0xDD, 0x66, 0x01,
+        ]);
+        memory[0x1235] = 0x77;
+        run(&mut cpu, &mut memory, 5);
+        assert_eq!((cpu.ix(), cpu.iy(), cpu.a()), (0x1234, 0x5656, 0x12));
+        assert_eq!(cpu.hl(), 0x0000);
+        // With a displaced operand H means H again, not IXH
+        assert_eq!(run(&mut cpu, &mut memory, 1), 19);
+        assert_eq!((cpu.hl(), cpu.ix()), (0x7700, 0x1234));
+    }
+
+    #[test]
+    fn bit_prefix_works_on_registers_and_memory() {
+        // LD B,$80 ; RLC B ; LD HL,$4000 ; SET 7,(HL) ; RES 0,(HL)
+        let (mut cpu, mut memory) = setup(&[0x06, 0x80, 0xCB, 0x00, 0x21, 0x00, 0x40, 0xCB, 0xFE, 0xCB, 0x86]);
+        memory[0x4000] = 0x01;
+        run(&mut cpu, &mut memory, 1);
+        assert_eq!(run(&mut cpu, &mut memory, 1), 8);
+        assert_eq!(cpu.bc() >> 8, 0x01);
+        assert_eq!(cpu.f() & 0x01, 0x01);
+        run(&mut cpu, &mut memory, 1);
+        assert_eq!(run(&mut cpu, &mut memory, 1), 15);
+        assert_eq!(memory[0x4000], 0x81);
+        run(&mut cpu, &mut memory, 1);
+        assert_eq!(memory[0x4000], 0x80);
+    }
+
+    #[test]
+    fn indexed_bit_operations_also_load_a_register() {
+        // LD IX,$4000 ; RLC (IX+2),B
+        let (mut cpu, mut memory) = setup(&[0xDD, 0x21, 0x00, 0x40, 0xDD, 0xCB, 0x02, 0x00]);
+        memory[0x4002] = 0x81;
+        run(&mut cpu, &mut memory, 1);
+        assert_eq!(run(&mut cpu, &mut memory, 1), 23);
+        assert_eq!(memory[0x4002], 0x03);
+        assert_eq!(cpu.bc() >> 8, 0x03);
+        assert_eq!(cpu.memptr(), 0x4002);
+    }
+
+    #[test]
+    fn extended_opcodes() {
+        // LD A,1 ; NEG ; LD I,A ; undefined ED 00
+        let (mut cpu, mut memory) = setup(&[0x3E, 0x01, 0xED, 0x44, 0xED, 0x47, 0xED, 0x00]);
+        run(&mut cpu, &mut memory, 1);
+        assert_eq!(run(&mut cpu, &mut memory, 1), 8);
+        assert_eq!(cpu.a(), 0xFF);
+        assert_eq!(run(&mut cpu, &mut memory, 1), 9);
+        assert_eq!(cpu.i(), 0xFF);
+        // Undefined ones act as two NOPs
+        assert_eq!(run(&mut cpu, &mut memory, 1), 8);
+        assert_eq!(cpu.pc(), 0x0008);
+    }
+}
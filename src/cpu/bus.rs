@@ -0,0 +1,203 @@
+use crate::cpu::memory::Memory;
+
+// Everything the processor sees on its address and data lines. Addresses are
+// as wide as the core drives them: 16 bits on the 6502 family, 24 on the 65C816
+pub trait Bus {
+    // A read is a bus cycle like any other, memory-mapped devices may react to it
+    // (clearing a status flag, advancing a FIFO)
+    fn read(&mut self, address: u32) -> u8;
+    fn write(&mut self, address: u32, value: u8);
+    // What a read would return, without its side effects. For debuggers and tracing
+    fn peek(&self, address: u32) -> u8;
+
+    // I/O request cycles, a port space separate from memory that only the Z80 has.
+    // Nothing answers by default and the data bus floats high
+    fn input(&mut self, _port: u16) -> u8 {
+        0xFF
+    }
+
+    fn output(&mut self, _port: u16, _value: u8) {}
+
+    // Address of an access the bus could not complete, such as an unmapped or
+    // protected one. The core asks after every step, or every cycle in cycle mode,
+    // and reports it as an error; the access itself went ahead with whatever the read returned
+    fn fault(&mut self) -> Option<u32> {
+        None
+    }
+}
+
+// Plain RAM, mirrored when the address space is larger than the memory
+impl Bus for Memory {
+    fn read(&mut self, address: u32) -> u8 {
+        self.peek(address)
+    }
+
+    fn write(&mut self, address: u32, value: u8) {
+        let index: usize = address as usize % self.size();
+        self[index] = value;
+    }
+
+    fn peek(&self, address: u32) -> u8 {
+        self[address as usize % self.size()]
+    }
+}
+
+// One bus cycle as seen by the tests
+#[cfg(test)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Access {
+    Read(u32),
+    Write(u32, u8),
+}
+
+// RAM that logs every access made through it, dummy reads and writes included.
+// Indexing goes straight to the memory and is not logged
+#[cfg(test)]
+pub(crate) struct RecordingBus {
+    pub(crate) memory: Memory,
+    pub(crate) accesses: Vec<Access>,
+}
+
+#[cfg(test)]
+impl RecordingBus {
+    pub(crate) fn new(memory: Memory) -> Self {
+        RecordingBus { memory, accesses : Vec::new() }
+    }
+
+    // Hands over the log and starts a new one
+    pub(crate) fn take(&mut self) -> Vec<Access> {
+        std::mem::take(&mut self.accesses)
+    }
+
+    pub(crate) fn writes(&self) -> Vec<(u32, u8)> {
+        self.accesses
+            .iter()
+            .filter_map(|access| match access {
+                Access::Write(address, value) => Some((*address, *value)),
+                Access::Read(_) => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+impl Bus for RecordingBus {
+    fn read(&mut self, address: u32) -> u8 {
+        self.accesses.push(Access::Read(address));
+        self.memory.read(address)
+    }
+
+    fn write(&mut self, address: u32, value: u8) {
+        self.accesses.push(Access::Write(address, value));
+        self.memory.write(address, value);
+    }
+
+    fn peek(&self, address: u32) -> u8 {
+        self.memory.peek(address)
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Index<usize> for RecordingBus {
+    type Output = u8;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.memory[index]
+    }
+}
+
+#[cfg(test)]
+impl std::ops::IndexMut<usize> for RecordingBus {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.memory[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Bus;
+    use crate::cpu::cpu::{Register, CPU};
+    use crate::cpu::error::ExecutionError;
+    use crate::cpu::memory::Memory;
+    use crate::cpu::MOS6502::{CoreMode, MOS6502};
+
+    // RAM with a ROM above $C000 that refuses writes, and a status
+    // register at $D000 that clears itself once read
+    struct DeviceBus {
+        memory: Memory,
+        status: u8,
+        fault: Option<u32>,
+    }
+
+    impl DeviceBus {
+        fn new(program: &[u8]) -> Self {
+            let mut memory: Memory = Memory::new();
+            for (offset, byte) in program.iter().enumerate() {
+                memory[0x0200 + offset] = *byte;
+            }
+            memory[0xFFFC] = 0x00;
+            memory[0xFFFD] = 0x02;
+            DeviceBus { memory, status : 0x80, fault : None }
+        }
+    }
+
+    impl Bus for DeviceBus {
+        fn read(&mut self, address: u32) -> u8 {
+            if address == 0xD000 {
+                return std::mem::take(&mut self.status);
+            }
+            self.memory.read(address)
+        }
+
+        fn write(&mut self, address: u32, value: u8) {
+            if address >= 0xC000 {
+                self.fault = Some(address);
+            } else {
+                self.memory.write(address, value);
+            }
+        }
+
+        fn peek(&self, address: u32) -> u8 {
+            if address == 0xD000 {
+                return self.status;
+            }
+            self.memory.peek(address)
+        }
+
+        fn fault(&mut self) -> Option<u32> {
+            self.fault.take()
+        }
+    }
+
+    #[test]
+    fn reads_can_have_side_effects() {
+        for mode in [CoreMode::Instruction, CoreMode::Cycle] {
+            // LDA $D000 ; LDX $D000
+            let mut bus: DeviceBus = DeviceBus::new(&[0xAD, 0x00, 0xD0, 0xAE, 0x00, 0xD0]);
+            let mut cpu: MOS6502 = MOS6502::new();
+            cpu.reset(&mut bus);
+            cpu.set_core_mode(mode);
+            assert_eq!(bus.peek(0xD000), 0x80);
+            cpu.run_instructions(2, &mut bus).unwrap();
+            let registers: Vec<Register> = cpu.registers();
+            let value = |name: &str| registers.iter().find(|register| register.name == name).unwrap().value;
+            assert_eq!(value("A"), 0x80);
+            assert_eq!(value("X"), 0x00);
+            assert_eq!(bus.peek(0xD000), 0x00);
+        }
+    }
+
+    #[test]
+    fn write_to_rom_is_reported() {
+        for mode in [CoreMode::Instruction, CoreMode::Cycle] {
+            // STA $C123 ; NOP
+            let mut bus: DeviceBus = DeviceBus::new(&[0x8D, 0x23, 0xC1, 0xEA]);
+            let mut cpu: MOS6502 = MOS6502::new();
+            cpu.reset(&mut bus);
+            cpu.set_core_mode(mode);
+            assert_eq!(cpu.step(&mut bus), Err(ExecutionError::BusFault { address : 0xC123 }));
+            assert_eq!(cpu.pc(), 0x0203);
+            assert!(cpu.step(&mut bus).is_ok());
+        }
+    }
+}
@@ -1,7 +1,177 @@
-use super::memory::Memory;
+use super::bus::Bus;
+use super::error::ExecutionError;
+
+// What a call to step() did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepInfo {
+    // Full address the opcode was fetched from, including the bank on cores that have one.
+    // For an interrupt or a wait state it is where execution stood
+    pub address: u32,
+    // None when the step serviced an interrupt or idled waiting for one
+    pub opcode: Option<u8>,
+    pub cycles: u32,
+}
+
+// One entry of a register file view, for tools that do not know the core they drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Register {
+    pub name: &'static str,
+    pub value: u32,
+    // Width of the register in bits
+    pub bits: u8,
+}
 
 pub trait CPU {
-    fn fetch(&mut self, memory: &Memory) -> u8;
-    fn read(&self, address: u16, memory: &Memory) -> u8;
-    fn execute(&mut self, cycles : u32, memory : &mut Memory);
+    fn fetch<B: Bus>(&mut self, memory: &mut B) -> u8;
+    fn read<B: Bus>(&mut self, address: u16, memory: &mut B) -> u8;
+    fn write<B: Bus>(&mut self, address: u16, value: u8, memory: &mut B);
+    // Executes a single instruction, or services a pending interrupt
+    fn step<B: Bus>(&mut self, memory : &mut B) -> Result<StepInfo, ExecutionError>;
+    fn execute<B: Bus>(&mut self, cycles : u32, memory : &mut B) -> Result<(), ExecutionError>;
+
+    // Reset line, brings the processor to a known state and starts it from its reset entry point
+    fn reset<B: Bus>(&mut self, memory : &mut B);
+    // True when the processor has stopped executing and only a reset can bring it back
+    fn halted(&self) -> bool;
+    // True while the processor idles until an interrupt, like WAI on the 65C02 or HALT on the Z80.
+    // step() still returns, one idle cycle at a time
+    fn waiting(&self) -> bool;
+    // Cycles elapsed since power-on, including reset and interrupt sequences
+    fn total_cycles(&self) -> u64;
+    // Program counter within the current program bank, if the core has banks
+    fn pc(&self) -> u16;
+    fn set_pc(&mut self, pc: u16);
+    // Every programmer-visible register, in the order a debugger would show them
+    fn registers(&self) -> Vec<Register>;
+
+    // Interrupt request line, level-triggered: the interrupt is taken as long as it is held active
+    fn irq(&mut self, active: bool);
+    // Non-maskable interrupt line, edge-triggered: only the transition to active raises an interrupt
+    fn nmi(&mut self, active: bool);
+
+    // Runs whole instructions until the condition holds before the next one,
+    // returns the cycles spent. An interrupt entry counts as one instruction.
+    // There is no cycle limit: a halted processor fails its next step, and
+    // that error ends the run
+    fn run_until<F, B: Bus>(&mut self, mut condition: F, memory: &mut B) -> Result<u64, ExecutionError>
+    where
+        Self: Sized,
+        F: FnMut(&Self, &B) -> bool,
+    {
+        let start: u64 = self.total_cycles();
+        while !condition(self, memory) {
+            self.step(memory)?;
+        }
+        Ok(self.total_cycles() - start)
+    }
+
+    fn run_until_pc<B: Bus>(&mut self, pc: u16, memory: &mut B) -> Result<u64, ExecutionError>
+    where
+        Self: Sized,
+    {
+        self.run_until(|cpu, _| cpu.pc() == pc, memory)
+    }
+
+    fn run_instructions<B: Bus>(&mut self, count: u64, memory: &mut B) -> Result<u64, ExecutionError>
+    where
+        Self: Sized,
+    {
+        let mut executed: u64 = 0;
+        self.run_until(
+            |_, _| {
+                executed += 1;
+                executed > count
+            },
+            memory,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CPU, Register, StepInfo};
+    use super::super::MOS6502::{MOS6502, Variant};
+    use super::super::WDC65C816::WDC65C816;
+    use super::super::memory::Memory;
+
+    // LDA #$42, NOP, WAI at $0200, the reset vector points elsewhere
+    fn program() -> Memory {
+        let mut memory: Memory = Memory::new();
+        memory[0xFFFC] = 0x00;
+        memory[0xFFFD] = 0x03;
+        memory[0x0200] = 0xA9;
+        memory[0x0201] = 0x42;
+        memory[0x0202] = 0xEA;
+        memory[0x0203] = 0xCB;
+        memory
+    }
+
+    // Only goes through the trait, so it runs any core
+    fn trace<C: CPU>(cpu: &mut C, memory: &mut Memory) -> Vec<StepInfo> {
+        cpu.reset(memory);
+        cpu.set_pc(0x0200);
+        let mut steps: Vec<StepInfo> = Vec::new();
+        while !cpu.waiting() {
+            steps.push(cpu.step(memory).unwrap());
+        }
+        steps.push(cpu.step(memory).unwrap());
+        steps
+    }
+
+    fn register(registers: &[Register], name: &str) -> u32 {
+        registers.iter().find(|register| register.name == name).unwrap().value
+    }
+
+    fn expected() -> Vec<StepInfo> {
+        vec![
+            StepInfo { address : 0x0200, opcode : Some(0xA9), cycles : 2 },
+            StepInfo { address : 0x0202, opcode : Some(0xEA), cycles : 2 },
+            StepInfo { address : 0x0203, opcode : Some(0xCB), cycles : 3 },
+            StepInfo { address : 0x0204, opcode : None, cycles : 1 },
+        ]
+    }
+
+    #[test]
+    fn trace_65c02_through_the_trait() {
+        let mut cpu: MOS6502 = MOS6502::with_variant(Variant::CMOS65C02);
+        let mut memory: Memory = program();
+        assert_eq!(trace(&mut cpu, &mut memory), expected());
+        assert!(cpu.waiting());
+        assert!(!cpu.halted());
+        assert_eq!(cpu.pc(), 0x0204);
+
+        let registers: Vec<Register> = cpu.registers();
+        assert_eq!(register(&registers, "PC"), 0x0204);
+        assert_eq!(register(&registers, "A"), 0x42);
+    }
+
+    #[test]
+    fn trace_65c816_through_the_trait() {
+        let mut cpu: WDC65C816 = WDC65C816::new();
+        let mut memory: Memory = program();
+        assert_eq!(trace(&mut cpu, &mut memory), expected());
+        assert!(cpu.waiting());
+        assert_eq!(cpu.pc(), 0x0204);
+
+        let registers: Vec<Register> = cpu.registers();
+        assert_eq!(register(&registers, "PB"), 0x00);
+        assert_eq!(register(&registers, "C") & 0xFF, 0x42);
+        assert_eq!(register(&registers, "E"), 1);
+    }
+
+    // Bit 5 is unused on the 6502 and reads as set, on the 65C816 in emulation mode as well
+    #[test]
+    fn status_register_reads_bit_5_set() {
+        let mut cpu: MOS6502 = MOS6502::new();
+        let mut memory: Memory = program();
+        cpu.reset(&mut memory);
+        cpu.set_pc(0x0200);
+        cpu.step(&mut memory).unwrap();
+        assert_eq!(cpu.status() & 0x20, 0x20);
+        assert_eq!(register(&cpu.registers(), "P") & 0x20, 0x20);
+
+        let mut cpu: WDC65C816 = WDC65C816::new();
+        cpu.reset(&mut memory);
+        assert_eq!(register(&cpu.registers(), "P") & 0x20, 0x20);
+    }
 }
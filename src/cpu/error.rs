@@ -0,0 +1,35 @@
+use std::fmt;
+
+// Addresses are as wide as the core's address bus, the 65C816 includes the program bank
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionError {
+    // Opcode with no behaviour under the current policy, pc is the address it was fetched from
+    UnknownOpcode { pc: u32, opcode: u8 },
+    // The processor is locked up and will not execute anything until it is reset
+    Jammed { pc: u32 },
+    // A 65C02 STP instruction stopped the clock, only a reset restarts it
+    Stopped { pc: u32 },
+    // The bus could not complete an access to this address
+    BusFault { address: u32 },
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionError::UnknownOpcode { pc, opcode } => {
+                write!(f, "unknown opcode {:#04X} at {:#06X}", opcode, pc)
+            }
+            ExecutionError::Jammed { pc } => {
+                write!(f, "processor jammed at {:#06X}", pc)
+            }
+            ExecutionError::Stopped { pc } => {
+                write!(f, "processor stopped at {:#06X}", pc)
+            }
+            ExecutionError::BusFault { address } => {
+                write!(f, "bus fault at {:#06X}", address)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExecutionError {}
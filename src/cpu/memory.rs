@@ -3,15 +3,32 @@ use std::ops::{Index, IndexMut};
 const MAX_MEM: usize = 1024 * 64;
 
 pub struct Memory {
-    data: [u8; MAX_MEM],
+    data: Vec<u8>,
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Memory::new()
+    }
 }
 
 impl Memory {
     pub fn new() -> Self {
+        Memory::with_size(MAX_MEM)
+    }
+
+    // Flat RAM of any size, e.g. 16 MiB for the 24-bit address space of the 65C816.
+    // Smaller sizes are mirrored across the address space, so there must be at least one byte
+    pub fn with_size(size: usize) -> Self {
+        assert!(size > 0, "memory size must be at least one byte");
         Memory {
-            data: [0; MAX_MEM],
+            data: vec![0; size],
         }
     }
+
+    pub fn size(&self) -> usize {
+        self.data.len()
+    }
 }
 
 impl Index<usize> for Memory {
@@ -27,3 +44,14 @@ impl IndexMut<usize> for Memory {
         &mut self.data[index]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Memory;
+
+    #[test]
+    #[should_panic(expected = "memory size must be at least one byte")]
+    fn empty_memory_is_rejected() {
+        Memory::with_size(0);
+    }
+}
@@ -0,0 +1,4 @@
+#![allow(non_snake_case, non_camel_case_types, clippy::upper_case_acronyms, clippy::module_inception)]
+
+pub mod cpu;
+pub mod cpm;
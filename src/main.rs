@@ -1,15 +1,32 @@
-mod cpu;
-
-use cpu::cpu::CPU;
-use cpu::MOS6502::MOS6502;
-use cpu::memory::Memory;
+use von_rustmann::cpm;
+use von_rustmann::cpu::cpu::CPU;
+use von_rustmann::cpu::MOS6502::MOS6502;
+use von_rustmann::cpu::memory::Memory;
 
 fn main() {
+    // A CP/M .COM file given on the command line runs on the Z80
+    if let Some(path) = std::env::args().nth(1) {
+        match std::fs::read(&path) {
+            Ok(program) => match cpm::run(&program, &mut std::io::stdout()) {
+                Ok(cpm::Exit::WarmBoot { cycles }) => println!("\n{} T-states", cycles),
+                Ok(cpm::Exit::Halted { pc, cycles }) => println!("\nhalted at {:#06X} after {} T-states", pc, cycles),
+                Err(error) => println!("\n{}", error),
+            },
+            Err(error) => println!("{}: {}", path, error),
+        }
+        return;
+    }
+
     let mut my_cpu = MOS6502::new();
     let mut memory = Memory::new();
-    memory[0xFFFC] = 0xA5;
-    memory[0xFFFD] = 0x84;
+    memory[0xFFFC] = 0x00;
+    memory[0xFFFD] = 0x02;
+    memory[0x0200] = 0xA5;
+    memory[0x0201] = 0x84;
     memory[0x0084] = 0x42;
 
-    my_cpu.execute(3, &mut memory);
-}
\ No newline at end of file
+    my_cpu.reset(&mut memory);
+    if let Err(error) = my_cpu.execute(3, &mut memory) {
+        println!("{}", error);
+    }
+}